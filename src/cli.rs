@@ -0,0 +1,461 @@
+//! The commands of the app which run without its window.
+//!
+//! `vault run --env DB_PASS=entry:prod-db/password -- ./deploy.sh` starts a
+//! program with secrets of the vault inside its environment. The values are
+//! only passed on to the program, they are never written to a file or the
+//! terminal.
+//!
+//! `vault inject -i config.tpl -o config.yml` fills in placeholders like
+//! `{{ vault "GitHub" "password" }}` and writes a file only the current user
+//! can read.
+//!
+//! `vault git-credential get` speaks the protocol of git credential helpers,
+//! so git takes the logins of a site from the vault and stores new ones there.
+//!
+//! `vault docker-credential get` does the same for docker, with the logins
+//! tagged as `registry`. Docker runs `docker-credential-vault`, which can be a
+//! link to the app.
+
+use std::{
+    collections::HashSet,
+    fs, io,
+    path::{Path, PathBuf},
+    process,
+};
+
+use crate::{
+    cryptography::decrypt_text,
+    data_base::{
+        audit::{AuditAction, AuditLog},
+        get::get_master,
+        repository::Repository,
+        store::open_store,
+    },
+    error::{CliError, InternalError},
+    helpers::{enter_app_folder, write_private, Secret},
+    logging::{self, LogLevel},
+};
+
+mod docker;
+mod git;
+mod template;
+mod test;
+
+pub use docker::{DockerAction, DockerCredential, DockerRequest, REGISTRY_TAG};
+pub use git::{GitAction, GitCredential};
+
+pub const RUN: &str = "run";
+pub const INJECT: &str = "inject";
+pub const GIT_CREDENTIAL: &str = "git-credential";
+pub const DOCKER_CREDENTIAL: &str = "docker-credential";
+/// The name docker looks for the helper by.
+pub const DOCKER_HELPER: &str = "docker-credential-vault";
+
+/// A field of an entry, written as `entry:<entry>/<field>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reference {
+    pub entry: String,
+    pub field: String,
+}
+impl Reference {
+    /// The name of the entry can hold `/` itself, the field can not.
+    pub fn parse(text: &str) -> Option<Self> {
+        let (entry, field) = text.strip_prefix("entry:")?.rsplit_once('/')?;
+        if entry.is_empty() || field.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            entry: String::from(entry),
+            field: String::from(field),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Starts the program with the referenced values inside its environment.
+    Run {
+        env: Vec<(String, Reference)>,
+        program: Vec<String>,
+    },
+    /// Fills in the placeholders of the template and writes the result.
+    Inject { input: PathBuf, output: PathBuf },
+    /// Answers git, `None` for actions which are ignored.
+    GitCredential(Option<GitAction>),
+    /// Answers docker.
+    DockerCredential(DockerAction),
+}
+
+/// The command the app was started with. `None` if it was started without
+/// one, which opens the window.
+pub fn command(args: &[String]) -> Option<Result<Command, InternalError>> {
+    // started by docker through a link, with the action as the only argument
+    let name = args
+        .first()
+        .and_then(|a| Path::new(a).file_stem())
+        .and_then(|n| n.to_str());
+    if name == Some(DOCKER_HELPER) {
+        return Some(parse_docker(&args[1..]));
+    }
+
+    match args.get(1).map(String::as_str) {
+        Some(RUN) => Some(parse_run(&args[2..])),
+        Some(INJECT) => Some(parse_inject(&args[2..])),
+        Some(GIT_CREDENTIAL) => Some(match &args[2..] {
+            [action] => Ok(Command::GitCredential(GitAction::parse(action))),
+            other => Err(CliError::UnknownArgument(other.join(" ")).into()),
+        }),
+        Some(DOCKER_CREDENTIAL) => Some(parse_docker(&args[2..])),
+        _ => None,
+    }
+}
+
+fn parse_docker(args: &[String]) -> Result<Command, InternalError> {
+    match args {
+        [action] => match DockerAction::parse(action) {
+            Some(action) => Ok(Command::DockerCredential(action)),
+            None => Err(CliError::UnknownArgument(action.clone()).into()),
+        },
+        other => Err(CliError::UnknownArgument(other.join(" ")).into()),
+    }
+}
+
+/// The options come first, the program starts after `--` or with the first
+/// argument which is no option.
+fn parse_run(args: &[String]) -> Result<Command, InternalError> {
+    let mut env = Vec::<(String, Reference)>::new();
+    let mut args = args.iter().peekable();
+    while let Some(&arg) = args.peek() {
+        let value = match (arg.as_str(), arg.strip_prefix("--env=")) {
+            ("--", _) => {
+                args.next();
+                break;
+            }
+            ("--env" | "-e", _) => {
+                args.next();
+                match args.next() {
+                    Some(value) => value.as_str(),
+                    None => return Err(CliError::InvalidReference(String::new()).into()),
+                }
+            }
+            (_, Some(value)) => {
+                args.next();
+                value
+            }
+            (other, None) if other.starts_with('-') => {
+                return Err(CliError::UnknownArgument(String::from(other)).into())
+            }
+            _ => break,
+        };
+        env.push(parse_env(value)?);
+    }
+
+    let program = args.cloned().collect::<Vec<String>>();
+    if program.is_empty() {
+        return Err(CliError::MissingProgram.into());
+    }
+
+    Ok(Command::Run { env, program })
+}
+
+fn parse_inject(args: &[String]) -> Result<Command, InternalError> {
+    let (mut input, mut output) = (None, None);
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-i" | "--input" => input = args.next().map(PathBuf::from),
+            "-o" | "--output" => output = args.next().map(PathBuf::from),
+            other => return Err(CliError::UnknownArgument(String::from(other)).into()),
+        }
+    }
+
+    match (input, output) {
+        (Some(input), Some(output)) => Ok(Command::Inject { input, output }),
+        _ => Err(CliError::MissingFile.into()),
+    }
+}
+
+/// Parses `NAME=entry:<entry>/<field>`.
+fn parse_env(text: &str) -> Result<(String, Reference), InternalError> {
+    let parsed = text
+        .split_once('=')
+        .filter(|(name, _)| !name.is_empty())
+        .and_then(|(name, reference)| Some((String::from(name), Reference::parse(reference)?)));
+
+    match parsed {
+        Some(parsed) => Ok(parsed),
+        None => Err(CliError::InvalidReference(String::from(text)).into()),
+    }
+}
+
+/// Runs the command and returns the exit code of the app.
+///
+/// The data of the app is read from its folder, so it can be started from
+/// anywhere. Programs are started and paths are read in the folder the app
+/// was started in.
+pub fn execute(command: Command) -> Result<i32, InternalError> {
+    let working_dir = match enter_app_folder() {
+        Ok(dir) => dir,
+        Err(err) => return Err(CliError::AppFolder(err).into()),
+    };
+    logging::init(LogLevel::default());
+
+    match command {
+        Command::Run { env, program } => run(&env, &program, &working_dir),
+        Command::Inject { input, output } => {
+            inject(&working_dir.join(input), &working_dir.join(output))
+        }
+        Command::GitCredential(Some(action)) => git_credential(action),
+        Command::GitCredential(None) => Ok(0),
+        Command::DockerCredential(action) => Ok(docker_credential(action)),
+    }
+}
+
+/// Asks for the master password on the terminal and opens the entries.
+fn unlock() -> Result<Repository, InternalError> {
+    verify_master()?;
+    open_vault()
+}
+
+fn verify_master() -> Result<(), InternalError> {
+    let master = decrypt_text(&get_master()?, true, true)?;
+    let given = match rpassword::prompt_password("Master password: ") {
+        Ok(p) => p,
+        Err(err) => return Err(CliError::Prompt(err).into()),
+    };
+    if given != master {
+        log::warn!("Command line login with a wrong master password");
+        return Err(CliError::WrongMaster.into());
+    }
+
+    Ok(())
+}
+
+fn open_vault() -> Result<Repository, InternalError> {
+    Ok(Repository::open(open_store()?)?.with_audit(AuditLog::open_default()))
+}
+
+/// Finds the entries of the references and **decrypts** the values. Returns
+/// the id of the entry together with the value, in the order of the
+/// references.
+pub fn resolve(
+    repository: &Repository,
+    references: &[&Reference],
+) -> Result<Vec<(String, Secret<String>)>, InternalError> {
+    let mut values = Vec::<(String, Secret<String>)>::new();
+    for reference in references {
+        let matching = repository
+            .list()
+            .into_iter()
+            .filter(|e| e.name() == reference.entry)
+            .collect::<Vec<_>>();
+        let details = match matching.as_slice() {
+            [entry] => entry.get_details()?,
+            [] => {
+                let mut available = repository
+                    .list()
+                    .iter()
+                    .map(|e| e.name())
+                    .collect::<Vec<String>>();
+                available.sort();
+                return Err(CliError::UnknownEntry {
+                    name: reference.entry.clone(),
+                    available,
+                }
+                .into());
+            }
+            _ => return Err(CliError::AmbiguousEntry(reference.entry.clone()).into()),
+        };
+
+        let payload = details.payload.expose();
+        match payload.field(&reference.field) {
+            Some(value) => values.push((details.id.clone(), Secret::new(String::from(value)))),
+            None => {
+                return Err(CliError::UnknownField {
+                    entry: reference.entry.clone(),
+                    field: reference.field.clone(),
+                    available: payload
+                        .kind()
+                        .fields()
+                        .iter()
+                        .map(|f| String::from(f.label))
+                        .collect(),
+                }
+                .into())
+            }
+        }
+    }
+
+    Ok(values)
+}
+
+/// Records the use of the entries inside the audit log, each entry once.
+fn audit_use(repository: &mut Repository, values: &[(String, Secret<String>)], client: &str) {
+    let mut seen = HashSet::<&str>::new();
+    for (id, _) in values {
+        if seen.insert(id) {
+            repository.audit_for_client(AuditAction::ReadByCommandLine, Some(id), client);
+            if let Err(err) = repository.mark_used(id) {
+                log::warn!("Failed to store the last use of {}: {:?}", id, err);
+            }
+        }
+    }
+}
+
+fn run(
+    env: &[(String, Reference)],
+    program: &[String],
+    working_dir: &Path,
+) -> Result<i32, InternalError> {
+    let mut repository = unlock()?;
+    let references = env.iter().map(|(_, r)| r).collect::<Vec<&Reference>>();
+    let values = resolve(&repository, &references)?;
+    audit_use(&mut repository, &values, &program[0]);
+
+    if let Err(err) = std::env::set_current_dir(working_dir) {
+        return Err(CliError::AppFolder(err).into());
+    }
+    let mut child = process::Command::new(&program[0]);
+    child.args(&program[1..]);
+    for ((name, _), (_, value)) in env.iter().zip(&values) {
+        child.env(name, value.expose());
+    }
+    log::info!("Running {} with {} secrets", program[0], values.len());
+
+    exec(child)
+}
+
+/// Replaces the app with the program, so the values only live on inside of
+/// it. Only returns if the program could not be started.
+#[cfg(unix)]
+fn exec(mut child: process::Command) -> Result<i32, InternalError> {
+    use std::os::unix::process::CommandExt;
+
+    Err(CliError::Spawn(child.exec()).into())
+}
+
+/// Runs the program and passes on its exit code.
+#[cfg(not(unix))]
+fn exec(mut child: process::Command) -> Result<i32, InternalError> {
+    match child.status() {
+        Ok(status) => Ok(status.code().unwrap_or(1)),
+        Err(err) => Err(CliError::Spawn(err).into()),
+    }
+}
+
+/// The template is checked before the master password is asked for.
+fn inject(input: &Path, output: &Path) -> Result<i32, InternalError> {
+    let template = match fs::read_to_string(input) {
+        Ok(t) => t,
+        Err(err) => return Err(CliError::ReadTemplate(err).into()),
+    };
+    let placeholders = template::placeholders(&template)?;
+
+    let mut repository = unlock()?;
+    let references = placeholders
+        .iter()
+        .map(|p| &p.reference)
+        .collect::<Vec<&Reference>>();
+    let values = resolve(&repository, &references)?;
+    let rendered = template::render(
+        &template,
+        &placeholders,
+        &values.iter().map(|(_, v)| v).collect::<Vec<_>>(),
+    )?;
+
+    if let Err(err) = write_private(output, rendered.expose().as_bytes()) {
+        return Err(CliError::WriteOutput(err).into());
+    }
+    audit_use(&mut repository, &values, INJECT);
+    log::info!(
+        "Filled in {} placeholders of {}",
+        placeholders.len(),
+        input.display()
+    );
+
+    Ok(0)
+}
+
+/// Git passes the request on stdin and reads the answer from stdout. The
+/// master password is only asked for if a login is sent or changed.
+fn git_credential(action: GitAction) -> Result<i32, InternalError> {
+    let request = match GitCredential::read(&mut io::stdin().lock()) {
+        Ok(r) => r,
+        Err(err) => return Err(CliError::Git(err).into()),
+    };
+
+    let mut repository = open_vault()?;
+    if !git::needs_unlock(action, &request, &repository)? {
+        return Ok(0);
+    }
+    verify_master()?;
+    git::answer(action, &request, &mut repository, &mut io::stdout().lock())?;
+
+    Ok(0)
+}
+
+/// Docker passes the request on stdin and reads the answer from stdout,
+/// where it also expects the errors. The master password is only asked for if
+/// a login is sent, changed or deleted.
+fn docker_credential(action: DockerAction) -> i32 {
+    let answered = DockerRequest::read(action, &mut io::stdin().lock()).and_then(|request| {
+        let mut repository = open_vault()?;
+        if docker::needs_unlock(&request, &repository)? {
+            verify_master()?;
+        }
+        docker::answer(&request, &mut repository, &mut io::stdout().lock())
+    });
+
+    match answered {
+        Ok(_) => 0,
+        Err(err) => {
+            log::warn!("Failed to answer docker: {:?}", err);
+            println!("{}", docker::message(&err));
+            1
+        }
+    }
+}
+
+/// The error together with what the user needs to fix it, e.g. the names of
+/// all entries if the referenced one does not exist.
+pub fn report(err: &InternalError) -> String {
+    let details = match err {
+        InternalError::Cli(CliError::UnknownArgument(arg)) => format!("\n  {}", arg),
+        InternalError::Cli(CliError::InvalidReference(text)) if !text.is_empty() => {
+            format!("\n  {}", text)
+        }
+        InternalError::Cli(CliError::UnknownEntry { name, available }) => {
+            format!("\n  \"{}\"\nThe entries are:{}", name, list(available))
+        }
+        InternalError::Cli(CliError::AmbiguousEntry(name)) => format!("\n  \"{}\"", name),
+        InternalError::Cli(CliError::UnknownField {
+            entry,
+            field,
+            available,
+        }) => format!(
+            "\n  \"{}\" of \"{}\"\nThe fields are:{}",
+            field,
+            entry,
+            list(available)
+        ),
+        InternalError::Cli(CliError::InvalidPlaceholder(line)) => format!("\n  line {}", line),
+        InternalError::Cli(
+            CliError::ReadTemplate(err)
+            | CliError::WriteOutput(err)
+            | CliError::Spawn(err)
+            | CliError::Git(err)
+            | CliError::Docker(err),
+        ) => format!("\n  {}", err),
+        _ => String::new(),
+    };
+
+    format!("{}{}", err, details)
+}
+
+fn list(names: &[String]) -> String {
+    match names.is_empty() {
+        true => String::from(" none"),
+        false => names.iter().map(|n| format!("\n  {}", n)).collect(),
+    }
+}
@@ -0,0 +1,265 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::{Read, Write},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    data_base::{audit::AuditAction, kind::EntryKind, repository::Repository},
+    error::{CliError, InternalError},
+    helpers::{url_origin, Secret},
+};
+
+use super::report;
+
+/// The tag of the logins docker uses.
+pub const REGISTRY_TAG: &str = "registry";
+
+/// What docker asks the helper to do.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DockerAction {
+    /// Send the login of the registry.
+    Get,
+    /// Remember the login of `docker login`.
+    Store,
+    /// Forget the login after `docker logout`.
+    Erase,
+    /// Send the usernames of all registries.
+    List,
+}
+impl DockerAction {
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "get" => Some(DockerAction::Get),
+            "store" => Some(DockerAction::Store),
+            "erase" => Some(DockerAction::Erase),
+            "list" => Some(DockerAction::List),
+            _ => None,
+        }
+    }
+}
+
+/// A login as docker sends and reads it.
+#[derive(Deserialize, Serialize, Clone, PartialEq)]
+pub struct DockerCredential {
+    #[serde(rename = "ServerURL")]
+    pub server_url: String,
+    #[serde(rename = "Username")]
+    pub username: String,
+    /// The **decrypted** password, or the token of the registry.
+    #[serde(rename = "Secret")]
+    pub secret: String,
+}
+impl fmt::Debug for DockerCredential {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "DockerCredential {{ server_url: {:?}, username: {:?}, .. }}",
+            self.server_url, self.username
+        )
+    }
+}
+
+/// The request together with what docker passed on stdin.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DockerRequest {
+    /// The address of the registry.
+    Get(String),
+    Store(DockerCredential),
+    /// The address of the registry.
+    Erase(String),
+    List,
+}
+impl DockerRequest {
+    /// `get` and `erase` pass the address of the registry, `store` the login
+    /// as JSON and `list` nothing.
+    pub fn read(action: DockerAction, input: &mut impl Read) -> Result<Self, InternalError> {
+        let mut text = String::new();
+        if action != DockerAction::List {
+            if let Err(err) = input.read_to_string(&mut text) {
+                return Err(CliError::Docker(err).into());
+            }
+        }
+
+        let request = match action {
+            DockerAction::Get => DockerRequest::Get(String::from(text.trim())),
+            DockerAction::Erase => DockerRequest::Erase(String::from(text.trim())),
+            DockerAction::List => DockerRequest::List,
+            DockerAction::Store => match serde_json::from_str(&text) {
+                Ok(credential) => DockerRequest::Store(credential),
+                Err(_) => return Err(CliError::InvalidCredential.into()),
+            },
+        };
+        match &request {
+            DockerRequest::Get(server) | DockerRequest::Erase(server)
+                if url_origin(server).is_none() =>
+            {
+                Err(CliError::InvalidCredential.into())
+            }
+            DockerRequest::Store(credential) if url_origin(&credential.server_url).is_none() => {
+                Err(CliError::InvalidCredential.into())
+            }
+            _ => Ok(request),
+        }
+    }
+}
+
+/// A login of a registry, with its **decrypted** values.
+struct Registry {
+    id: String,
+    url: String,
+    username: String,
+    password: Secret<String>,
+}
+
+/// The logins tagged as registries. If an address is given, only the ones
+/// of its registry are kept.
+fn registries(
+    repository: &Repository,
+    server: Option<&str>,
+) -> Result<Vec<Registry>, InternalError> {
+    let origin = server.and_then(url_origin);
+
+    let mut found = Vec::<Registry>::new();
+    for entry in repository.find_by_tag(REGISTRY_TAG) {
+        if entry.kind() != EntryKind::Login {
+            continue;
+        }
+        if server.is_some() && url_origin(&entry.url()) != origin {
+            continue;
+        }
+        let details = entry.get_details()?;
+        found.push(Registry {
+            id: details.id.clone(),
+            url: details.url.clone(),
+            username: details.user_name.expose().clone(),
+            password: details.password.clone(),
+        });
+    }
+
+    Ok(found)
+}
+
+/// If answering the request sends, changes or deletes a login, which needs
+/// the master password. Docker stores the login after every `docker login`,
+/// which usually changes nothing.
+pub fn needs_unlock(
+    request: &DockerRequest,
+    repository: &Repository,
+) -> Result<bool, InternalError> {
+    Ok(match request {
+        DockerRequest::Get(server) | DockerRequest::Erase(server) => {
+            !registries(repository, Some(server))?.is_empty()
+        }
+        DockerRequest::List => !registries(repository, None)?.is_empty(),
+        DockerRequest::Store(credential) => !registries(repository, Some(&credential.server_url))?
+            .iter()
+            .any(|r| {
+                r.username == credential.username && r.password.expose() == &credential.secret
+            }),
+    })
+}
+
+/// Answers the request. `get` and `erase` fail with
+/// [`CliError::CredentialsNotFound`] if the vault has no login of the
+/// registry.
+pub fn answer(
+    request: &DockerRequest,
+    repository: &mut Repository,
+    output: &mut impl Write,
+) -> Result<(), InternalError> {
+    match request {
+        DockerRequest::Get(server) => {
+            let found = registries(repository, Some(server))?;
+            let login = match found.first() {
+                Some(l) => l,
+                None => return Err(CliError::CredentialsNotFound.into()),
+            };
+            let credential = DockerCredential {
+                server_url: server.clone(),
+                username: login.username.clone(),
+                secret: login.password.expose().clone(),
+            };
+            write_json(output, &credential)?;
+            repository.audit_for_client(AuditAction::ReadByCommandLine, Some(&login.id), "docker");
+            if let Err(err) = repository.mark_used(&login.id) {
+                log::warn!("Failed to store the last use of {}: {:?}", login.id, err);
+            }
+        }
+        DockerRequest::Store(credential) => {
+            let found = registries(repository, Some(&credential.server_url))?;
+            match found.first() {
+                Some(login)
+                    if login.username == credential.username
+                        && login.password.expose() == &credential.secret => {}
+                Some(login) => {
+                    let name = repository
+                        .get(&login.id)
+                        .map(|e| e.name())
+                        .unwrap_or_default();
+                    repository.update(
+                        &login.id,
+                        &name,
+                        &credential.username,
+                        &credential.secret,
+                    )?;
+                    log::info!("Changed the login of {} for docker", login.id);
+                }
+                None => {
+                    // the origin without its scheme, e.g. `ghcr.io`
+                    let name = url_origin(&credential.server_url)
+                        .and_then(|o| o.split_once("://").map(|(_, host)| String::from(host)))
+                        .unwrap_or_else(|| credential.server_url.clone());
+                    let id = repository
+                        .add(&name, &credential.username, &credential.secret)?
+                        .id();
+                    repository.set_url(&id, &credential.server_url)?;
+                    repository.set_tags(&id, &[REGISTRY_TAG])?;
+                    log::info!("Stored a login for {} from docker", name);
+                }
+            }
+        }
+        DockerRequest::Erase(server) => {
+            let found = registries(repository, Some(server))?;
+            if found.is_empty() {
+                return Err(CliError::CredentialsNotFound.into());
+            }
+            for login in found {
+                repository.delete(&login.id)?;
+                log::info!("Moved {} into the trash for docker", login.id);
+            }
+        }
+        DockerRequest::List => {
+            let listed = registries(repository, None)?
+                .into_iter()
+                .map(|r| (r.url, r.username))
+                .collect::<BTreeMap<String, String>>();
+            write_json(output, &listed)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// What docker is told about the error. It only sees that the login is
+/// missing if the message is exactly the one of [`CliError::CredentialsNotFound`],
+/// without the code.
+pub fn message(err: &InternalError) -> String {
+    match err {
+        InternalError::Cli(err @ CliError::CredentialsNotFound) => err.to_string(),
+        other => report(other),
+    }
+}
+
+fn write_json(output: &mut impl Write, value: &impl Serialize) -> Result<(), InternalError> {
+    let written = serde_json::to_writer(&mut *output, value)
+        .map_err(std::io::Error::from)
+        .and_then(|_| output.write_all(b"\n"))
+        .and_then(|_| output.flush());
+    match written {
+        Ok(_) => Ok(()),
+        Err(err) => Err(CliError::Docker(err).into()),
+    }
+}
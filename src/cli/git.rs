@@ -0,0 +1,208 @@
+use std::io::{self, BufRead, Write};
+
+use crate::{
+    data_base::{audit::AuditAction, kind::EntryKind, repository::Repository},
+    error::{CliError, InternalError},
+    helpers::{url_origin, Secret},
+};
+
+/// What git asks the helper to do.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GitAction {
+    /// Send the login of the site.
+    Get,
+    /// Remember the login, which git just used successfully.
+    Store,
+    /// Forget the login, which git just saw rejected.
+    Erase,
+}
+impl GitAction {
+    /// `None` for actions of newer versions of git, which helpers ignore.
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "get" => Some(GitAction::Get),
+            "store" => Some(GitAction::Store),
+            "erase" => Some(GitAction::Erase),
+            _ => None,
+        }
+    }
+}
+
+/// The attributes git describes a login with.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GitCredential {
+    pub protocol: String,
+    /// The host, together with the port if it is not the default one.
+    pub host: String,
+    pub username: Option<String>,
+    pub password: Option<Secret<String>>,
+}
+impl GitCredential {
+    /// Reads `key=value` lines until an empty line or the end of the input.
+    /// Unknown attributes are ignored.
+    pub fn read(input: &mut impl BufRead) -> io::Result<Self> {
+        let mut credential = GitCredential::default();
+        let mut url = None;
+        for line in input.lines() {
+            let line = line?;
+            if line.is_empty() {
+                break;
+            }
+            match line.split_once('=') {
+                Some(("protocol", value)) => credential.protocol = String::from(value),
+                Some(("host", value)) => credential.host = String::from(value),
+                Some(("username", value)) => credential.username = Some(String::from(value)),
+                Some(("password", value)) => {
+                    credential.password = Some(Secret::new(String::from(value)))
+                }
+                Some(("url", value)) => url = url_origin(value),
+                _ => {}
+            }
+        }
+
+        // the url only fills in what is missing
+        if let Some((protocol, host)) = url.as_deref().and_then(|u| u.split_once("://")) {
+            if credential.protocol.is_empty() && credential.host.is_empty() {
+                credential.protocol = String::from(protocol);
+                credential.host = String::from(host);
+            }
+        }
+
+        Ok(credential)
+    }
+
+    /// The origin of the site, which the website of the logins has to match.
+    fn origin(&self) -> Option<String> {
+        if self.protocol.is_empty() || self.host.is_empty() {
+            return None;
+        }
+
+        url_origin(&format!("{}://{}", self.protocol, self.host))
+    }
+}
+
+/// A login of the site, with its **decrypted** values.
+struct Match {
+    id: String,
+    username: String,
+    password: Secret<String>,
+}
+
+/// The logins of the site. If git names a user, only their logins are kept.
+fn matching(repository: &Repository, request: &GitCredential) -> Result<Vec<Match>, InternalError> {
+    let origin = match request.origin() {
+        Some(o) => o,
+        None => return Ok(Vec::new()),
+    };
+
+    let mut found = Vec::<Match>::new();
+    for entry in repository.find_by_origin(&origin) {
+        if entry.kind() != EntryKind::Login {
+            continue;
+        }
+        let details = entry.get_details()?;
+        let username = details.user_name.expose();
+        if request.username.as_ref().is_some_and(|u| u != username) {
+            continue;
+        }
+        found.push(Match {
+            id: details.id.clone(),
+            username: username.clone(),
+            password: details.password.clone(),
+        });
+    }
+
+    Ok(found)
+}
+
+/// If answering the request sends, changes or deletes a login, which needs
+/// the master password. Git stores every login after using it, which
+/// usually changes nothing.
+pub fn needs_unlock(
+    action: GitAction,
+    request: &GitCredential,
+    repository: &Repository,
+) -> Result<bool, InternalError> {
+    let found = matching(repository, request)?;
+
+    Ok(match action {
+        GitAction::Get | GitAction::Erase => !found.is_empty(),
+        GitAction::Store => match (&request.username, &request.password) {
+            (Some(_), Some(password)) => !found.iter().any(|m| &m.password == password),
+            _ => false,
+        },
+    })
+}
+
+/// Answers the request. Only `get` writes anything, the username and
+/// password of the first matching login.
+pub fn answer(
+    action: GitAction,
+    request: &GitCredential,
+    repository: &mut Repository,
+    output: &mut impl Write,
+) -> Result<(), InternalError> {
+    let found = matching(repository, request)?;
+    match action {
+        GitAction::Get => {
+            // values with a line break would break the protocol
+            let login = found
+                .iter()
+                .find(|m| !m.username.contains('\n') && !m.password.expose().contains('\n'));
+            if let Some(login) = login {
+                let written = write!(
+                    output,
+                    "username={}\npassword={}\n",
+                    login.username,
+                    login.password.expose()
+                );
+                if let Err(err) = written.and_then(|_| output.flush()) {
+                    return Err(CliError::Git(err).into());
+                }
+                repository.audit_for_client(AuditAction::ReadByCommandLine, Some(&login.id), "git");
+                if let Err(err) = repository.mark_used(&login.id) {
+                    log::warn!("Failed to store the last use of {}: {:?}", login.id, err);
+                }
+            }
+        }
+        GitAction::Store => {
+            let (username, password, origin) =
+                match (&request.username, &request.password, request.origin()) {
+                    (Some(u), Some(p), Some(o)) => (u, p, o),
+                    _ => return Ok(()),
+                };
+            match found.first() {
+                Some(login) if &login.password == password => {}
+                Some(login) => {
+                    let name = repository
+                        .get(&login.id)
+                        .map(|e| e.name())
+                        .unwrap_or_default();
+                    repository.update(&login.id, &name, username, password.expose())?;
+                    log::info!("Changed the password of {} for git", login.id);
+                }
+                None => {
+                    let id = repository
+                        .add(&request.host, username, password.expose())?
+                        .id();
+                    repository.set_url(&id, &origin)?;
+                    log::info!("Stored a login for {} from git", origin);
+                }
+            }
+        }
+        GitAction::Erase => {
+            for login in found {
+                if request
+                    .password
+                    .as_ref()
+                    .is_none_or(|p| p == &login.password)
+                {
+                    repository.delete(&login.id)?;
+                    log::info!("Moved {} into the trash for git", login.id);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,149 @@
+use std::ops::Range;
+
+use crate::{
+    error::{CliError, InternalError},
+    helpers::Secret,
+};
+
+use super::Reference;
+
+/// A placeholder like `{{ vault "GitHub" "password" }}`, with where it is
+/// inside the template.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Placeholder {
+    pub range: Range<usize>,
+    pub reference: Reference,
+}
+
+/// Finds all placeholders of the vault. Other placeholders, like the ones of
+/// Helm or Jinja, are left alone.
+pub fn placeholders(template: &str) -> Result<Vec<Placeholder>, InternalError> {
+    let mut found = Vec::<Placeholder>::new();
+    let mut rest = 0;
+    while let Some(start) = template[rest..].find("{{").map(|i| rest + i) {
+        // other placeholders are only stepped over, so one which is never
+        // closed does not hide the placeholders of the vault after it
+        if !opens_vault(&template[start + 2..]) {
+            rest = start + 2;
+            continue;
+        }
+        let end = match closing(&template[start + 2..]) {
+            Some(i) => start + 2 + i + 2,
+            None => return Err(CliError::InvalidPlaceholder(line_of(template, start)).into()),
+        };
+        rest = end;
+
+        let arguments = template[start + 2..end - 2]
+            .trim()
+            .strip_prefix("vault")
+            .unwrap_or_default();
+        match quoted(arguments).as_deref() {
+            Some([entry, field]) if !entry.is_empty() && !field.is_empty() => {
+                found.push(Placeholder {
+                    range: start..end,
+                    reference: Reference {
+                        entry: entry.clone(),
+                        field: field.clone(),
+                    },
+                })
+            }
+            _ => return Err(CliError::InvalidPlaceholder(line_of(template, start)).into()),
+        }
+    }
+
+    Ok(found)
+}
+
+/// Whether the text after `{{` is a placeholder of the vault, which starts
+/// with the word `vault`.
+fn opens_vault(text: &str) -> bool {
+    match text.trim_start().strip_prefix("vault") {
+        Some(rest) => !rest.starts_with(|c: char| c.is_alphanumeric() || c == '_'),
+        None => false,
+    }
+}
+
+/// Where the `}}` closing a placeholder starts. Braces inside of quotes do
+/// not close it.
+fn closing(text: &str) -> Option<usize> {
+    let mut inside_quotes = false;
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if inside_quotes => escaped = true,
+            '"' => inside_quotes = !inside_quotes,
+            '}' if !inside_quotes && text[i..].starts_with("}}") => return Some(i),
+            _ => {}
+        }
+    }
+
+    None
+}
+
+fn line_of(template: &str, index: usize) -> usize {
+    template[..index].matches('\n').count() + 1
+}
+
+/// The strings inside of quotes, where `\` escapes the next character.
+fn quoted(text: &str) -> Option<Vec<String>> {
+    let mut values = Vec::<String>::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {}
+            c if c.is_whitespace() => continue,
+            _ => return None,
+        }
+
+        let mut value = String::new();
+        loop {
+            match chars.next()? {
+                '"' => break,
+                '\\' => value.push(chars.next()?),
+                c => value.push(c),
+            }
+        }
+        values.push(value);
+    }
+
+    Some(values)
+}
+
+/// Replaces the placeholders with the **decrypted** values, in the same
+/// order.
+///
+/// Fails if a placeholder of the vault would be left inside the result, so
+/// it is never written out as it is.
+pub fn render(
+    template: &str,
+    placeholders: &[Placeholder],
+    values: &[&Secret<String>],
+) -> Result<Secret<String>, InternalError> {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = 0;
+    for (placeholder, value) in placeholders.iter().zip(values) {
+        check_left_over(template, rest..placeholder.range.start)?;
+        rendered.push_str(&template[rest..placeholder.range.start]);
+        rendered.push_str(value.expose());
+        rest = placeholder.range.end;
+    }
+    check_left_over(template, rest..template.len())?;
+    rendered.push_str(&template[rest..]);
+
+    Ok(Secret::new(rendered))
+}
+
+/// Fails if the part of the template holds the start of a placeholder of the
+/// vault.
+fn check_left_over(template: &str, part: Range<usize>) -> Result<(), InternalError> {
+    let left_over = template[part.clone()]
+        .match_indices("{{")
+        .map(|(i, _)| part.start + i)
+        .find(|start| opens_vault(&template[start + 2..part.end]));
+
+    match left_over {
+        Some(start) => Err(CliError::InvalidPlaceholder(line_of(template, start)).into()),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,432 @@
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::{
+        cli::{
+            command, docker,
+            git::{answer, needs_unlock},
+            report, resolve,
+            template::{placeholders, render},
+            Command, DockerAction, DockerRequest, GitAction, GitCredential, Reference,
+        },
+        data_base::{kind::Payload, repository::Repository, store::MemoryStore},
+        error::{CliError, InternalError},
+        helpers::Secret,
+    };
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| String::from(*a)).collect()
+    }
+
+    fn reference(entry: &str, field: &str) -> Reference {
+        Reference {
+            entry: String::from(entry),
+            field: String::from(field),
+        }
+    }
+
+    fn repository() -> Repository {
+        let mut repository = Repository::open(Box::new(MemoryStore::new())).unwrap();
+        repository.add("prod-db", "admin", "hunter2").unwrap();
+        repository
+            .add_payload(
+                "ci/token",
+                &Payload::ApiToken {
+                    key_id: String::from("ci"),
+                    token: String::from("t0ken"),
+                },
+            )
+            .unwrap();
+        repository
+    }
+
+    #[test]
+    fn run_is_parsed() {
+        assert_eq!(
+            command(&args(&[
+                "vault",
+                "run",
+                "--env",
+                "DB_PASS=entry:prod-db/password",
+                "--env=TOKEN=entry:ci/token/token",
+                "--",
+                "./deploy.sh",
+                "--env",
+            ]))
+            .unwrap()
+            .unwrap(),
+            Command::Run {
+                env: vec![
+                    (String::from("DB_PASS"), reference("prod-db", "password")),
+                    (String::from("TOKEN"), reference("ci/token", "token")),
+                ],
+                program: args(&["./deploy.sh", "--env"]),
+            },
+            "\n!!! The references or the program were not parsed",
+        );
+        assert!(
+            command(&args(&["vault"])).is_none(),
+            "\n!!! The window was not opened without a command",
+        );
+
+        let code = |a: &[&str]| command(&args(a)).unwrap().unwrap_err().code();
+        assert_eq!(code(&["vault", "run", "-e", "DB_PASS=prod-db"]), "[CL_A-2]");
+        assert_eq!(code(&["vault", "run", "-e", "=entry:a/b", "x"]), "[CL_A-2]");
+        assert_eq!(code(&["vault", "run", "--verbose", "x"]), "[CL_A-1]");
+        assert_eq!(
+            code(&["vault", "run", "-e", "A=entry:a/b", "--"]),
+            "[CL_A-3]",
+            "\n!!! Nothing to run was accepted",
+        );
+    }
+
+    #[test]
+    fn inject_is_parsed() {
+        assert_eq!(
+            command(&args(&[
+                "vault",
+                "inject",
+                "-i",
+                "config.tpl",
+                "-o",
+                "config.yml"
+            ]))
+            .unwrap()
+            .unwrap(),
+            Command::Inject {
+                input: "config.tpl".into(),
+                output: "config.yml".into(),
+            },
+        );
+        assert_eq!(
+            command(&args(&["vault", "inject", "-i", "config.tpl"]))
+                .unwrap()
+                .unwrap_err()
+                .code(),
+            "[CL_A-4]",
+            "\n!!! A missing output was accepted",
+        );
+    }
+
+    #[test]
+    fn templates_are_filled_in() {
+        let template = "user: {{ vault \"GitHub\" \"username\" }}\n\
+                        pass: {{vault \"say \\\"hi\\\"\" \"password\"}}\n\
+                        helm: {{ .Values.vault }}\n";
+        let found = placeholders(template).unwrap();
+
+        assert_eq!(
+            found
+                .iter()
+                .map(|p| p.reference.clone())
+                .collect::<Vec<Reference>>(),
+            vec![
+                reference("GitHub", "username"),
+                reference("say \"hi\"", "password"),
+            ],
+            "\n!!! The placeholders were not found",
+        );
+        let values = [
+            Secret::new(String::from("me")),
+            Secret::new(String::from("pw")),
+        ];
+        assert_eq!(
+            render(template, &found, &[&values[0], &values[1]])
+                .unwrap()
+                .expose(),
+            "user: me\npass: pw\nhelm: {{ .Values.vault }}\n",
+            "\n!!! The template was not filled in",
+        );
+
+        let err = placeholders("a\nb: {{ vault \"GitHub\" }}").unwrap_err();
+        assert!(
+            matches!(err, InternalError::Cli(CliError::InvalidPlaceholder(2))),
+            "\n!!! The broken placeholder was not found on its line",
+        );
+    }
+
+    #[test]
+    fn placeholders_are_never_left_in_the_output() {
+        let template = "a: {{ vault \"a}}b\" \"password\" }}\n\
+                        b: {{ if .Values.open\n\
+                        c: {{ vault \"GitHub\" \"password\" }}\n";
+        let found = placeholders(template).unwrap();
+        assert_eq!(
+            found
+                .iter()
+                .map(|p| p.reference.clone())
+                .collect::<Vec<Reference>>(),
+            vec![
+                reference("a}}b", "password"),
+                reference("GitHub", "password")
+            ],
+            "\n!!! Braces inside of quotes or an unclosed placeholder hid a placeholder",
+        );
+
+        let value = Secret::new(String::from("pw"));
+        let err = render(template, &found[..1], &[&value]).unwrap_err();
+        assert!(
+            matches!(err, InternalError::Cli(CliError::InvalidPlaceholder(3))),
+            "\n!!! A placeholder of the vault was written out as it is",
+        );
+        let err = placeholders("a: {{ vault \"GitHub\" \"password\"").unwrap_err();
+        assert!(
+            matches!(err, InternalError::Cli(CliError::InvalidPlaceholder(1))),
+            "\n!!! An unclosed placeholder of the vault was left alone",
+        );
+    }
+
+    /// Stands in for git, which passes the lines on stdin.
+    fn git(action: GitAction, lines: &str, repository: &mut Repository) -> String {
+        let request = GitCredential::read(&mut Cursor::new(lines)).unwrap();
+        let mut output = Vec::<u8>::new();
+        answer(action, &request, repository, &mut output).unwrap();
+
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn git_gets_the_login_of_the_site() {
+        let mut repository = repository();
+        let id = repository.add("GitHub", "octo", "pw").unwrap().id();
+        repository.set_url(&id, "https://github.com/login").unwrap();
+
+        assert_eq!(
+            git(
+                GitAction::Get,
+                "protocol=https\nhost=github.com\n\nignored=after the end\n",
+                &mut repository
+            ),
+            "username=octo\npassword=pw\n",
+            "\n!!! The login of the site was not sent",
+        );
+        assert_eq!(
+            git(
+                GitAction::Get,
+                "url=https://github.com:443/org/repo.git\n",
+                &mut repository
+            ),
+            "username=octo\npassword=pw\n",
+            "\n!!! The url of the request was not used",
+        );
+        assert_eq!(
+            git(
+                GitAction::Get,
+                "protocol=https\nhost=github.com\nusername=other\n",
+                &mut repository
+            ),
+            "",
+            "\n!!! The login of another user was sent",
+        );
+        assert_eq!(
+            git(
+                GitAction::Get,
+                "protocol=http\nhost=github.com\n",
+                &mut repository
+            ),
+            "",
+            "\n!!! The login was sent over another protocol",
+        );
+        assert_eq!(
+            command(&args(&["vault", "git-credential", "capability"]))
+                .unwrap()
+                .unwrap(),
+            Command::GitCredential(None),
+            "\n!!! Unknown actions of git are not ignored",
+        );
+    }
+
+    #[test]
+    fn git_stores_and_erases_logins() {
+        let mut repository = Repository::open(Box::new(MemoryStore::new())).unwrap();
+        let login = |password: &str| {
+            format!(
+                "protocol=https\nhost=git.example.org:8443\nusername=me\npassword={}\n",
+                password
+            )
+        };
+        let request = GitCredential::read(&mut Cursor::new(login("first"))).unwrap();
+
+        assert!(needs_unlock(GitAction::Store, &request, &repository).unwrap());
+        git(GitAction::Store, &login("first"), &mut repository);
+        assert_eq!(repository.list().len(), 1, "\n!!! The login was not stored");
+        assert_eq!(repository.list()[0].url(), "https://git.example.org:8443");
+        assert!(
+            !needs_unlock(GitAction::Store, &request, &repository).unwrap(),
+            "\n!!! Storing the same login again needs the master password",
+        );
+
+        git(GitAction::Store, &login("second"), &mut repository);
+        assert_eq!(
+            git(GitAction::Get, &login(""), &mut repository),
+            "username=me\npassword=second\n",
+            "\n!!! The changed password was not stored",
+        );
+        assert_eq!(
+            repository.list().len(),
+            1,
+            "\n!!! The login was stored twice"
+        );
+
+        git(GitAction::Erase, &login("first"), &mut repository);
+        assert_eq!(
+            repository.list().len(),
+            1,
+            "\n!!! A login with another password was erased",
+        );
+        git(GitAction::Erase, &login("second"), &mut repository);
+        assert!(
+            repository.list().is_empty(),
+            "\n!!! The login was not erased"
+        );
+        assert_eq!(
+            repository.trash().len(),
+            1,
+            "\n!!! The login was not kept in the trash"
+        );
+    }
+
+    /// Stands in for docker, which passes the request on stdin.
+    fn docker(
+        action: DockerAction,
+        input: &str,
+        repository: &mut Repository,
+    ) -> Result<String, InternalError> {
+        let request = DockerRequest::read(action, &mut Cursor::new(input))?;
+        let mut output = Vec::<u8>::new();
+        docker::answer(&request, repository, &mut output)?;
+
+        Ok(String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn docker_stores_and_gets_registry_logins() {
+        let mut repository = repository();
+        let login = |secret: &str| {
+            format!(
+                "{{\"ServerURL\":\"https://index.docker.io/v1/\",\"Username\":\"me\",\"Secret\":\"{}\"}}",
+                secret
+            )
+        };
+
+        docker(DockerAction::Store, &login("first"), &mut repository).unwrap();
+        let stored = repository.find_by_tag("registry");
+        assert_eq!(
+            stored.len(),
+            1,
+            "\n!!! The login was not tagged as a registry"
+        );
+        assert_eq!(stored[0].name(), "index.docker.io");
+        let request =
+            DockerRequest::read(DockerAction::Store, &mut Cursor::new(login("first"))).unwrap();
+        assert!(
+            !docker::needs_unlock(&request, &repository).unwrap(),
+            "\n!!! Storing the same login again needs the master password",
+        );
+
+        docker(DockerAction::Store, &login("second"), &mut repository).unwrap();
+        assert_eq!(
+            docker(DockerAction::Get, "https://index.docker.io/v1/\n", &mut repository).unwrap(),
+            "{\"ServerURL\":\"https://index.docker.io/v1/\",\"Username\":\"me\",\"Secret\":\"second\"}\n",
+            "\n!!! The changed login was not sent",
+        );
+        assert_eq!(
+            docker(DockerAction::List, "", &mut repository).unwrap(),
+            "{\"https://index.docker.io/v1/\":\"me\"}\n",
+            "\n!!! Other entries were listed as registries",
+        );
+
+        let err = docker(DockerAction::Get, "ghcr.io", &mut repository).unwrap_err();
+        assert_eq!(
+            docker::message(&err),
+            "credentials not found in native keychain",
+            "\n!!! Docker would not see that the login is missing",
+        );
+
+        docker(DockerAction::Erase, "index.docker.io", &mut repository).unwrap();
+        assert!(
+            repository.find_by_tag("registry").is_empty(),
+            "\n!!! The login was not erased",
+        );
+    }
+
+    #[test]
+    fn docker_helper_is_started_through_a_link() {
+        assert_eq!(
+            command(&args(&["/usr/local/bin/docker-credential-vault", "get"]))
+                .unwrap()
+                .unwrap(),
+            Command::DockerCredential(DockerAction::Get),
+            "\n!!! The link docker runs was not recognized",
+        );
+        assert_eq!(
+            command(&args(&["vault", "docker-credential", "list"]))
+                .unwrap()
+                .unwrap(),
+            Command::DockerCredential(DockerAction::List),
+        );
+        assert_eq!(
+            command(&args(&["vault", "docker-credential", "version"]))
+                .unwrap()
+                .unwrap_err()
+                .code(),
+            "[CL_A-1]",
+        );
+        assert_eq!(
+            DockerRequest::read(DockerAction::Store, &mut Cursor::new("not json"))
+                .unwrap_err()
+                .code(),
+            "[CL_D-2]",
+            "\n!!! A broken request was accepted",
+        );
+    }
+
+    #[test]
+    fn references_are_decrypted() {
+        let repository = repository();
+        let values = resolve(
+            &repository,
+            &[
+                &reference("prod-db", "password"),
+                &reference("ci/token", "Token"),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            values
+                .iter()
+                .map(|(_, v)| v.expose().as_str())
+                .collect::<Vec<&str>>(),
+            vec!["hunter2", "t0ken"],
+            "\n!!! The values were not decrypted",
+        );
+    }
+
+    #[test]
+    fn unknown_references_list_what_exists() {
+        let repository = repository();
+
+        let err = resolve(&repository, &[&reference("prod", "password")]).unwrap_err();
+        assert!(
+            matches!(
+                &err,
+                InternalError::Cli(CliError::UnknownEntry { available, .. })
+                    if available == &vec![String::from("ci/token"), String::from("prod-db")]
+            ),
+            "\n!!! The names of the entries were not kept",
+        );
+        assert!(
+            report(&err).contains("ci/token") && report(&err).contains("prod-db"),
+            "\n!!! The names of the entries are not shown",
+        );
+
+        let err = resolve(&repository, &[&reference("prod-db", "token")]).unwrap_err();
+        assert_eq!(err.code(), "[CL_R-3]");
+        assert!(
+            report(&err).contains("Password"),
+            "\n!!! The fields of the entry are not shown",
+        );
+    }
+}
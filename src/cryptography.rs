@@ -1,9 +1,10 @@
 use aes_gcm::{
-    aead::{Aead, AeadCore, KeyInit, OsRng},
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng},
     Aes256Gcm, Key, Nonce,
 };
+use serde::{Deserialize, Serialize};
 
-use crate::helpers::InternalError;
+use crate::error::{CryptoError, InternalError};
 
 mod test;
 
@@ -11,6 +12,90 @@ const MASTER_KEY_STR: &str = super::env::MASTER_KEY_STR;
 const PW_KEY_STR: &str = super::env::PW_KEY_STR;
 const GENERIC_KEY_STR: &str = super::env::GENERIC_KEY_STR;
 
+const LOWERCASE: &str = "abcdefghijklmnopqrstuvwxyz";
+const UPPERCASE: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DIGITS: &str = "0123456789";
+const SYMBOLS: &str = "!#$%&*+-.:;=?@_~";
+
+/// What a generated password is made of.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct PasswordRules {
+    pub length: usize,
+    pub lowercase: bool,
+    pub uppercase: bool,
+    pub digits: bool,
+    pub symbols: bool,
+}
+impl PasswordRules {
+    /// The character sets which are allowed.
+    fn sets(&self) -> Vec<&'static [u8]> {
+        [
+            (self.lowercase, LOWERCASE),
+            (self.uppercase, UPPERCASE),
+            (self.digits, DIGITS),
+            (self.symbols, SYMBOLS),
+        ]
+        .into_iter()
+        .filter(|(allowed, _)| *allowed)
+        .map(|(_, set)| set.as_bytes())
+        .collect()
+    }
+}
+impl Default for PasswordRules {
+    fn default() -> Self {
+        Self {
+            length: 20,
+            lowercase: true,
+            uppercase: true,
+            digits: true,
+            symbols: true,
+        }
+    }
+}
+
+/// Generates a random password which holds at least one character of every
+/// allowed set.
+pub fn generate_password(rules: &PasswordRules) -> Result<String, InternalError> {
+    let sets = rules.sets();
+    if sets.is_empty() {
+        return Err(CryptoError::NoCharacters.into());
+    }
+    if rules.length < sets.len() {
+        return Err(CryptoError::PasswordTooShort.into());
+    }
+
+    let all = sets.concat();
+    let mut password = sets
+        .iter()
+        .map(|set| set[random_below(set.len())])
+        .collect::<Vec<u8>>();
+    while password.len() < rules.length {
+        password.push(all[random_below(all.len())]);
+    }
+
+    // the required characters would otherwise always be at the start
+    for index in (1..password.len()).rev() {
+        password.swap(index, random_below(index + 1));
+    }
+
+    // all sets are ASCII
+    Ok(password.into_iter().map(char::from).collect())
+}
+
+/// A random number from `0` up to `bound`, without the bias of a plain
+/// modulo.
+fn random_below(bound: usize) -> usize {
+    let bound = bound as u32;
+    let zone = u32::MAX - u32::MAX % bound;
+    loop {
+        let value = OsRng.next_u32();
+        if value < zone {
+            return (value % bound) as usize;
+        }
+    }
+}
+
 pub fn encrypt_text(
     text: &str,
     is_master: bool,
@@ -31,7 +116,7 @@ pub fn encrypt_text(
     let ciphered_data = match cipher.encrypt(&nonce, text.as_bytes()) {
         Ok(data) => data,
         Err(_) => {
-            return Err(InternalError::new("[CR_EP-1]", "Failed to encrypt"));
+            return Err(CryptoError::Encrypt.into());
         }
     };
 
@@ -50,11 +135,8 @@ pub fn decrypt_text(
 ) -> Result<String, InternalError> {
     let encrypted_data = match hex::decode(text) {
         Ok(data) => data,
-        Err(_) => {
-            return Err(InternalError::new(
-                "[CR_DP-1]",
-                "Failed to decode HEX String into Vec.",
-            ));
+        Err(err) => {
+            return Err(CryptoError::HexDecode(err).into());
         }
     };
 
@@ -67,6 +149,9 @@ pub fn decrypt_text(
     };
 
     let key = Key::<Aes256Gcm>::from_slice(use_key.as_bytes());
+    if encrypted_data.len() < 12 {
+        return Err(CryptoError::Decrypt.into());
+    }
 
     // we split the vector at 12th position because we used
     // nonce of length 12
@@ -78,15 +163,72 @@ pub fn decrypt_text(
     let plaintext = match cipher.decrypt(nonce, ciphered_data) {
         Ok(text) => text,
         Err(_) => {
-            return Err(InternalError::new("[CR_DP-2]", "Failed to decrypt data."));
+            return Err(CryptoError::Decrypt.into());
         }
     };
 
     match String::from_utf8(plaintext) {
         Ok(s) => Ok(s),
-        Err(_) => Err(InternalError::new(
-            "[CR_DP-3]",
-            "Failed to convert Vector of Bytes into String.",
-        )),
+        Err(err) => Err(CryptoError::Utf8(err).into()),
     }
 }
+
+/// A new random key for [`encrypt_bytes`], hex encoded so it can be stored
+/// like any other text.
+pub fn generate_key() -> String {
+    hex::encode(Aes256Gcm::generate_key(OsRng))
+}
+
+/// A new random token, hex encoded, which local programs have to know to be
+/// served.
+pub fn generate_token() -> String {
+    let mut token = [0u8; 32];
+    OsRng.fill_bytes(&mut token);
+    hex::encode(token)
+}
+
+/// Encrypts binary data, like the files attached to an entry, with the given
+/// hex encoded key. The nonce is put in front of the encrypted data.
+pub fn encrypt_bytes(data: &[u8], key: &str) -> Result<Vec<u8>, InternalError> {
+    let cipher = cipher_of(key)?;
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+
+    let ciphered_data = match cipher.encrypt(&nonce, data) {
+        Ok(data) => data,
+        Err(_) => {
+            return Err(CryptoError::Encrypt.into());
+        }
+    };
+
+    let mut encrypted_data: Vec<u8> = nonce.to_vec();
+    encrypted_data.extend_from_slice(&ciphered_data);
+
+    Ok(encrypted_data)
+}
+
+pub fn decrypt_bytes(data: &[u8], key: &str) -> Result<Vec<u8>, InternalError> {
+    let cipher = cipher_of(key)?;
+    if data.len() < 12 {
+        return Err(CryptoError::Decrypt.into());
+    }
+
+    let (nonce_arr, ciphered_data) = data.split_at(12);
+    let nonce = Nonce::from_slice(nonce_arr);
+
+    match cipher.decrypt(nonce, ciphered_data) {
+        Ok(data) => Ok(data),
+        Err(_) => Err(CryptoError::Decrypt.into()),
+    }
+}
+
+fn cipher_of(key: &str) -> Result<Aes256Gcm, InternalError> {
+    let key = match hex::decode(key) {
+        Ok(k) => k,
+        Err(err) => return Err(CryptoError::HexDecode(err).into()),
+    };
+    if key.len() != 32 {
+        return Err(CryptoError::InvalidKey.into());
+    }
+
+    Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)))
+}
@@ -1,6 +1,9 @@
 #[cfg(test)]
 mod tests {
-    use crate::cryptography::{decrypt_text, encrypt_text};
+    use crate::cryptography::{
+        decrypt_bytes, decrypt_text, encrypt_bytes, encrypt_text, generate_key, generate_password,
+        PasswordRules,
+    };
 
     #[test]
     fn different_encryptions_for_same_text() {
@@ -116,4 +119,93 @@ ahsdoifhasdolfjasldflkasdhjf;bfkj  apsdjfasdhfjasfkdasbfhdasfdnaskfl asfnasdhjfb
             text, &text_decrypted
         );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn generated_password_follows_rules() {
+        let rules = PasswordRules {
+            length: 12,
+            symbols: false,
+            ..Default::default()
+        };
+        for _ in 0..50 {
+            let password = generate_password(&rules).unwrap();
+
+            assert_eq!(
+                password.len(),
+                12,
+                "\n!!! Password {} does not have the given length",
+                password
+            );
+            assert!(
+                password.chars().all(|c| c.is_ascii_alphanumeric()),
+                "\n!!! Password {} holds a symbol",
+                password
+            );
+            assert!(
+                password.chars().any(|c| c.is_ascii_lowercase())
+                    && password.chars().any(|c| c.is_ascii_uppercase())
+                    && password.chars().any(|c| c.is_ascii_digit()),
+                "\n!!! Password {} misses a kind of character",
+                password
+            );
+        }
+    }
+
+    #[test]
+    fn generated_passwords_differ() {
+        let rules = PasswordRules::default();
+        let first = generate_password(&rules).unwrap();
+        let second = generate_password(&rules).unwrap();
+
+        assert_ne!(first, second, "\n!!! Two generated passwords are equal");
+    }
+
+    #[test]
+    fn impossible_password_rules_fail() {
+        let nothing = PasswordRules {
+            lowercase: false,
+            uppercase: false,
+            digits: false,
+            symbols: false,
+            ..Default::default()
+        };
+        let too_short = PasswordRules {
+            length: 3,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            generate_password(&nothing).unwrap_err().code(),
+            "[CR_GP-1]",
+            "\n!!! Rules without characters did not fail"
+        );
+        assert_eq!(
+            generate_password(&too_short).unwrap_err().code(),
+            "[CR_GP-2]",
+            "\n!!! Rules shorter than the sets did not fail"
+        );
+    }
+
+    #[test]
+    fn bytes_only_decrypt_with_their_key() {
+        let data = [0u8, 159, 146, 150, 255, 10];
+        let key = generate_key();
+        let other_key = generate_key();
+        let encrypted = encrypt_bytes(&data, &key).unwrap();
+
+        assert_ne!(key, other_key, "\n!!! Two generated keys are equal");
+        assert_eq!(
+            decrypt_bytes(&encrypted, &key).unwrap(),
+            data,
+            "\n!!! Decrypted bytes differ from the original"
+        );
+        assert!(
+            decrypt_bytes(&encrypted, &other_key).is_err(),
+            "\n!!! Bytes were decrypted with another key"
+        );
+        assert!(
+            decrypt_bytes(&encrypted[..8], &key).is_err(),
+            "\n!!! Cut off bytes were decrypted"
+        );
+    }
+}
@@ -1,10 +1,23 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::{cryptography::{decrypt_text, encrypt_text}, helpers::InternalError};
+use crate::{cryptography::{decrypt_text, encrypt_text}, error::{DbError, InternalError}, helpers::Secret};
+use attachment::Attachment;
+use kind::{EntryKind, Payload};
 
 pub mod post;
 pub mod get;
+pub mod attachment;
+pub mod audit;
+pub mod folder;
+pub mod history;
+pub mod kind;
+pub mod recovery;
+pub mod repository;
+pub mod store;
+mod test;
 mod utils;
 
 /// This structure holds the data of one Password Entry.
@@ -20,6 +33,39 @@ pub struct Entry {
     password: String,
     created_at: i64,
     last_edited: i64,
+    #[serde(default)]
+    deleted_at: Option<i64>,
+    /// Favorites are listed above all other entries.
+    #[serde(default)]
+    favorite: bool,
+    /// When the username or password was used the last time.
+    #[serde(default)]
+    last_used: Option<i64>,
+    /// The path of the folder the entry is inside, e.g. `Work/AWS`. Entries
+    /// on the top level have an empty path.
+    #[serde(default)]
+    folder: String,
+    /// The address of the site the entry is used on, e.g.
+    /// `https://github.com/login`.
+    #[serde(default)]
+    url: String,
+    /// Labels to group entries by, e.g. `registry` for the logins of
+    /// container registries.
+    #[serde(default)]
+    tags: Vec<String>,
+    /// What other apps find the entry by through the Secret Service, e.g.
+    /// `service` and `username`.
+    #[serde(default)]
+    attributes: BTreeMap<String, String>,
+    #[serde(default)]
+    kind: EntryKind,
+    /// The **encrypted** values of every kind besides logins, which keep using
+    /// `user_name` and `password`.
+    #[serde(default)]
+    payload: Option<String>,
+    /// The files attached to the entry, without their contents.
+    #[serde(default)]
+    attachments: Vec<Attachment>,
 }
 impl Entry {
     pub fn new(name: &str, user_name: &str, password: &str) -> Result<Self, InternalError> {
@@ -46,8 +92,27 @@ impl Entry {
             password,
             created_at,
             last_edited,
+            deleted_at: None,
+            favorite: false,
+            last_used: None,
+            folder: String::new(),
+            url: String::new(),
+            tags: Vec::new(),
+            attributes: BTreeMap::new(),
+            kind: EntryKind::Login,
+            payload: None,
+            attachments: Vec::new(),
         })
     }
+
+    /// Creates an entry of any kind. A login is the same as one created with
+    /// [`Entry::new`].
+    pub fn with_payload(name: &str, payload: &Payload) -> Result<Self, InternalError> {
+        let mut entry = Entry::new(name, "", "")?;
+        entry.set_payload(payload)?;
+
+        Ok(entry)
+    }
     
     pub fn name(&self) -> String {
         String::from(&self.name)
@@ -57,6 +122,76 @@ impl Entry {
         String::from(&self.id)
     }
 
+    /// When the entry was moved into the trash, if it is inside the trash.
+    pub fn deleted_at(&self) -> Option<i64> {
+        self.deleted_at
+    }
+
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+
+    pub fn created_at(&self) -> i64 {
+        self.created_at
+    }
+
+    pub fn last_edited(&self) -> i64 {
+        self.last_edited
+    }
+
+    pub fn is_favorite(&self) -> bool {
+        self.favorite
+    }
+
+    pub fn last_used(&self) -> Option<i64> {
+        self.last_used
+    }
+
+    pub fn folder(&self) -> String {
+        String::from(&self.folder)
+    }
+
+    pub fn url(&self) -> String {
+        String::from(&self.url)
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+
+    pub fn attributes(&self) -> &BTreeMap<String, String> {
+        &self.attributes
+    }
+
+    pub fn kind(&self) -> EntryKind {
+        self.kind
+    }
+
+    pub fn attachments(&self) -> &[Attachment] {
+        &self.attachments
+    }
+
+    /// The size of all attachments together.
+    pub fn attachments_size(&self) -> u64 {
+        self.attachments.iter().map(|a| a.size).sum()
+    }
+
+    /// Whether both are the same entry holding the same name and values.
+    /// Every edit encrypts the values again, so an edited entry never has the
+    /// same values as before.
+    pub fn same_values(&self, other: &Entry) -> bool {
+        self.id == other.id
+            && self.name == other.name
+            && self.kind == other.kind
+            && self.user_name == other.user_name
+            && self.password == other.password
+            && self.payload == other.payload
+    }
+
     /// Gets the informations needed to display.
     /// 
     /// The Password and Usernames are here already **decrypted**
@@ -73,38 +208,82 @@ impl Entry {
         Ok(EntryDisplay {
             id: self.id.clone(),
             name: String::from(&self.name),
-            user_name,
-            password,
+            user_name: Secret::new(user_name),
+            password: Secret::new(password),
             created_at: self.created_at,
             last_edited: self.last_edited,
+            favorite: self.favorite,
+            last_used: self.last_used,
+            folder: String::from(&self.folder),
+            url: String::from(&self.url),
+            tags: self.tags.clone(),
+            kind: self.kind,
+            payload: Secret::new(self.decrypt_payload()?),
+            attachments: self.attachments.clone(),
         })
         
     }
 
-    pub fn edit(&mut self, name: &str, user_name: &str, password: &str) -> Result<(), InternalError> {
-        
-        match encrypt_text(user_name, false, false) {
-            Ok(u) => {
-                self.user_name = u;
-                self.edited();
-            },
-            Err(err) => return Err(err),
+    /// The values of the entry, **decrypted**.
+    fn decrypt_payload(&self) -> Result<Payload, InternalError> {
+        if self.kind == EntryKind::Login {
+            return Ok(Payload::Login {
+                user_name: decrypt_text(&self.user_name, false, false)?,
+                password: decrypt_text(&self.password, false, true)?,
+            });
         }
-        
-        match encrypt_text(password, false, true) {
-            Ok(p) => {
-                self.password = p;
-                self.edited();
-            },
-            Err(err) => return Err(err),
+
+        let encrypted = match &self.payload {
+            Some(p) => p,
+            None => return Err(DbError::PayloadMissing.into()),
+        };
+        match serde_json::from_str::<Payload>(&decrypt_text(encrypted, false, true)?) {
+            Ok(payload) if payload.kind() == self.kind => Ok(payload),
+            Ok(_) => Err(DbError::PayloadMissing.into()),
+            Err(err) => Err(DbError::PayloadParse(err).into()),
         }
+    }
 
+    /// Changes the name and all values of the entry, also its kind.
+    pub fn edit_payload(&mut self, name: &str, payload: &Payload) -> Result<(), InternalError> {
+        self.set_payload(payload)?;
         self.name = String::from(name);
         self.edited();
 
         Ok(())
     }
 
+    fn set_payload(&mut self, payload: &Payload) -> Result<(), InternalError> {
+        payload.validate()?;
+
+        let (user_name, password, encrypted) = match payload {
+            Payload::Login { user_name, password } => (user_name.as_str(), password.as_str(), None),
+            _ => {
+                let buffer = match serde_json::to_string(payload) {
+                    Ok(s) => s,
+                    Err(err) => return Err(DbError::PayloadSerialize(err).into()),
+                };
+                ("", "", Some(encrypt_text(&buffer, false, true)?))
+            }
+        };
+
+        self.user_name = encrypt_text(user_name, false, false)?;
+        self.password = encrypt_text(password, false, true)?;
+        self.payload = encrypted;
+        self.kind = payload.kind();
+
+        Ok(())
+    }
+
+    pub fn edit(&mut self, name: &str, user_name: &str, password: &str) -> Result<(), InternalError> {
+        let payload = Payload::Login {
+            user_name: String::from(user_name),
+            password: String::from(password),
+        };
+
+        self.edit_payload(name, &payload)
+    }
+
     fn edited(&mut self) {
         self.last_edited = chrono::Local::now().timestamp();
     }
@@ -114,8 +293,18 @@ impl Entry {
 pub struct EntryDisplay {
     pub id: String,
     pub name: String,
-    pub user_name: String,
-    pub password: String,
+    pub user_name: Secret<String>,
+    pub password: Secret<String>,
     pub created_at: i64,
     pub last_edited: i64,
+    pub favorite: bool,
+    pub last_used: Option<i64>,
+    pub folder: String,
+    pub url: String,
+    pub tags: Vec<String>,
+    pub kind: EntryKind,
+    /// All values of the entry. For logins they are the same as `user_name`
+    /// and `password`.
+    pub payload: Secret<Payload>,
+    pub attachments: Vec<Attachment>,
 }
\ No newline at end of file
@@ -0,0 +1,166 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    cryptography::{decrypt_bytes, encrypt_bytes, generate_key},
+    error::DbError,
+};
+
+use super::{utils::ATTACHMENT_FOLDER, *};
+
+/// The largest file which can be attached.
+pub const MAX_ATTACHMENT_SIZE: u64 = 10 * 1024 * 1024;
+/// The largest size of all attachments of one entry together.
+pub const MAX_ENTRY_ATTACHMENTS_SIZE: u64 = 25 * 1024 * 1024;
+
+/// A file attached to an entry.
+///
+/// Every attachment is encrypted with its own key, which is kept inside the
+/// entry in its **encrypted** state. The encrypted file is named after the id
+/// of the attachment.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Attachment {
+    pub id: String,
+    /// The name of the original file.
+    pub name: String,
+    /// The size of the original file in bytes.
+    pub size: u64,
+    pub added_at: i64,
+    key: String,
+}
+impl Attachment {
+    /// Encrypts the data with a new key. Returns the attachment together with
+    /// the encrypted data, which still has to be written.
+    pub fn encrypt(name: &str, data: &[u8]) -> Result<(Self, Vec<u8>), InternalError> {
+        let key = generate_key();
+        let encrypted = encrypt_bytes(data, &key)?;
+
+        let attachment = Self {
+            id: Uuid::new_v4().to_string(),
+            name: String::from(name),
+            size: data.len() as u64,
+            added_at: chrono::Local::now().timestamp(),
+            key: encrypt_text(&key, false, true)?,
+        };
+
+        Ok((attachment, encrypted))
+    }
+
+    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, InternalError> {
+        let key = decrypt_text(&self.key, false, true)?;
+        decrypt_bytes(data, &key)
+    }
+}
+
+/// Shows the size in the largest fitting unit, e.g. `1.5 MB`.
+pub fn format_size(size: u64) -> String {
+    match size {
+        s if s < 1024 => format!("{} B", s),
+        s if s < 1024 * 1024 => format!("{:.1} KB", s as f64 / 1024.0),
+        s => format!("{:.1} MB", s as f64 / (1024.0 * 1024.0)),
+    }
+}
+
+/// Reads a file which should be attached, together with its name. Files
+/// above [`MAX_ATTACHMENT_SIZE`] are refused before they are read.
+pub fn read_source(path: impl AsRef<Path>) -> Result<(String, Vec<u8>), InternalError> {
+    let path = path.as_ref();
+    let size = match fs::metadata(path) {
+        Ok(metadata) => metadata.len(),
+        Err(err) => return Err(DbError::AttachmentSource(err).into()),
+    };
+    if size > MAX_ATTACHMENT_SIZE {
+        return Err(DbError::AttachmentTooLarge.into());
+    }
+
+    let name = match path.file_name() {
+        Some(name) => name.to_string_lossy().into_owned(),
+        None => String::from("attachment"),
+    };
+    match fs::read(path) {
+        Ok(data) => Ok((name, data)),
+        Err(err) => Err(DbError::AttachmentSource(err).into()),
+    }
+}
+
+/// Saves the **decrypted** data of an attachment where the user chose to.
+pub fn export(path: impl AsRef<Path>, data: &[u8]) -> Result<(), InternalError> {
+    match fs::write(path, data) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(DbError::AttachmentExport(err).into()),
+    }
+}
+
+/// The folder holding the encrypted files of all attachments, which lies next
+/// to the entries of the vault.
+pub struct AttachmentFolder {
+    path: PathBuf,
+}
+impl AttachmentFolder {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn write(&self, id: &str, data: &[u8]) -> Result<(), InternalError> {
+        if let Err(err) = fs::create_dir_all(&self.path) {
+            return Err(DbError::AttachmentWrite(err).into());
+        }
+
+        match fs::write(self.path.join(id), data) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(DbError::AttachmentWrite(err).into()),
+        }
+    }
+
+    pub fn read(&self, id: &str) -> Result<Vec<u8>, InternalError> {
+        match fs::read(self.path.join(id)) {
+            Ok(data) => Ok(data),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                Err(DbError::AttachmentNotFound.into())
+            }
+            Err(err) => Err(DbError::AttachmentRead(err).into()),
+        }
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.path.join(id).is_file()
+    }
+
+    /// Deletes the file of an attachment, a missing file is already gone.
+    pub fn remove(&self, id: &str) -> Result<(), InternalError> {
+        match fs::remove_file(self.path.join(id)) {
+            Ok(_) => Ok(()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(DbError::AttachmentRemove(err).into()),
+        }
+    }
+
+    /// The ids of all stored files.
+    pub fn ids(&self) -> Result<Vec<String>, InternalError> {
+        let dir = match fs::read_dir(&self.path) {
+            Ok(dir) => dir,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(DbError::AttachmentFolder(err).into()),
+        };
+
+        let mut ids = Vec::<String>::new();
+        for file in dir {
+            match file {
+                Ok(file) => ids.push(file.file_name().to_string_lossy().into_owned()),
+                Err(err) => return Err(DbError::AttachmentFolder(err).into()),
+            }
+        }
+
+        Ok(ids)
+    }
+}
+impl Default for AttachmentFolder {
+    fn default() -> Self {
+        Self::new(ATTACHMENT_FOLDER)
+    }
+}
@@ -0,0 +1,262 @@
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::DbError;
+
+use super::*;
+
+/// The hash the first record of a log points to.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// What was done with an entry.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+pub enum AuditAction {
+    Added,
+    Edited,
+    /// The entry was moved into the trash.
+    Deleted,
+    Restored,
+    /// The entry was removed for good.
+    Purged,
+    CopiedUserName,
+    CopiedPassword,
+    /// An attached file was saved outside of the vault.
+    Exported,
+    /// Something was signed with the key of the entry by the SSH agent.
+    Signed,
+    /// The login was sent to the browser extension.
+    SentToBrowser,
+    /// The names of all entries were sent to a client of the local API.
+    ListedByApi,
+    /// A field of the entry was sent to a client of the local API.
+    ReadByApi,
+    /// The user refused a request of a client of the local API.
+    RefusedToApi,
+    /// A value of the entry was used by a command of the command line.
+    ReadByCommandLine,
+    /// The secret of the entry was sent to an app through the Secret Service.
+    ReadBySecretService,
+    /// The user refused a request of an app through the Secret Service.
+    RefusedToSecretService,
+}
+impl AuditAction {
+    pub fn label(&self) -> &'static str {
+        match self {
+            AuditAction::Added => "Added",
+            AuditAction::Edited => "Edited",
+            AuditAction::Deleted => "Deleted",
+            AuditAction::Restored => "Restored",
+            AuditAction::Purged => "Deleted for good",
+            AuditAction::CopiedUserName => "Copied Username",
+            AuditAction::CopiedPassword => "Copied Password",
+            AuditAction::Exported => "Saved Attachment",
+            AuditAction::Signed => "Signed with SSH Key",
+            AuditAction::SentToBrowser => "Sent to Browser",
+            AuditAction::ListedByApi => "Listed by API",
+            AuditAction::ReadByApi => "Read by API",
+            AuditAction::RefusedToApi => "Refused to API",
+            AuditAction::ReadByCommandLine => "Read from the Command Line",
+            AuditAction::ReadBySecretService => "Read by Secret Service",
+            AuditAction::RefusedToSecretService => "Refused to Secret Service",
+        }
+    }
+}
+
+/// One record of the audit log. It never holds any value of the entry
+/// besides its id.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct AuditRecord {
+    pub timestamp: i64,
+    /// The user of the operating system who did the action.
+    pub actor: String,
+    pub action: AuditAction,
+    pub entry_id: Option<String>,
+    /// The hash of the record before, which chains all records together.
+    pub prev_hash: String,
+    pub hash: String,
+}
+impl AuditRecord {
+    fn new(prev_hash: &str, action: AuditAction, entry_id: Option<&str>, actor: String) -> Self {
+        let mut record = Self {
+            timestamp: chrono::Local::now().timestamp(),
+            actor,
+            action,
+            entry_id: entry_id.map(String::from),
+            prev_hash: String::from(prev_hash),
+            hash: String::new(),
+        };
+        record.hash = record.compute_hash();
+
+        record
+    }
+
+    /// The hash covers the name of the action, which is the one it is stored
+    /// under and stays the same when its label is reworded.
+    fn compute_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.prev_hash.as_bytes());
+        hasher.update(self.timestamp.to_le_bytes());
+        hasher.update(self.actor.as_bytes());
+        hasher.update([0]);
+        hasher.update(format!("{:?}", self.action).as_bytes());
+        hasher.update([0]);
+        hasher.update(self.entry_id.as_deref().unwrap_or_default().as_bytes());
+
+        hex::encode(hasher.finalize())
+    }
+
+    fn matches_hash(&self) -> bool {
+        self.compute_hash() == self.hash
+    }
+}
+
+/// The result of verifying one line of the log.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChainStatus {
+    Valid,
+    /// The line could not be decrypted or parsed.
+    Unreadable,
+    /// The record does not match its own hash anymore.
+    Altered,
+    /// The record does not point to the one before, so records were removed,
+    /// added or reordered.
+    Broken,
+}
+
+#[derive(Debug, Clone)]
+pub struct VerifiedRecord {
+    /// `None` if the line is unreadable.
+    pub record: Option<AuditRecord>,
+    pub status: ChainStatus,
+}
+
+/// An append only log of everything done with the entries.
+///
+/// Every record is stored **encrypted** on its own line and holds the hash of
+/// the record before, so changing, removing or reordering records can be
+/// detected with [`AuditLog::verify`].
+pub struct AuditLog {
+    path: PathBuf,
+    last_hash: Option<String>,
+}
+impl AuditLog {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            last_hash: None,
+        }
+    }
+
+    /// Opens the log inside the data folder.
+    pub fn open_default() -> Self {
+        AuditLog::new(utils::AUDIT_PATH)
+    }
+
+    /// Adds a record to the end of the log.
+    pub fn append(
+        &mut self,
+        action: AuditAction,
+        entry_id: Option<&str>,
+    ) -> Result<AuditRecord, InternalError> {
+        self.append_as(action, entry_id, actor())
+    }
+
+    /// Adds a record of an action a program did on behalf of the user, like
+    /// a script using the local API. The program is named next to the user.
+    pub fn append_for_client(
+        &mut self,
+        action: AuditAction,
+        entry_id: Option<&str>,
+        client: &str,
+    ) -> Result<AuditRecord, InternalError> {
+        self.append_as(action, entry_id, format!("{} via {}", actor(), client))
+    }
+
+    fn append_as(
+        &mut self,
+        action: AuditAction,
+        entry_id: Option<&str>,
+        actor: String,
+    ) -> Result<AuditRecord, InternalError> {
+        let prev_hash = match &self.last_hash {
+            Some(hash) => hash.clone(),
+            None => self.read_last_hash()?,
+        };
+
+        let record = AuditRecord::new(&prev_hash, action, entry_id, actor);
+        let buffer = match serde_json::to_string(&record) {
+            Ok(s) => s,
+            Err(err) => return Err(DbError::AuditSerialize(err).into()),
+        };
+        let line = encrypt_text(&buffer, false, false)?;
+
+        let file = File::options().create(true).append(true).open(&self.path);
+        match file.and_then(|mut f| writeln!(f, "{}", line)) {
+            Ok(_) => {
+                self.last_hash = Some(record.hash.clone());
+                Ok(record)
+            }
+            Err(err) => Err(DbError::AuditWrite(err).into()),
+        }
+    }
+
+    /// Reads all records and checks if the chain is still intact.
+    pub fn verify(&self) -> Result<Vec<VerifiedRecord>, InternalError> {
+        if !self.path.exists() {
+            return Ok(Vec::<VerifiedRecord>::new());
+        }
+        let content = match fs::read_to_string(&self.path) {
+            Ok(s) => s,
+            Err(err) => return Err(DbError::AuditRead(err).into()),
+        };
+
+        // after an unreadable line, the next record can not be checked
+        // against the one before
+        let mut expected = Some(String::from(GENESIS_HASH));
+        let mut verified = Vec::<VerifiedRecord>::new();
+        for line in content.lines().filter(|l| !l.trim().is_empty()) {
+            let record = decrypt_text(line.trim(), false, false)
+                .ok()
+                .and_then(|s| serde_json::from_str::<AuditRecord>(&s).ok());
+
+            let status = match &record {
+                None => ChainStatus::Unreadable,
+                Some(r) if !r.matches_hash() => ChainStatus::Altered,
+                Some(r) if expected.as_ref().is_some_and(|e| e != &r.prev_hash) => {
+                    ChainStatus::Broken
+                }
+                Some(_) => ChainStatus::Valid,
+            };
+
+            expected = record.as_ref().map(|r| r.hash.clone());
+            verified.push(VerifiedRecord { record, status });
+        }
+
+        Ok(verified)
+    }
+
+    /// The hash of the last readable record, which the next one points to.
+    fn read_last_hash(&self) -> Result<String, InternalError> {
+        let last = self
+            .verify()?
+            .into_iter()
+            .rev()
+            .find_map(|v| v.record.map(|r| r.hash));
+
+        Ok(last.unwrap_or(String::from(GENESIS_HASH)))
+    }
+}
+
+/// The name of the user of the operating system.
+fn actor() -> String {
+    match std::env::var("USER").or_else(|_| std::env::var("USERNAME")) {
+        Ok(name) => name,
+        Err(_) => String::from("unknown"),
+    }
+}
@@ -0,0 +1,141 @@
+use crate::helpers::natural_cmp;
+
+use super::{
+    store::{load_config, save_config},
+    *,
+};
+
+/// What happens to the entries of a folder which gets deleted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FolderContents {
+    /// The entries and subfolders are moved into the parent folder.
+    MoveToParent,
+    /// The entries of the folder and all its subfolders are moved into the
+    /// trash.
+    Trash,
+}
+
+/// Cleans up a folder path, e.g. ` Work//AWS/ ` becomes `Work/AWS`. The empty
+/// path is the top level.
+pub fn normalize(path: &str) -> String {
+    path.split('/')
+        .map(|part| part.trim())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<&str>>()
+        .join("/")
+}
+
+/// The folder the given one is inside of.
+pub fn parent(path: &str) -> String {
+    match path.rsplit_once('/') {
+        Some((parent, _)) => String::from(parent),
+        None => String::new(),
+    }
+}
+
+/// The last part of the path.
+pub fn name(path: &str) -> &str {
+    match path.rsplit_once('/') {
+        Some((_, name)) => name,
+        None => path,
+    }
+}
+
+/// Checks if the path is the folder itself or one of its subfolders.
+pub fn is_inside(path: &str, folder: &str) -> bool {
+    folder.is_empty()
+        || path == folder
+        || (path.starts_with(folder) && path[folder.len()..].starts_with('/'))
+}
+
+/// The path after the folder `from` was renamed to `to`, if the path is
+/// inside of it.
+pub fn renamed(path: &str, from: &str, to: &str) -> Option<String> {
+    if from.is_empty() || !is_inside(path, from) {
+        return None;
+    }
+
+    Some(normalize(&format!("{}/{}", to, &path[from.len()..])))
+}
+
+/// The folders created by the user, which are kept inside the config of the
+/// vault.
+pub fn load_folders() -> Result<Vec<String>, InternalError> {
+    Ok(load_config()?.folders)
+}
+
+pub fn save_folders(folders: &[String]) -> Result<(), InternalError> {
+    let mut config = load_config()?;
+    config.folders = folders.to_vec();
+
+    save_config(&config)
+}
+
+/// A folder together with its subfolders and the ids of its entries.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FolderNode {
+    pub path: String,
+    pub folders: Vec<FolderNode>,
+    pub entries: Vec<String>,
+}
+impl FolderNode {
+    /// Builds the tree of all given folders and the folders of the entries.
+    /// The entries keep their order, the folders are sorted by their name.
+    pub fn build(folders: &[String], entries: &[&Entry]) -> Self {
+        let mut root = FolderNode::default();
+        for folder in folders {
+            root.node_mut(&normalize(folder));
+        }
+        for entry in entries {
+            root.node_mut(&entry.folder).entries.push(entry.id());
+        }
+        root.sort();
+
+        root
+    }
+
+    pub fn name(&self) -> &str {
+        name(&self.path)
+    }
+
+    /// The number of entries inside the folder and all its subfolders.
+    pub fn count(&self) -> usize {
+        self.entries.len() + self.folders.iter().map(|f| f.count()).sum::<usize>()
+    }
+
+    /// Checks if the entry is inside the folder or one of its subfolders.
+    pub fn contains(&self, id: &str) -> bool {
+        self.entries.iter().any(|e| e == id) || self.folders.iter().any(|f| f.contains(id))
+    }
+
+    /// Gets the node of the path, creating it and its parents if needed.
+    fn node_mut(&mut self, path: &str) -> &mut FolderNode {
+        let mut node = self;
+        for part in path.split('/').filter(|p| !p.is_empty()) {
+            let child = match node.path.is_empty() {
+                true => String::from(part),
+                false => format!("{}/{}", node.path, part),
+            };
+            let index = match node.folders.iter().position(|f| f.path == child) {
+                Some(index) => index,
+                None => {
+                    node.folders.push(FolderNode {
+                        path: child,
+                        ..Default::default()
+                    });
+                    node.folders.len() - 1
+                }
+            };
+            node = &mut node.folders[index];
+        }
+
+        node
+    }
+
+    fn sort(&mut self) {
+        self.folders.sort_by(|a, b| natural_cmp(a.name(), b.name()));
+        for folder in self.folders.iter_mut() {
+            folder.sort();
+        }
+    }
+}
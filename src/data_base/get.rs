@@ -1,6 +1,6 @@
 use std::fs::{self, File};
 
-use crate::helpers::InternalError;
+use crate::error::{DbError, InternalError};
 
 use super::*;
 
@@ -16,49 +16,12 @@ pub fn get_master() -> Result<String, InternalError> {
                 Ok(s) => {
                     match serde_json::from_str::<utils::Master>(&s) {
                         Ok(json) => Ok(json.master),
-                        Err(_) => Err(InternalError::new(
-                            "[DB_G_GM-1]",
-                            "Failed to convert buffer",
-                        ))
+                        Err(err) => Err(DbError::MasterParse(err).into())
                     }
                 },
-                Err(_) => Err(InternalError::new(
-                    "[DB_G_GM-2]",
-                    "Failed to read contents"
-                ))
+                Err(err) => Err(DbError::MasterRead(err).into())
             }
         },
-        Err(_) => Err(InternalError::new(
-            "[DB_G_GM-3]",
-            "Failed to load file",
-        ))
+        Err(err) => Err(DbError::MasterOpen(err).into())
     }
 }
-
-/// Gets the **encrypted** entries
-pub fn get_entries() -> Result<Vec<Entry>, InternalError> {
-    let file = File::options()
-        .read(true)
-        .open(utils::DB_PATH);
-
-    match file {
-        Ok(_) => {
-            match fs::read_to_string(utils::DB_PATH) {
-                Ok(s) => {
-                    match serde_json::from_str::<utils::Entries>(&s) {
-                        Ok(json) => Ok(json.entries),
-                        Err(_) => Err(InternalError::new(
-                            "[DB_G_GE-1]",
-                            "Failed to convert buffer",
-                        ))
-                    }
-                },
-                Err(_) => Err(InternalError::new(
-                    "[DB_G_GE-2]",
-                    "Failed to read contents"
-                ))
-            }
-        },
-        Err(_) => Ok(Vec::<Entry>::new())
-    }
-}
\ No newline at end of file
@@ -0,0 +1,116 @@
+use super::{repository::Repository, *};
+
+/// A change done on the entries of a vault.
+///
+/// Every action holds the **encrypted** snapshots of the entry, which are
+/// needed to revert or repeat it. Only what the action changed is taken from
+/// them, and an added entry is undone by moving it into the trash.
+#[derive(Debug, Clone)]
+pub enum Action {
+    Added(Entry),
+    /// The snapshots are boxed, as they would make every action twice as
+    /// large as an entry.
+    Edited {
+        before: Box<Entry>,
+        after: Box<Entry>,
+    },
+    /// Holds the entry as it was before it was moved into the trash.
+    Deleted(Entry),
+}
+impl Action {
+    /// The id of the entry the action is about.
+    pub fn id(&self) -> String {
+        match self {
+            Action::Added(entry) => entry.id(),
+            Action::Edited { after, .. } => after.id(),
+            Action::Deleted(entry) => entry.id(),
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            Action::Added(entry) => format!("Added \"{}\"", entry.name),
+            Action::Edited { after, .. } => format!("Edited \"{}\"", after.name),
+            Action::Deleted(entry) => format!("Deleted \"{}\"", entry.name),
+        }
+    }
+}
+
+/// The undo and redo stacks of one session.
+#[derive(Debug, Default)]
+pub struct History {
+    undo: Vec<Action>,
+    redo: Vec<Action>,
+}
+impl History {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an action which was just done. Everything that could be redone
+    /// until now is dropped.
+    pub fn record(&mut self, action: Action) {
+        log::debug!("Recorded a change of entry {}", action.id());
+        self.undo.push(action);
+        self.redo.clear();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Reverts the last action and returns it. If reverting fails, the action
+    /// stays on the stack so it can be tried again.
+    pub fn undo(&mut self, repository: &mut Repository) -> Result<Option<Action>, InternalError> {
+        let action = match self.undo.pop() {
+            Some(a) => a,
+            None => return Ok(None),
+        };
+
+        let result = match &action {
+            Action::Added(entry) => repository.delete(&entry.id).map(|_| ()),
+            Action::Edited { before, after } => repository.replay_edit(after, before).map(|_| ()),
+            Action::Deleted(entry) => repository.put_back(entry).map(|_| ()),
+        };
+
+        match result {
+            Ok(_) => {
+                self.redo.push(action.clone());
+                Ok(Some(action))
+            }
+            Err(err) => {
+                self.undo.push(action);
+                Err(err)
+            }
+        }
+    }
+
+    /// Repeats the last reverted action and returns it.
+    pub fn redo(&mut self, repository: &mut Repository) -> Result<Option<Action>, InternalError> {
+        let action = match self.redo.pop() {
+            Some(a) => a,
+            None => return Ok(None),
+        };
+
+        let result = match &action {
+            Action::Added(entry) => repository.put_back(entry).map(|_| ()),
+            Action::Edited { before, after } => repository.replay_edit(before, after).map(|_| ()),
+            Action::Deleted(entry) => repository.delete(&entry.id).map(|_| ()),
+        };
+
+        match result {
+            Ok(_) => {
+                self.undo.push(action.clone());
+                Ok(Some(action))
+            }
+            Err(err) => {
+                self.redo.push(action);
+                Err(err)
+            }
+        }
+    }
+}
@@ -6,26 +6,9 @@ use std::{
 
 use utils::{Master, DATA_FOLDER};
 
-use super::*;
-
-/// Adds an entry to the list and saves the list immediately
-pub fn add_entry(
-    name: &str,
-    user_name: &str,
-    password: &str,
-    mut current_list: Vec<Entry>,
-) -> Result<Vec<Entry>, InternalError> {
-    let new_entry = match Entry::new(name, user_name, password) {
-        Ok(e) => e,
-        Err(err) => return Err(err),
-    };
-    current_list.push(new_entry);
+use crate::error::DbError;
 
-    match save_current_list(current_list.clone()) {
-        Ok(_) => Ok(current_list),
-        Err(err) => Err(err),
-    }
-}
+use super::*;
 
 /// Saves the Master Password as an encrypted String and creates the directory
 /// for all the saved data.
@@ -41,20 +24,14 @@ pub fn save_master(master_password: &str) -> Result<(), InternalError> {
 
                 match dir {
                     Ok(_) => {}
-                    Err(_) => {
-                        return Err(InternalError::new(
-                            "[DB_P_SM-1]",
-                            &format!("Failed to create folder {}.", DATA_FOLDER),
-                        ));
+                    Err(err) => {
+                        return Err(DbError::FolderCreate(err).into());
                     }
                 }
             }
         }
-        Err(_) => {
-            return Err(InternalError::new(
-                "[DB_P_SM-1]",
-                &format!("Could not check if folder {} exists.", DATA_FOLDER),
-            ));
+        Err(err) => {
+            return Err(DbError::FolderCheck(err).into());
         }
     }
 
@@ -68,7 +45,7 @@ pub fn save_master(master_password: &str) -> Result<(), InternalError> {
     };
     let buffer = match serde_json::to_string_pretty(&master) {
         Ok(s) => s,
-        Err(_) => return Err(InternalError::new("[DB_P_SM-1]", "Failed to write buffer")),
+        Err(err) => return Err(DbError::MasterSerialize(err).into()),
     };
 
     let file = File::options()
@@ -78,15 +55,9 @@ pub fn save_master(master_password: &str) -> Result<(), InternalError> {
 
     match file {
         Ok(mut f) => {
-            // the unwrap() call is safe since we ensure in the
-            // declaration that we have write access
-            f.set_len(0).unwrap();
-            match f.write_all(buffer.as_bytes()) {
+            match f.set_len(0).and_then(|_| f.write_all(buffer.as_bytes())) {
                 Ok(_) => Ok(()),
-                Err(_) => Err(InternalError::new(
-                    "[DB_P_SM-2]",
-                    "Failed to save buffer in file",
-                )),
+                Err(err) => Err(DbError::MasterWrite(err).into()),
             }
         }
         Err(_) => {
@@ -99,60 +70,13 @@ pub fn save_master(master_password: &str) -> Result<(), InternalError> {
             match new_file {
                 Ok(mut f) => match f.write_all(buffer.as_bytes()) {
                     Ok(_) => Ok(()),
-                    Err(_) => Err(InternalError::new(
-                        "[DB_P_SM-3]",
-                        "Failed to save buffer in new file",
-                    )),
+                    Err(err) => Err(DbError::MasterWriteNew(err).into()),
                 },
                 Err(err) => {
-                    dbg!(err);
-                    Err(InternalError::new("[DB_P_SM-4]", "Failed to create file"))
+                    log::error!("Failed to create the master file: {}", err);
+                    Err(DbError::MasterCreate(err).into())
                 }
             }
         }
     }
 }
-
-/// Saves the current state of the entries
-pub fn save_current_list(list: Vec<Entry>) -> Result<(), InternalError> {
-    let file = File::options().read(true).write(true).open(utils::DB_PATH);
-
-    let entries = utils::Entries { entries: list };
-    let buffer = match serde_json::to_string_pretty(&entries) {
-        Ok(s) => s,
-        Err(_) => return Err(InternalError::new("[DB_P_SCL-1]", "Failed to write buffer")),
-    };
-
-    match file {
-        Ok(mut f) => {
-            // the unwrap() call is safe since we ensure in the
-            // declaration that we have write access
-            f.set_len(0).unwrap();
-            match f.write_all(buffer.as_bytes()) {
-                Ok(_) => Ok(()),
-                Err(_) => Err(InternalError::new(
-                    "[DB_P_SCL-2]",
-                    "Failed to save buffer in file",
-                )),
-            }
-        }
-        Err(_) => {
-            let new_file = File::options()
-                .read(true)
-                .write(true)
-                .create_new(true)
-                .open(utils::DB_PATH);
-
-            match new_file {
-                Ok(mut f) => match f.write_all(buffer.as_bytes()) {
-                    Ok(_) => Ok(()),
-                    Err(_) => Err(InternalError::new(
-                        "[DB_P_SCL-3]",
-                        "Failed to save buffer in new file",
-                    )),
-                },
-                Err(_) => Err(InternalError::new("[DB_P_SCL-4]", "Failed to create file")),
-            }
-        }
-    }
-}
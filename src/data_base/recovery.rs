@@ -0,0 +1,91 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::error::DbError;
+
+use super::{
+    store::{
+        load_config, migrate, open_backend, Backend, JsonStore, MemoryStore, SqliteStore,
+        VaultStore,
+    },
+    *,
+};
+
+/// Moves a broken file aside, so a new one can be created in its place
+/// without losing what was inside.
+///
+/// Returns where the file was moved to, or `None` if there was no file.
+pub fn preserve_broken(path: impl AsRef<Path>) -> Result<Option<PathBuf>, InternalError> {
+    let path = path.as_ref();
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let target = PathBuf::from(format!(
+        "{}.broken-{}",
+        path.display(),
+        chrono::Local::now().timestamp()
+    ));
+
+    match fs::rename(path, &target) {
+        Ok(_) => Ok(Some(target)),
+        Err(err) => Err(DbError::PreserveBroken(err).into()),
+    }
+}
+
+/// Starts over with an empty store. The broken store is kept next to the new
+/// one.
+pub fn start_fresh() -> Result<Box<dyn VaultStore>, InternalError> {
+    let backend = recovery_backend()?;
+    preserve_broken(backend.path())?;
+
+    open_backend(backend)
+}
+
+/// Moves a broken master file aside, so a new master password can be set.
+/// The entries are kept as they are.
+pub fn reset_master() -> Result<(), InternalError> {
+    preserve_broken(utils::MASTER_PATH)?;
+
+    Ok(())
+}
+
+/// Replaces the broken store with the entries of a backup, which is either a
+/// `json` file or a SQLite database. The broken store is kept next to the new
+/// one.
+///
+/// The backup is loaded before anything is touched, so a broken backup leaves
+/// the vault as it is.
+pub fn restore_backup(backup: impl AsRef<Path>) -> Result<Box<dyn VaultStore>, InternalError> {
+    let backup = backup.as_ref();
+    if !backup.is_file() {
+        return Err(DbError::BackupNotFound.into());
+    }
+
+    let source: Box<dyn VaultStore> = match backup.extension().and_then(|e| e.to_str()) {
+        Some("sqlite") => Box::new(SqliteStore::open(backup)?),
+        _ => Box::new(JsonStore::new(backup)),
+    };
+    // the entries are kept in memory, since the backup could be the broken
+    // store itself, which is moved aside before the migration
+    let entries = MemoryStore::with_entries(source.load()?);
+
+    let mut store = start_fresh()?;
+    migrate(&entries, store.as_mut())?;
+
+    Ok(store)
+}
+
+/// The backend the vault uses. A config which can not be loaded is kept aside
+/// as well and the default backend is used instead.
+fn recovery_backend() -> Result<Backend, InternalError> {
+    match load_config() {
+        Ok(config) => Ok(config.backend),
+        Err(_) => {
+            preserve_broken(utils::CONFIG_PATH)?;
+            Ok(Backend::default())
+        }
+    }
+}
@@ -0,0 +1,750 @@
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BTreeMap,
+    sync::atomic::{AtomicU64, Ordering as AtomicOrdering},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::DbError,
+    helpers::{natural_cmp, url_origin},
+};
+
+use super::{
+    attachment::{Attachment, AttachmentFolder, MAX_ATTACHMENT_SIZE, MAX_ENTRY_ATTACHMENTS_SIZE},
+    audit::{AuditAction, AuditLog},
+    folder::{self, FolderContents},
+    kind::Payload,
+    store::{Change, MemoryStore, VaultStore},
+    *,
+};
+
+/// The order in which the entries are listed. Favorites are always on top.
+#[derive(Debug, Deserialize, Serialize, Default, Clone, Copy, PartialEq)]
+pub enum SortMode {
+    #[default]
+    Name,
+    /// The newest entries first.
+    Created,
+    /// The last edited entries first.
+    Edited,
+    /// The last used entries first, the never used ones by their name.
+    Recent,
+}
+impl SortMode {
+    pub const ALL: [SortMode; 4] = [
+        SortMode::Name,
+        SortMode::Created,
+        SortMode::Edited,
+        SortMode::Recent,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SortMode::Name => "Name",
+            SortMode::Created => "Created",
+            SortMode::Edited => "Last Edited",
+            SortMode::Recent => "Recently Used",
+        }
+    }
+
+    fn compare(&self, a: &Entry, b: &Entry) -> Ordering {
+        let ordering = match self {
+            SortMode::Name => Ordering::Equal,
+            SortMode::Created => b.created_at.cmp(&a.created_at),
+            SortMode::Edited => b.last_edited.cmp(&a.last_edited),
+            SortMode::Recent => b.last_used.cmp(&a.last_used),
+        };
+
+        b.favorite
+            .cmp(&a.favorite)
+            .then(ordering)
+            .then_with(|| natural_cmp(&a.name, &b.name))
+    }
+}
+
+/// Counts the changes of all repositories, so no two states share a revision.
+static REVISIONS: AtomicU64 = AtomicU64::new(0);
+
+fn next_revision() -> u64 {
+    REVISIONS.fetch_add(1, AtomicOrdering::Relaxed) + 1
+}
+
+/// Holds the entries of a vault and keeps them in sync with its store.
+///
+/// All changes on entries should go through here, the store is only written
+/// when a change succeeded and the list is only changed when the store was
+/// written successfully.
+pub struct Repository {
+    store: Box<dyn VaultStore>,
+    entries: Vec<Entry>,
+    audit: Option<AuditLog>,
+    attachments: AttachmentFolder,
+    revision: u64,
+}
+impl Repository {
+    /// Opens the repository and loads all entries of the store.
+    pub fn open(store: Box<dyn VaultStore>) -> Result<Self, InternalError> {
+        let entries = store.load()?;
+
+        Ok(Self {
+            store,
+            entries,
+            audit: None,
+            attachments: AttachmentFolder::default(),
+            revision: next_revision(),
+        })
+    }
+
+    /// Keeps the files of the attachments inside another folder than the
+    /// default one next to the entries.
+    pub fn with_attachment_folder(mut self, folder: AttachmentFolder) -> Self {
+        self.attachments = folder;
+        self
+    }
+
+    /// Records every change on the entries inside the audit log.
+    pub fn with_audit(mut self, audit: AuditLog) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    pub fn audit_log(&self) -> Option<&AuditLog> {
+        self.audit.as_ref()
+    }
+
+    /// Adds a record to the audit log, if there is one. Used for actions that
+    /// do not change the entry, like copying its password.
+    ///
+    /// The action already happened at this point, so failing to record it is
+    /// only logged.
+    pub fn audit(&mut self, action: AuditAction, id: Option<&str>) {
+        if let Some(audit) = self.audit.as_mut() {
+            if let Err(err) = audit.append(action, id) {
+                log::error!("Failed to record {:?} in the audit log: {:?}", action, err);
+            }
+        }
+    }
+
+    /// Like [`Repository::audit`], for an action a client of the local API
+    /// did.
+    pub fn audit_for_client(&mut self, action: AuditAction, id: Option<&str>, client: &str) {
+        if let Some(audit) = self.audit.as_mut() {
+            if let Err(err) = audit.append_for_client(action, id, client) {
+                log::error!("Failed to record {:?} in the audit log: {:?}", action, err);
+            }
+        }
+    }
+
+    /// A repository without any entries, which only lives in memory. Used
+    /// while the store of the vault can not be opened.
+    pub fn empty() -> Self {
+        Self {
+            store: Box::new(MemoryStore::new()),
+            entries: Vec::<Entry>::new(),
+            audit: None,
+            attachments: AttachmentFolder::default(),
+            revision: next_revision(),
+        }
+    }
+
+    /// Changes every time the entries may have changed, and differs between
+    /// repositories. Lets views which are costly to build skip unchanged
+    /// entries.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// The store for writing, which counts as a change of the entries.
+    fn store_mut(&mut self) -> &mut dyn VaultStore {
+        self.revision = next_revision();
+        self.store.as_mut()
+    }
+
+    /// Loads the entries of the store again, dropping the current list.
+    pub fn reload(&mut self) -> Result<(), InternalError> {
+        self.entries = self.store.load()?;
+        self.revision = next_revision();
+
+        Ok(())
+    }
+
+    /// All **encrypted** entries in the order they were added. Entries inside
+    /// the trash are not part of the list.
+    pub fn list(&self) -> Vec<&Entry> {
+        self.entries.iter().filter(|e| !e.is_deleted()).collect()
+    }
+
+    /// All entries outside the trash in the given order.
+    pub fn sorted(&self, mode: SortMode) -> Vec<&Entry> {
+        let mut entries = self.list();
+        entries.sort_by(|a, b| mode.compare(a, b));
+
+        entries
+    }
+
+    /// The favorites by their name.
+    pub fn favorites(&self) -> Vec<&Entry> {
+        self.sorted(SortMode::Name)
+            .into_iter()
+            .filter(|e| e.favorite)
+            .collect()
+    }
+
+    /// The entries used the most recently, the last used one first.
+    pub fn recent(&self, limit: usize) -> Vec<&Entry> {
+        let mut used = self
+            .list()
+            .into_iter()
+            .filter(|e| e.last_used.is_some())
+            .collect::<Vec<&Entry>>();
+        used.sort_by_key(|e| Reverse(e.last_used));
+        used.truncate(limit);
+
+        used
+    }
+
+    /// All entries inside the trash.
+    pub fn trash(&self) -> Vec<&Entry> {
+        self.entries.iter().filter(|e| e.is_deleted()).collect()
+    }
+
+    /// Gets an entry, no matter if it is inside the trash or not.
+    pub fn get(&self, id: &str) -> Option<&Entry> {
+        self.entries.iter().find(|e| e.id == id)
+    }
+
+    /// Finds all entries outside the trash whose url belongs to the same site
+    /// as the origin, e.g. `https://github.com`.
+    pub fn find_by_origin(&self, origin: &str) -> Vec<&Entry> {
+        let origin = match url_origin(origin) {
+            Some(o) => o,
+            None => return Vec::new(),
+        };
+
+        self.list()
+            .into_iter()
+            .filter(|e| url_origin(&e.url).as_deref() == Some(origin.as_str()))
+            .collect()
+    }
+
+    /// Finds all entries whose name contains the query, ignoring the case.
+    pub fn find_by_name(&self, query: &str) -> Vec<&Entry> {
+        let query = query.to_lowercase();
+
+        self.list()
+            .into_iter()
+            .filter(|e| e.name.to_lowercase().contains(&query))
+            .collect()
+    }
+
+    /// Creates a new entry and stores it.
+    pub fn add(
+        &mut self,
+        name: &str,
+        user_name: &str,
+        password: &str,
+    ) -> Result<&Entry, InternalError> {
+        let payload = Payload::Login {
+            user_name: String::from(user_name),
+            password: String::from(password),
+        };
+        self.add_payload(name, &payload)
+    }
+
+    /// Creates a new entry of the kind of the payload and stores it.
+    pub fn add_payload(&mut self, name: &str, payload: &Payload) -> Result<&Entry, InternalError> {
+        let entry = Entry::with_payload(name, payload)?;
+        self.store_mut().insert(&entry)?;
+        self.audit(AuditAction::Added, Some(&entry.id));
+        self.entries.push(entry);
+
+        Ok(&self.entries[self.entries.len() - 1])
+    }
+
+    /// Changes the values of an entry and stores it.
+    pub fn update(
+        &mut self,
+        id: &str,
+        name: &str,
+        user_name: &str,
+        password: &str,
+    ) -> Result<&Entry, InternalError> {
+        let payload = Payload::Login {
+            user_name: String::from(user_name),
+            password: String::from(password),
+        };
+        self.update_payload(id, name, &payload)
+    }
+
+    /// Changes the name and values of an entry and stores it.
+    pub fn update_payload(
+        &mut self,
+        id: &str,
+        name: &str,
+        payload: &Payload,
+    ) -> Result<&Entry, InternalError> {
+        let index = self.index_of(id)?;
+
+        let mut edited = self.entries[index].clone();
+        edited.edit_payload(name, payload)?;
+        self.store_mut().update(&edited)?;
+        self.audit(AuditAction::Edited, Some(id));
+        self.entries[index] = edited;
+
+        Ok(&self.entries[index])
+    }
+
+    /// Changes the address of the site the entry is used on.
+    pub fn set_url(&mut self, id: &str, url: &str) -> Result<&Entry, InternalError> {
+        let index = self.index_of(id)?;
+
+        let mut changed = self.entries[index].clone();
+        changed.url = String::from(url.trim());
+        changed.edited();
+        self.store_mut().update(&changed)?;
+        self.entries[index] = changed;
+
+        Ok(&self.entries[index])
+    }
+
+    /// Replaces the tags of the entry. Empty tags and ones which are there
+    /// twice, ignoring the case, are left out.
+    pub fn set_tags(&mut self, id: &str, tags: &[&str]) -> Result<&Entry, InternalError> {
+        let index = self.index_of(id)?;
+
+        let mut cleaned = Vec::<String>::new();
+        for tag in tags.iter().map(|t| t.trim()) {
+            if !tag.is_empty() && !cleaned.iter().any(|c| c.eq_ignore_ascii_case(tag)) {
+                cleaned.push(String::from(tag));
+            }
+        }
+
+        let mut changed = self.entries[index].clone();
+        changed.tags = cleaned;
+        changed.edited();
+        self.store_mut().update(&changed)?;
+        self.entries[index] = changed;
+
+        Ok(&self.entries[index])
+    }
+
+    /// Replaces the attributes other apps find the entry by.
+    pub fn set_attributes(
+        &mut self,
+        id: &str,
+        attributes: BTreeMap<String, String>,
+    ) -> Result<&Entry, InternalError> {
+        let index = self.index_of(id)?;
+
+        let mut changed = self.entries[index].clone();
+        changed.attributes = attributes;
+        changed.edited();
+        self.store_mut().update(&changed)?;
+        self.entries[index] = changed;
+
+        Ok(&self.entries[index])
+    }
+
+    /// Finds all entries with the tag, ignoring the case.
+    pub fn find_by_tag(&self, tag: &str) -> Vec<&Entry> {
+        self.list().into_iter().filter(|e| e.has_tag(tag)).collect()
+    }
+
+    /// Pins the entry on top of the list, or removes it from there.
+    pub fn set_favorite(&mut self, id: &str, favorite: bool) -> Result<&Entry, InternalError> {
+        let index = self.index_of(id)?;
+
+        let mut changed = self.entries[index].clone();
+        changed.favorite = favorite;
+        self.store_mut().update(&changed)?;
+        self.entries[index] = changed;
+
+        Ok(&self.entries[index])
+    }
+
+    /// Remembers that the username or password of the entry was just used.
+    pub fn mark_used(&mut self, id: &str) -> Result<&Entry, InternalError> {
+        let index = self.index_of(id)?;
+
+        let mut changed = self.entries[index].clone();
+        changed.last_used = Some(chrono::Local::now().timestamp());
+        self.store_mut().update(&changed)?;
+        self.entries[index] = changed;
+
+        Ok(&self.entries[index])
+    }
+
+    /// Encrypts the file with a key of its own and attaches it to the entry.
+    pub fn add_attachment(
+        &mut self,
+        id: &str,
+        name: &str,
+        data: &[u8],
+    ) -> Result<&Entry, InternalError> {
+        let index = self.index_of(id)?;
+        let size = data.len() as u64;
+        if size > MAX_ATTACHMENT_SIZE {
+            return Err(DbError::AttachmentTooLarge.into());
+        }
+        if self.entries[index].attachments_size() + size > MAX_ENTRY_ATTACHMENTS_SIZE {
+            return Err(DbError::AttachmentsFull.into());
+        }
+
+        let (attachment, encrypted) = Attachment::encrypt(name, data)?;
+        self.attachments.write(&attachment.id, &encrypted)?;
+
+        let mut changed = self.entries[index].clone();
+        changed.attachments.push(attachment.clone());
+        changed.edited();
+        if let Err(err) = self.store_mut().update(&changed) {
+            self.discard_attachment(&attachment.id);
+            return Err(err);
+        }
+        self.audit(AuditAction::Edited, Some(id));
+        self.entries[index] = changed;
+
+        Ok(&self.entries[index])
+    }
+
+    /// The **decrypted** contents of an attachment of the entry.
+    pub fn read_attachment(&self, id: &str, attachment_id: &str) -> Result<Vec<u8>, InternalError> {
+        let attachment = self
+            .get(id)
+            .and_then(|e| e.attachments.iter().find(|a| a.id == attachment_id));
+
+        match attachment {
+            Some(a) => a.decrypt(&self.attachments.read(&a.id)?),
+            None => Err(DbError::AttachmentNotFound.into()),
+        }
+    }
+
+    /// Removes the attachment from the entry and deletes its file.
+    pub fn remove_attachment(
+        &mut self,
+        id: &str,
+        attachment_id: &str,
+    ) -> Result<&Entry, InternalError> {
+        let index = self.index_of(id)?;
+
+        let mut changed = self.entries[index].clone();
+        let count = changed.attachments.len();
+        changed.attachments.retain(|a| a.id != attachment_id);
+        if changed.attachments.len() == count {
+            return Err(DbError::AttachmentNotFound.into());
+        }
+        changed.edited();
+        self.store_mut().update(&changed)?;
+        self.audit(AuditAction::Edited, Some(id));
+        self.entries[index] = changed;
+        self.discard_attachment(attachment_id);
+
+        Ok(&self.entries[index])
+    }
+
+    /// Deletes the files no entry refers to anymore, e.g. the ones which
+    /// could not be deleted together with their entry.
+    ///
+    /// Returns the number of deleted files.
+    pub fn clean_attachments(&self) -> Result<usize, InternalError> {
+        let used = self
+            .entries
+            .iter()
+            .flat_map(|e| e.attachments.iter().map(|a| a.id.as_str()))
+            .collect::<Vec<&str>>();
+
+        let mut removed = 0;
+        for id in self.attachments.ids()? {
+            if !used.contains(&id.as_str()) {
+                self.attachments.remove(&id)?;
+                removed += 1;
+            }
+        }
+
+        Ok(removed)
+    }
+
+    /// Moves the entry into the folder of the given path.
+    pub fn move_to_folder(&mut self, id: &str, folder: &str) -> Result<&Entry, InternalError> {
+        let index = self.index_of(id)?;
+
+        let mut changed = self.entries[index].clone();
+        changed.folder = folder::normalize(folder);
+        self.store_mut().update(&changed)?;
+        self.audit(AuditAction::Edited, Some(id));
+        self.entries[index] = changed;
+
+        Ok(&self.entries[index])
+    }
+
+    /// Renames or moves a folder together with everything inside of it,
+    /// including the entries inside the trash.
+    ///
+    /// Returns the number of moved entries.
+    pub fn rename_folder(&mut self, from: &str, to: &str) -> Result<usize, InternalError> {
+        let (from, to) = (folder::normalize(from), folder::normalize(to));
+        if !from.is_empty() && !to.is_empty() && folder::is_inside(&to, &from) && to != from {
+            return Err(DbError::FolderInsideItself.into());
+        }
+
+        let changed = self
+            .entries
+            .iter()
+            .filter_map(|e| {
+                folder::renamed(&e.folder, &from, &to).map(|path| {
+                    let mut changed = e.clone();
+                    changed.folder = path;
+                    changed
+                })
+            })
+            .collect::<Vec<Entry>>();
+        self.store_changes(changed, AuditAction::Edited)
+    }
+
+    /// Deletes a folder, the entries inside are either moved up or into the
+    /// trash.
+    ///
+    /// Returns the number of moved entries.
+    pub fn delete_folder(
+        &mut self,
+        path: &str,
+        contents: FolderContents,
+    ) -> Result<usize, InternalError> {
+        let path = folder::normalize(path);
+        match contents {
+            FolderContents::MoveToParent => self.rename_folder(&path, &folder::parent(&path)),
+            FolderContents::Trash => {
+                let now = chrono::Local::now().timestamp();
+                let changed = self
+                    .list()
+                    .into_iter()
+                    .filter(|e| !path.is_empty() && folder::is_inside(&e.folder, &path))
+                    .map(|e| {
+                        let mut changed = e.clone();
+                        changed.deleted_at = Some(now);
+                        changed
+                    })
+                    .collect::<Vec<Entry>>();
+                self.store_changes(changed, AuditAction::Deleted)
+            }
+        }
+    }
+
+    /// Moves an entry into the trash and returns it. It can be restored from
+    /// there until it gets purged. An entry already inside the trash is left
+    /// as it is, so deleting it again does not delay its purge.
+    pub fn delete(&mut self, id: &str) -> Result<&Entry, InternalError> {
+        let index = self.index_of(id)?;
+        if self.entries[index].is_deleted() {
+            return Ok(&self.entries[index]);
+        }
+
+        let now = chrono::Local::now().timestamp();
+        self.set_deleted(id, Some(now), AuditAction::Deleted)
+    }
+
+    /// Moves an entry out of the trash again.
+    pub fn restore(&mut self, id: &str) -> Result<&Entry, InternalError> {
+        self.set_deleted(id, None, AuditAction::Restored)
+    }
+
+    /// Removes an entry inside the trash from the store for good.
+    pub fn purge(&mut self, id: &str) -> Result<Entry, InternalError> {
+        let index = self.index_of(id)?;
+        if !self.entries[index].is_deleted() {
+            return Err(DbError::NotInTrash.into());
+        }
+
+        self.remove(id)
+    }
+
+    /// Purges all entries which are inside the trash for more than the given
+    /// number of days.
+    ///
+    /// Returns the number of purged entries.
+    pub fn purge_expired(&mut self, days: u32) -> Result<usize, InternalError> {
+        let limit = chrono::Local::now().timestamp() - i64::from(days) * 24 * 60 * 60;
+        let expired = self
+            .trash()
+            .into_iter()
+            .filter(|e| e.deleted_at.unwrap_or(i64::MAX) <= limit)
+            .map(|e| e.id())
+            .collect::<Vec<String>>();
+
+        if expired.is_empty() {
+            return Ok(0);
+        }
+
+        let changes = expired
+            .iter()
+            .map(|id| Change::Delete(id.clone()))
+            .collect::<Vec<Change>>();
+        self.store_mut().transaction(&changes)?;
+        let purged = self
+            .entries
+            .iter()
+            .filter(|e| expired.contains(&e.id))
+            .cloned()
+            .collect::<Vec<Entry>>();
+        for entry in purged.iter() {
+            self.discard_attachments(entry);
+        }
+        self.entries.retain(|e| !expired.contains(&e.id));
+        for id in &expired {
+            self.audit(AuditAction::Purged, Some(id));
+        }
+
+        Ok(expired.len())
+    }
+
+    /// Reverts or repeats an edit on the entry as it is now. Only the values
+    /// which differ between the snapshots are set to the ones of `to`, so
+    /// changes which were not recorded, like marking it a favorite, are kept.
+    pub(crate) fn replay_edit(
+        &mut self,
+        from: &Entry,
+        to: &Entry,
+    ) -> Result<&Entry, InternalError> {
+        let index = self.index_of(&to.id)?;
+
+        let mut changed = self.entries[index].clone();
+        if from.name != to.name {
+            changed.name = to.name.clone();
+        }
+        if from.kind != to.kind
+            || from.user_name != to.user_name
+            || from.password != to.password
+            || from.payload != to.payload
+        {
+            changed.kind = to.kind;
+            changed.user_name = to.user_name.clone();
+            changed.password = to.password.clone();
+            changed.payload = to.payload.clone();
+        }
+        if from.url != to.url {
+            changed.url = to.url.clone();
+        }
+        if from.tags != to.tags {
+            changed.tags = to.tags.clone();
+        }
+        if from.folder != to.folder {
+            changed.folder = to.folder.clone();
+        }
+        changed.edited();
+        self.store_mut().update(&changed)?;
+        self.audit(AuditAction::Edited, Some(&changed.id));
+        self.entries[index] = changed;
+
+        Ok(&self.entries[index])
+    }
+
+    /// Brings an entry back into the list. An entry inside the trash is
+    /// restored as it is now, and one which was removed for good is added
+    /// again from the snapshot.
+    ///
+    /// Attachments are not part of a snapshot, their files are deleted right
+    /// away. An entry added again only gets back the ones whose files are
+    /// still there.
+    pub(crate) fn put_back(&mut self, snapshot: &Entry) -> Result<&Entry, InternalError> {
+        if let Ok(index) = self.index_of(&snapshot.id) {
+            return match self.entries[index].is_deleted() {
+                true => self.restore(&snapshot.id),
+                false => Ok(&self.entries[index]),
+            };
+        }
+
+        let mut restored = snapshot.clone();
+        restored.deleted_at = None;
+        restored
+            .attachments
+            .retain(|a| self.attachments.contains(&a.id));
+        self.store_mut().insert(&restored)?;
+        self.audit(AuditAction::Added, Some(&restored.id));
+        self.entries.push(restored);
+
+        Ok(&self.entries[self.entries.len() - 1])
+    }
+
+    /// Removes an entry from the store for good, no matter if it is inside
+    /// the trash or not. The files of its attachments are deleted as well.
+    pub(crate) fn remove(&mut self, id: &str) -> Result<Entry, InternalError> {
+        let index = self.index_of(id)?;
+
+        self.store_mut().delete(id)?;
+        self.audit(AuditAction::Purged, Some(id));
+        let entry = self.entries.remove(index);
+        self.discard_attachments(&entry);
+
+        Ok(entry)
+    }
+
+    fn set_deleted(
+        &mut self,
+        id: &str,
+        deleted_at: Option<i64>,
+        action: AuditAction,
+    ) -> Result<&Entry, InternalError> {
+        let index = self.index_of(id)?;
+
+        let mut changed = self.entries[index].clone();
+        changed.deleted_at = deleted_at;
+        self.store_mut().update(&changed)?;
+        self.audit(action, Some(id));
+        self.entries[index] = changed;
+
+        Ok(&self.entries[index])
+    }
+
+    /// Stores the changed entries in one transaction and takes them over.
+    fn store_changes(
+        &mut self,
+        changed: Vec<Entry>,
+        action: AuditAction,
+    ) -> Result<usize, InternalError> {
+        if changed.is_empty() {
+            return Ok(0);
+        }
+
+        let changes = changed
+            .iter()
+            .map(|e| Change::Update(e.clone()))
+            .collect::<Vec<Change>>();
+        self.store_mut().transaction(&changes)?;
+        for entry in changed.iter() {
+            self.audit(action, Some(&entry.id));
+        }
+        for entry in changed.iter() {
+            if let Some(e) = self.entries.iter_mut().find(|e| e.id == entry.id) {
+                *e = entry.clone();
+            }
+        }
+
+        Ok(changed.len())
+    }
+
+    /// Deletes the files of a removed entry. The entry is already gone, so a
+    /// file which can not be deleted is left for [`Repository::clean_attachments`].
+    fn discard_attachments(&self, entry: &Entry) {
+        for attachment in entry.attachments.iter() {
+            self.discard_attachment(&attachment.id);
+        }
+    }
+
+    fn discard_attachment(&self, attachment_id: &str) {
+        if let Err(err) = self.attachments.remove(attachment_id) {
+            log::warn!(
+                "Failed to delete the attachment {}: {:?}",
+                attachment_id,
+                err
+            );
+        }
+    }
+
+    fn index_of(&self, id: &str) -> Result<usize, InternalError> {
+        match self.entries.iter().position(|e| e.id == id) {
+            Some(index) => Ok(index),
+            None => Err(DbError::EntryNotFound.into()),
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::DbError;
+
+use super::*;
+
+pub mod json;
+pub mod memory;
+pub mod sqlite;
+
+pub use json::JsonStore;
+pub use memory::MemoryStore;
+pub use sqlite::SqliteStore;
+
+/// A single change on the stored entries.
+///
+/// Changes are handed to [`VaultStore::transaction`], so multiple of them can
+/// be stored at once.
+#[derive(Debug, Clone)]
+pub enum Change {
+    Insert(Entry),
+    Update(Entry),
+    Delete(String),
+}
+
+/// The storage backend of a vault.
+///
+/// The entries are always handed over in their **encrypted** state, so a
+/// backend never has to know anything about the encryption.
+pub trait VaultStore {
+    /// Loads all **encrypted** entries in the order they were inserted.
+    fn load(&self) -> Result<Vec<Entry>, InternalError>;
+
+    /// Applies all given changes. Either all of them are stored or none.
+    fn transaction(&mut self, changes: &[Change]) -> Result<(), InternalError>;
+
+    fn insert(&mut self, entry: &Entry) -> Result<(), InternalError> {
+        self.transaction(&[Change::Insert(entry.clone())])
+    }
+
+    fn update(&mut self, entry: &Entry) -> Result<(), InternalError> {
+        self.transaction(&[Change::Update(entry.clone())])
+    }
+
+    fn delete(&mut self, id: &str) -> Result<(), InternalError> {
+        self.transaction(&[Change::Delete(String::from(id))])
+    }
+}
+
+/// The available storage backends. Which one is used is stored per vault
+/// inside its config file.
+#[derive(Debug, Deserialize, Serialize, Default, Clone, Copy, PartialEq)]
+pub enum Backend {
+    #[default]
+    Json,
+    Sqlite,
+}
+impl Backend {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Backend::Json => "JSON File",
+            Backend::Sqlite => "SQLite Database",
+        }
+    }
+
+    /// Where the store of the backend is located.
+    pub fn path(&self) -> &'static str {
+        match self {
+            Backend::Json => utils::DB_PATH,
+            Backend::Sqlite => utils::SQLITE_PATH,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Default)]
+pub struct VaultConfig {
+    pub backend: Backend,
+    /// The folders created by the user. Folders holding entries exist
+    /// without being listed here.
+    #[serde(default)]
+    pub folders: Vec<String>,
+}
+
+/// Loads the config of the vault. A vault without a config file uses the
+/// defaults, which is the case for every vault created before backends existed.
+pub fn load_config() -> Result<VaultConfig, InternalError> {
+    if !Path::new(utils::CONFIG_PATH).exists() {
+        return Ok(VaultConfig::default());
+    }
+
+    match fs::read_to_string(utils::CONFIG_PATH) {
+        Ok(s) => match serde_json::from_str::<VaultConfig>(&s) {
+            Ok(config) => Ok(config),
+            Err(err) => Err(DbError::ConfigParse(err).into()),
+        },
+        Err(err) => Err(DbError::ConfigRead(err).into()),
+    }
+}
+
+/// Saves the config of the vault. The data folder has to exist already.
+pub fn save_config(config: &VaultConfig) -> Result<(), InternalError> {
+    let buffer = match serde_json::to_string_pretty(config) {
+        Ok(s) => s,
+        Err(err) => return Err(DbError::ConfigSerialize(err).into()),
+    };
+
+    match fs::write(utils::CONFIG_PATH, buffer) {
+        Ok(_) => Ok(()),
+        Err(err) => Err(DbError::ConfigWrite(err).into()),
+    }
+}
+
+/// Opens the store of the given backend at its default location.
+pub fn open_backend(backend: Backend) -> Result<Box<dyn VaultStore>, InternalError> {
+    match backend {
+        Backend::Json => Ok(Box::new(JsonStore::new(backend.path()))),
+        Backend::Sqlite => Ok(Box::new(SqliteStore::open(backend.path())?)),
+    }
+}
+
+/// Opens the store of the vault with the backend selected in its config.
+pub fn open_store() -> Result<Box<dyn VaultStore>, InternalError> {
+    open_backend(load_config()?.backend)
+}
+
+/// Copies all entries of one store into another one, e.g. when switching the
+/// backend of a vault. The entries are inserted in a single transaction, so
+/// the target either holds all of them afterwards or is left unchanged.
+///
+/// Returns the number of migrated entries.
+pub fn migrate(from: &dyn VaultStore, to: &mut dyn VaultStore) -> Result<usize, InternalError> {
+    let changes = from
+        .load()?
+        .into_iter()
+        .map(Change::Insert)
+        .collect::<Vec<Change>>();
+    to.transaction(&changes)?;
+
+    Ok(changes.len())
+}
+
+/// Applies the changes on a list of entries. Used by the backends that keep
+/// the whole list in one place.
+pub(crate) fn apply_changes(
+    entries: &mut Vec<Entry>,
+    changes: &[Change],
+) -> Result<(), InternalError> {
+    for change in changes {
+        match change {
+            Change::Insert(entry) => {
+                if entries.iter().any(|e| e.id == entry.id) {
+                    return Err(DbError::EntryExists.into());
+                }
+                entries.push(entry.clone());
+            }
+            Change::Update(entry) => match entries.iter_mut().find(|e| e.id == entry.id) {
+                Some(e) => *e = entry.clone(),
+                None => return Err(DbError::UpdatedNotFound.into()),
+            },
+            Change::Delete(id) => match entries.iter().position(|e| &e.id == id) {
+                Some(index) => {
+                    entries.remove(index);
+                }
+                None => return Err(DbError::DeletedNotFound.into()),
+            },
+        }
+    }
+
+    Ok(())
+}
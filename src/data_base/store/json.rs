@@ -0,0 +1,69 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::error::DbError;
+
+use super::*;
+
+/// Stores all entries inside one `json` file.
+///
+/// Every change rewrites the whole file. The new content is written to a
+/// temporary file first, which then replaces the old one, so a failing write
+/// never leaves a half written file behind.
+pub struct JsonStore {
+    path: PathBuf,
+}
+impl JsonStore {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Parses the content of a `json` file into the **encrypted** entries.
+    pub fn parse(buffer: &str) -> Result<Vec<Entry>, InternalError> {
+        match serde_json::from_str::<utils::Entries>(buffer) {
+            Ok(json) => Ok(json.entries),
+            Err(err) => Err(DbError::EntriesParse(err).into()),
+        }
+    }
+
+    fn save(&self, list: Vec<Entry>) -> Result<(), InternalError> {
+        let entries = utils::Entries { entries: list };
+        let buffer = match serde_json::to_string_pretty(&entries) {
+            Ok(s) => s,
+            Err(err) => return Err(DbError::EntriesSerialize(err).into()),
+        };
+
+        let tmp_path = self.path.with_extension("json.tmp");
+        if let Err(err) = fs::write(&tmp_path, buffer) {
+            return Err(DbError::EntriesWrite(err).into());
+        }
+
+        match fs::rename(&tmp_path, &self.path) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(DbError::EntriesReplace(err).into()),
+        }
+    }
+}
+impl VaultStore for JsonStore {
+    fn load(&self) -> Result<Vec<Entry>, InternalError> {
+        if !self.path.exists() {
+            return Ok(Vec::<Entry>::new());
+        }
+
+        match fs::read_to_string(&self.path) {
+            Ok(s) => JsonStore::parse(&s),
+            Err(err) => Err(DbError::EntriesRead(err).into()),
+        }
+    }
+
+    fn transaction(&mut self, changes: &[Change]) -> Result<(), InternalError> {
+        let mut entries = self.load()?;
+        apply_changes(&mut entries, changes)?;
+
+        self.save(entries)
+    }
+}
@@ -0,0 +1,48 @@
+use std::sync::{Arc, Mutex};
+
+use crate::error::DbError;
+
+use super::*;
+
+/// Keeps the entries in memory only, nothing is ever written to disk.
+///
+/// Clones of a store share the same entries, so a clone behaves like the same
+/// vault being opened a second time.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    entries: Arc<Mutex<Vec<Entry>>>,
+}
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_entries(entries: Vec<Entry>) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(entries)),
+        }
+    }
+}
+impl VaultStore for MemoryStore {
+    fn load(&self) -> Result<Vec<Entry>, InternalError> {
+        match self.entries.lock() {
+            Ok(entries) => Ok(entries.clone()),
+            Err(_) => Err(DbError::StoreLocked.into()),
+        }
+    }
+
+    fn transaction(&mut self, changes: &[Change]) -> Result<(), InternalError> {
+        let mut entries = match self.entries.lock() {
+            Ok(e) => e,
+            Err(_) => return Err(DbError::StoreLockedForChange.into()),
+        };
+
+        // the changes are applied on a copy first, so a failing change
+        // leaves the stored entries untouched
+        let mut changed = entries.clone();
+        apply_changes(&mut changed, changes)?;
+        *entries = changed;
+
+        Ok(())
+    }
+}
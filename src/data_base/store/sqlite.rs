@@ -0,0 +1,120 @@
+use std::path::Path;
+
+use rusqlite::{params, Connection};
+
+use crate::error::DbError;
+
+use super::*;
+
+/// Stores the entries inside an embedded SQLite database.
+///
+/// Each entry is one row, so a change only touches the rows it is about
+/// instead of rewriting the whole vault. The entry itself is kept as its
+/// serialized **encrypted** form, which keeps the schema independent of the
+/// fields an `Entry` has.
+pub struct SqliteStore {
+    connection: Connection,
+}
+impl SqliteStore {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, InternalError> {
+        match Connection::open(path) {
+            Ok(connection) => SqliteStore::with_connection(connection),
+            Err(err) => Err(DbError::SqliteOpen(err).into()),
+        }
+    }
+
+    /// Opens a database which only lives as long as the store.
+    pub fn open_in_memory() -> Result<Self, InternalError> {
+        match Connection::open_in_memory() {
+            Ok(connection) => SqliteStore::with_connection(connection),
+            Err(err) => Err(DbError::SqliteOpenMemory(err).into()),
+        }
+    }
+
+    fn with_connection(connection: Connection) -> Result<Self, InternalError> {
+        let created = connection.execute(
+            "CREATE TABLE IF NOT EXISTS entries (
+                id TEXT PRIMARY KEY,
+                data TEXT NOT NULL
+            )",
+            (),
+        );
+
+        match created {
+            Ok(_) => Ok(Self { connection }),
+            Err(err) => Err(DbError::SqliteSchema(err).into()),
+        }
+    }
+}
+impl VaultStore for SqliteStore {
+    fn load(&self) -> Result<Vec<Entry>, InternalError> {
+        let mut statement = match self
+            .connection
+            .prepare("SELECT data FROM entries ORDER BY rowid")
+        {
+            Ok(s) => s,
+            Err(err) => return Err(DbError::SqlitePrepare(err).into()),
+        };
+
+        let rows = match statement.query_map((), |row| row.get::<_, String>(0)) {
+            Ok(r) => r,
+            Err(err) => return Err(DbError::SqliteQuery(err).into()),
+        };
+
+        let mut entries = Vec::<Entry>::new();
+        for row in rows {
+            let data = match row {
+                Ok(d) => d,
+                Err(err) => return Err(DbError::SqliteRow(err).into()),
+            };
+            match serde_json::from_str::<Entry>(&data) {
+                Ok(entry) => entries.push(entry),
+                Err(err) => return Err(DbError::SqliteEntryParse(err).into()),
+            }
+        }
+
+        Ok(entries)
+    }
+
+    fn transaction(&mut self, changes: &[Change]) -> Result<(), InternalError> {
+        let transaction = match self.connection.transaction() {
+            Ok(t) => t,
+            Err(err) => return Err(DbError::SqliteBegin(err).into()),
+        };
+
+        for change in changes {
+            let result = match change {
+                Change::Insert(entry) | Change::Update(entry) => {
+                    let data = match serde_json::to_string(entry) {
+                        Ok(d) => d,
+                        Err(err) => return Err(DbError::SqliteSerialize(err).into()),
+                    };
+                    match change {
+                        Change::Insert(_) => transaction.execute(
+                            "INSERT INTO entries (id, data) VALUES (?1, ?2)",
+                            params![entry.id, data],
+                        ),
+                        _ => transaction.execute(
+                            "UPDATE entries SET data = ?2 WHERE id = ?1",
+                            params![entry.id, data],
+                        ),
+                    }
+                }
+                Change::Delete(id) => {
+                    transaction.execute("DELETE FROM entries WHERE id = ?1", params![id])
+                }
+            };
+
+            match result {
+                Ok(0) => return Err(DbError::SqliteNotFound.into()),
+                Ok(_) => {}
+                Err(err) => return Err(DbError::SqliteApply(err).into()),
+            }
+        }
+
+        match transaction.commit() {
+            Ok(_) => Ok(()),
+            Err(err) => Err(DbError::SqliteCommit(err).into()),
+        }
+    }
+}
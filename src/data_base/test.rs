@@ -0,0 +1,1206 @@
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use crate::cryptography::{decrypt_text, encrypt_text};
+    use crate::data_base::{
+        attachment::{AttachmentFolder, MAX_ATTACHMENT_SIZE},
+        audit::{AuditAction, AuditLog, ChainStatus},
+        folder::{self, FolderContents, FolderNode},
+        kind::{EntryKind, Payload},
+        history::{Action, History},
+        recovery::preserve_broken,
+        repository::{Repository, SortMode},
+        store::{migrate, Change, JsonStore, MemoryStore, SqliteStore, VaultStore},
+        Entry,
+    };
+
+    fn entry(name: &str) -> Entry {
+        Entry::new(name, "user", "password").unwrap()
+    }
+
+    fn ids(store: &dyn VaultStore) -> Vec<String> {
+        store.load().unwrap().iter().map(|e| e.id()).collect()
+    }
+
+    #[test]
+    fn add_entry() {
+        let mut store = MemoryStore::new();
+        let first = entry("first");
+        let second = entry("second");
+
+        store.insert(&first).unwrap();
+        store.insert(&second).unwrap();
+
+        assert_eq!(
+            ids(&store),
+            vec![first.id(), second.id()],
+            "\n!!! Entries are not stored in insertion order",
+        );
+        assert!(
+            store.insert(&first).is_err(),
+            "\n!!! Inserting the same entry twice did not fail",
+        );
+    }
+
+    #[test]
+    fn edit_entry() {
+        let mut store = MemoryStore::new();
+        let mut entry = entry("before");
+        store.insert(&entry).unwrap();
+
+        entry.edit("after", "new user", "new password").unwrap();
+        store.update(&entry).unwrap();
+
+        let loaded = store.load().unwrap();
+        let details = loaded[0].get_details().unwrap();
+        assert_eq!(
+            loaded.len(),
+            1,
+            "\n!!! Editing changed the number of entries"
+        );
+        assert_eq!(&details.name, "after", "\n!!! Name was not updated");
+        assert_eq!(
+            details.user_name.expose(), "new user",
+            "\n!!! Username was not updated"
+        );
+        assert_eq!(
+            details.password.expose(), "new password",
+            "\n!!! Password was not updated"
+        );
+    }
+
+    #[test]
+    fn edit_unknown_entry_fails() {
+        let mut store = MemoryStore::new();
+
+        assert!(
+            store.update(&entry("unknown")).is_err(),
+            "\n!!! Updating an entry which was never added did not fail",
+        );
+    }
+
+    #[test]
+    fn delete_entry() {
+        let mut store = MemoryStore::new();
+        let kept = entry("kept");
+        let deleted = entry("deleted");
+        store.insert(&kept).unwrap();
+        store.insert(&deleted).unwrap();
+
+        store.delete(&deleted.id()).unwrap();
+
+        assert_eq!(
+            ids(&store),
+            vec![kept.id()],
+            "\n!!! Wrong entry was deleted"
+        );
+        assert!(
+            store.delete(&deleted.id()).is_err(),
+            "\n!!! Deleting an entry twice did not fail",
+        );
+    }
+
+    #[test]
+    fn reload_sees_saved_entries() {
+        let mut store = MemoryStore::new();
+        let reopened = store.clone();
+        let entry = entry("reload");
+
+        store.insert(&entry).unwrap();
+
+        assert_eq!(
+            ids(&reopened),
+            vec![entry.id()],
+            "\n!!! Reopened store does not see the saved entry",
+        );
+    }
+
+    #[test]
+    fn failing_transaction_changes_nothing() {
+        let mut store = MemoryStore::new();
+        let kept = entry("kept");
+        store.insert(&kept).unwrap();
+
+        let result = store.transaction(&[
+            Change::Insert(entry("new")),
+            Change::Delete(kept.id()),
+            Change::Update(entry("unknown")),
+        ]);
+
+        assert!(
+            result.is_err(),
+            "\n!!! Transaction with unknown entry did not fail"
+        );
+        assert_eq!(
+            ids(&store),
+            vec![kept.id()],
+            "\n!!! Failed transaction was partially applied",
+        );
+    }
+
+    #[test]
+    fn corrupt_file_is_reported() {
+        let result = JsonStore::parse("{\"entries\": [{\"id\": ");
+
+        match result {
+            Ok(_) => panic!("\n!!! Corrupt file was parsed"),
+            Err(err) => {
+                assert_eq!(
+                    &err.code(),
+                    "[DB_G_GE-1]",
+                    "\n!!! Corrupt file gave the wrong error code",
+                );
+                // internal error -> db error -> serde error
+                let cause = err.source().and_then(|db| db.source());
+                assert!(
+                    cause.is_some_and(|c| c.is::<serde_json::Error>()),
+                    "\n!!! Corrupt file did not keep the parse error as cause",
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn broken_file_is_preserved() {
+        let path =
+            std::env::temp_dir().join(format!("vault-broken-{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "{\"entries\": [").unwrap();
+
+        let preserved = preserve_broken(&path).unwrap().unwrap();
+
+        assert!(!path.exists(), "\n!!! Broken file was not moved aside");
+        assert_eq!(
+            std::fs::read_to_string(&preserved).unwrap(),
+            "{\"entries\": [",
+            "\n!!! Content of the broken file was not kept",
+        );
+        assert!(
+            preserve_broken(&path).unwrap().is_none(),
+            "\n!!! Preserving a missing file did something",
+        );
+
+        std::fs::remove_file(preserved).unwrap();
+    }
+
+    fn audit_path() -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("vault-audit-{}.log", uuid::Uuid::new_v4()))
+    }
+
+    fn statuses(audit: &AuditLog) -> Vec<ChainStatus> {
+        audit.verify().unwrap().iter().map(|r| r.status).collect()
+    }
+
+    #[test]
+    fn audit_records_repository_changes() {
+        let path = audit_path();
+        let mut repository = Repository::open(Box::new(MemoryStore::new()))
+            .unwrap()
+            .with_audit(AuditLog::new(&path));
+
+        let id = repository.add("name", "user", "secret pw").unwrap().id();
+        repository.delete(&id).unwrap();
+        repository.audit(AuditAction::CopiedPassword, Some(&id));
+
+        let records = repository.audit_log().unwrap().verify().unwrap();
+        let actions = records
+            .iter()
+            .map(|r| r.record.clone().unwrap().action)
+            .collect::<Vec<AuditAction>>();
+        assert_eq!(
+            actions,
+            vec![
+                AuditAction::Added,
+                AuditAction::Deleted,
+                AuditAction::CopiedPassword
+            ],
+            "\n!!! Wrong actions were recorded",
+        );
+        assert!(
+            records.iter().all(|r| r.status == ChainStatus::Valid),
+            "\n!!! Untouched chain is not valid",
+        );
+        assert!(
+            !std::fs::read_to_string(&path).unwrap().contains("secret pw"),
+            "\n!!! Audit log contains a secret",
+        );
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn audit_detects_tampering() {
+        let path = audit_path();
+        let mut audit = AuditLog::new(&path);
+        for _ in 0..4 {
+            audit.append(AuditAction::Edited, Some("id")).unwrap();
+        }
+        let lines = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect::<Vec<String>>();
+
+        // removing a record breaks the chain at the one after it
+        let removed = [&lines[0], &lines[2], &lines[3]];
+        std::fs::write(&path, removed.map(|l| format!("{}\n", l)).concat()).unwrap();
+        assert_eq!(
+            statuses(&audit),
+            vec![ChainStatus::Valid, ChainStatus::Broken, ChainStatus::Valid],
+            "\n!!! Removed record was not detected",
+        );
+
+        // changing a record breaks its own hash
+        let altered = decrypt_text(&lines[1], false, false)
+            .unwrap()
+            .replace("Edited", "Added");
+        let altered = encrypt_text(&altered, false, false).unwrap();
+        let changed = [&lines[0], &altered, &String::from("garbage"), &lines[3]];
+        std::fs::write(&path, changed.map(|l| format!("{}\n", l)).concat()).unwrap();
+        assert_eq!(
+            statuses(&audit),
+            vec![
+                ChainStatus::Valid,
+                ChainStatus::Altered,
+                ChainStatus::Unreadable,
+                ChainStatus::Valid
+            ],
+            "\n!!! Altered or unreadable record was not detected",
+        );
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn audit_survives_short_lines() {
+        let path = audit_path();
+        let mut audit = AuditLog::new(&path);
+        audit.append(AuditAction::Edited, Some("id")).unwrap();
+        let line = std::fs::read_to_string(&path).unwrap();
+
+        // valid hex, but shorter than the nonce
+        std::fs::write(&path, format!("abcd\n{}", line)).unwrap();
+        assert_eq!(
+            statuses(&audit),
+            vec![ChainStatus::Unreadable, ChainStatus::Valid],
+            "\n!!! A short line was not shown as unreadable",
+        );
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn missing_entries_keep_their_codes() {
+        let code = |result: Result<(), crate::error::InternalError>| match result {
+            Ok(_) => panic!("\n!!! A missing entry was changed"),
+            Err(err) => err.code(),
+        };
+        let mut memory = MemoryStore::new();
+        let mut sqlite = SqliteStore::open_in_memory().unwrap();
+        let mut repository = Repository::open(Box::new(MemoryStore::new())).unwrap();
+
+        let codes = [
+            code(repository.delete("unknown").map(|_| ())),
+            code(memory.transaction(&[Change::Update(entry("unknown"))])),
+            code(memory.transaction(&[Change::Delete(String::from("unknown"))])),
+            code(sqlite.transaction(&[Change::Update(entry("unknown"))])),
+        ];
+        assert_eq!(
+            codes,
+            ["[DB_R_IO-1]", "[DB_ST_AC-2]", "[DB_ST_AC-3]", "[DB_SQ_T-3]"].map(String::from),
+            "\n!!! Missing entries can not be told apart by their code",
+        );
+    }
+
+    #[test]
+    fn json_file_can_be_parsed() {
+        let entry = entry("parsed");
+        let buffer = format!(
+            "{{\"entries\": [{}]}}",
+            serde_json::to_string(&entry).unwrap()
+        );
+
+        let parsed = JsonStore::parse(&buffer).unwrap();
+
+        assert_eq!(parsed.len(), 1, "\n!!! Wrong number of parsed entries");
+        assert_eq!(
+            parsed[0].id(),
+            entry.id(),
+            "\n!!! Parsed entry has another id"
+        );
+    }
+
+    #[test]
+    fn entries_without_kind_are_logins() {
+        let entry = entry("old");
+        let mut value = serde_json::to_value(&entry).unwrap();
+        let object = value.as_object_mut().unwrap();
+        object.remove("kind");
+        object.remove("payload");
+
+        let parsed = serde_json::from_value::<Entry>(value).unwrap();
+        let details = parsed.get_details().unwrap();
+
+        assert_eq!(parsed.kind(), EntryKind::Login, "\n!!! Old entry is not a login");
+        assert_eq!(
+            details.payload.expose(),
+            &Payload::Login {
+                user_name: String::from("user"),
+                password: String::from("password"),
+            },
+            "\n!!! Values of the old entry were not kept",
+        );
+    }
+
+    #[test]
+    fn payloads_are_stored_encrypted() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let mut repository = Repository::open(Box::new(store)).unwrap();
+        let card = Payload::Card {
+            holder: String::from("Jane Doe"),
+            number: String::from("4111 1111 1111 1111"),
+            expiry: String::from("09/29"),
+            cvv: String::from("123"),
+        };
+        let added = repository.add_payload("Visa", &card).unwrap().clone();
+
+        let stored = serde_json::to_string(&added).unwrap();
+        assert!(
+            !stored.contains("4111") && !stored.contains("Jane"),
+            "\n!!! Values of the card are stored in plain text",
+        );
+        assert_eq!(
+            added.get_details().unwrap().payload.expose(),
+            &card,
+            "\n!!! Decrypted card differs",
+        );
+
+        let note = Payload::Note {
+            text: String::from("secret"),
+        };
+        let edited = repository
+            .update_payload(&added.id(), "Note", &note)
+            .unwrap();
+        assert_eq!(edited.kind(), EntryKind::Note, "\n!!! Kind was not changed");
+        assert_eq!(
+            edited.get_details().unwrap().payload.expose().secret(),
+            Some(("Text", "secret")),
+            "\n!!! Secret of the note is wrong",
+        );
+    }
+
+    #[test]
+    fn invalid_cards_are_rejected() {
+        let card = |number: &str, expiry: &str, cvv: &str| Payload::Card {
+            holder: String::new(),
+            number: String::from(number),
+            expiry: String::from(expiry),
+            cvv: String::from(cvv),
+        };
+
+        let codes = [
+            card("4111 1111 1111 1112", "", ""),
+            card("", "13/29", ""),
+            card("", "", "12a"),
+        ]
+        .iter()
+        .map(|c| c.validate().unwrap_err().code())
+        .collect::<Vec<String>>();
+        assert_eq!(
+            codes,
+            vec!["[DB_E_V-1]", "[DB_E_V-2]", "[DB_E_V-3]"],
+            "\n!!! Invalid values of the card were accepted",
+        );
+        assert!(
+            card("4111-1111-1111-1111", "01/30", "1234").validate().is_ok(),
+            "\n!!! Valid card was rejected",
+        );
+    }
+
+    #[test]
+    fn fields_are_found_by_label() {
+        let key = Payload::SshKey {
+            public_key: String::from("ssh-ed25519 AAAA"),
+            private_key: String::from("-----BEGIN"),
+            passphrase: String::new(),
+        };
+
+        assert_eq!(key.field("private_key"), Some("-----BEGIN"));
+        assert_eq!(key.field("Public Key"), Some("ssh-ed25519 AAAA"));
+        assert_eq!(
+            key.field("password"),
+            None,
+            "\n!!! A field the kind does not have was found",
+        );
+        assert_eq!(
+            Payload::Login {
+                user_name: String::from("user"),
+                password: String::from("pw"),
+            }
+            .field("USERNAME"),
+            Some("user"),
+        );
+    }
+
+    #[test]
+    fn migration_keeps_entries() {
+        let first = entry("first");
+        let second = entry("second");
+        let from = MemoryStore::with_entries(vec![first.clone(), second.clone()]);
+        let mut to = SqliteStore::open_in_memory().unwrap();
+
+        let migrated = migrate(&from, &mut to).unwrap();
+
+        assert_eq!(migrated, 2, "\n!!! Wrong number of migrated entries");
+        assert_eq!(
+            ids(&to),
+            vec![first.id(), second.id()],
+            "\n!!! Migrated entries differ from the source",
+        );
+        assert_eq!(
+            to.load().unwrap()[1].get_details().unwrap().password.expose(),
+            "password",
+            "\n!!! Migrated entry can not be decrypted anymore",
+        );
+    }
+
+    #[test]
+    fn failed_migration_leaves_target_unchanged() {
+        let existing = entry("existing");
+        let from = MemoryStore::with_entries(vec![entry("new"), existing.clone()]);
+        let mut to = SqliteStore::open_in_memory().unwrap();
+        to.insert(&existing).unwrap();
+
+        assert!(
+            migrate(&from, &mut to).is_err(),
+            "\n!!! Migrating a duplicate entry did not fail",
+        );
+        assert_eq!(
+            ids(&to),
+            vec![existing.id()],
+            "\n!!! Failed migration was partially applied",
+        );
+    }
+
+    #[test]
+    fn repository_get_and_find() {
+        let store = MemoryStore::new();
+        let mut repository = Repository::open(Box::new(store)).unwrap();
+        let github = repository.add("GitHub", "user", "pw").unwrap().id();
+        repository.add("GitLab", "user", "pw").unwrap();
+        repository.add("Mail", "user", "pw").unwrap();
+
+        assert_eq!(
+            repository.get(&github).unwrap().name(),
+            "GitHub",
+            "\n!!! Got the wrong entry for the id",
+        );
+        assert!(
+            repository.get("unknown").is_none(),
+            "\n!!! Got an entry for an unknown id",
+        );
+        assert_eq!(
+            repository.find_by_name("git").len(),
+            2,
+            "\n!!! Search by name is not case-insensitive",
+        );
+    }
+
+    #[test]
+    fn repository_changes_are_stored() {
+        let store = MemoryStore::new();
+        let mut repository = Repository::open(Box::new(store.clone())).unwrap();
+        let kept = repository.add("kept", "user", "pw").unwrap().id();
+        let deleted = repository.add("deleted", "user", "pw").unwrap().id();
+
+        repository
+            .update(&kept, "edited", "user", "new pw")
+            .unwrap();
+        let removed = repository.delete(&deleted).unwrap();
+
+        let removed = removed.id();
+
+        let reopened = Repository::open(Box::new(store)).unwrap();
+        assert_eq!(removed, deleted, "\n!!! Wrong entry was deleted");
+        assert_eq!(
+            reopened.list().len(),
+            1,
+            "\n!!! Deleted entry is still listed"
+        );
+        assert_eq!(
+            reopened.trash().len(),
+            1,
+            "\n!!! Deleted entry was not stored inside the trash"
+        );
+        assert_eq!(
+            reopened.get(&kept).unwrap().get_details().unwrap().password.expose(),
+            "new pw",
+            "\n!!! Edited entry was not stored",
+        );
+    }
+
+    #[test]
+    fn repository_finds_entries_by_origin() {
+        let store = MemoryStore::new();
+        let mut repository = Repository::open(Box::new(store.clone())).unwrap();
+        let github = repository.add("GitHub", "user", "pw").unwrap().id();
+        let gist = repository.add("Gist", "user", "pw").unwrap().id();
+        let local = repository.add("Local", "user", "pw").unwrap().id();
+        repository.set_url(&github, "https://GitHub.com/login").unwrap();
+        repository.set_url(&gist, "gist.github.com").unwrap();
+        repository.set_url(&local, "http://localhost:8080/admin").unwrap();
+
+        let found = |origin: &str| names(repository.find_by_origin(origin));
+        assert_eq!(
+            found("https://github.com"),
+            vec!["GitHub"],
+            "\n!!! The login of the origin was not found",
+        );
+        assert_eq!(found("https://gist.github.com:443"), vec!["Gist"]);
+        assert_eq!(found("http://localhost:8080"), vec!["Local"]);
+        assert!(
+            found("http://github.com").is_empty(),
+            "\n!!! A login was found for another scheme",
+        );
+        assert!(
+            found("https://github.com.evil.org").is_empty(),
+            "\n!!! A login was found for another site",
+        );
+        assert!(found("http://localhost").is_empty());
+
+        let reopened = Repository::open(Box::new(store)).unwrap();
+        assert_eq!(
+            reopened.get(&github).unwrap().url(),
+            "https://GitHub.com/login",
+            "\n!!! The url was not stored",
+        );
+    }
+
+    #[test]
+    fn repository_finds_entries_by_tag() {
+        let store = MemoryStore::new();
+        let mut repository = Repository::open(Box::new(store.clone())).unwrap();
+        let ghcr = repository.add("ghcr.io", "user", "pw").unwrap().id();
+        repository.add("GitHub", "user", "pw").unwrap();
+        repository
+            .set_tags(&ghcr, &["registry", " Registry ", "", "work"])
+            .unwrap();
+
+        assert_eq!(
+            repository.get(&ghcr).unwrap().tags(),
+            &[String::from("registry"), String::from("work")],
+            "\n!!! Empty or repeated tags were kept",
+        );
+        assert_eq!(
+            names(repository.find_by_tag("REGISTRY")),
+            vec!["ghcr.io"],
+            "\n!!! The entry was not found by its tag",
+        );
+
+        let reopened = Repository::open(Box::new(store)).unwrap();
+        assert_eq!(
+            reopened.find_by_tag("work").len(),
+            1,
+            "\n!!! The tags were not stored",
+        );
+    }
+
+    #[test]
+    fn repository_unknown_id_fails() {
+        let mut repository = Repository::open(Box::new(MemoryStore::new())).unwrap();
+
+        assert!(
+            repository.update("unknown", "name", "user", "pw").is_err(),
+            "\n!!! Updating an unknown id did not fail",
+        );
+        assert!(
+            repository.delete("unknown").is_err(),
+            "\n!!! Deleting an unknown id did not fail",
+        );
+    }
+
+    fn names(entries: Vec<&Entry>) -> Vec<String> {
+        entries.iter().map(|e| e.name()).collect()
+    }
+
+    #[test]
+    fn repository_sorts_names_naturally() {
+        let mut store = MemoryStore::new();
+        for name in ["item 10", "Item 2", "item 1", "Bank"] {
+            store.insert(&entry(name)).unwrap();
+        }
+        let repository = Repository::open(Box::new(store)).unwrap();
+
+        assert_eq!(
+            names(repository.sorted(SortMode::Name)),
+            vec!["Bank", "item 1", "Item 2", "item 10"],
+            "\n!!! Names are not sorted naturally and case-insensitive",
+        );
+    }
+
+    #[test]
+    fn repository_sorts_by_time_with_favorites_on_top() {
+        let mut store = MemoryStore::new();
+        let mut old = entry("old");
+        old.created_at = 100;
+        old.last_edited = 300;
+        let mut new = entry("new");
+        new.created_at = 200;
+        new.last_edited = 200;
+        let mut pinned = entry("pinned");
+        pinned.created_at = 50;
+        pinned.last_edited = 50;
+        for e in [&old, &new, &pinned] {
+            store.insert(e).unwrap();
+        }
+        let mut repository = Repository::open(Box::new(store.clone())).unwrap();
+        repository.set_favorite(&pinned.id(), true).unwrap();
+
+        assert_eq!(
+            names(repository.sorted(SortMode::Created)),
+            vec!["pinned", "new", "old"],
+            "\n!!! Entries are not sorted by their creation",
+        );
+        assert_eq!(
+            names(repository.sorted(SortMode::Edited)),
+            vec!["pinned", "old", "new"],
+            "\n!!! Entries are not sorted by their last edit",
+        );
+
+        repository.mark_used(&old.id()).unwrap();
+        assert_eq!(
+            names(repository.sorted(SortMode::Recent)),
+            vec!["pinned", "old", "new"],
+            "\n!!! The last used entry is not listed first",
+        );
+
+        let reopened = Repository::open(Box::new(store)).unwrap();
+        assert!(
+            reopened.get(&pinned.id()).unwrap().is_favorite(),
+            "\n!!! Favorite was not stored",
+        );
+        assert!(
+            reopened.get(&old.id()).unwrap().last_used().is_some(),
+            "\n!!! Last use was not stored",
+        );
+    }
+
+    #[test]
+    fn repository_lists_favorites_and_recent() {
+        let mut store = MemoryStore::new();
+        let mut mail = entry("Mail");
+        mail.last_used = Some(100);
+        mail.favorite = true;
+        let mut bank = entry("Bank");
+        bank.last_used = Some(300);
+        let mut shop = entry("Shop");
+        shop.last_used = Some(200);
+        let mut chat = entry("Chat");
+        chat.favorite = true;
+        let unused = entry("Unused");
+        for e in [&mail, &bank, &shop, &chat, &unused] {
+            store.insert(e).unwrap();
+        }
+        let mut repository = Repository::open(Box::new(store)).unwrap();
+        repository.delete(&shop.id()).unwrap();
+
+        assert_eq!(
+            names(repository.favorites()),
+            vec!["Chat", "Mail"],
+            "\n!!! Favorites are not listed by their name",
+        );
+        assert_eq!(
+            names(repository.recent(5)),
+            vec!["Bank", "Mail"],
+            "\n!!! Recent entries are wrong or hold entries inside the trash",
+        );
+        assert_eq!(
+            names(repository.recent(1)),
+            vec!["Bank"],
+            "\n!!! Recent entries are not limited",
+        );
+    }
+
+    #[test]
+    fn trash_restore_and_purge() {
+        let store = MemoryStore::new();
+        let mut repository = Repository::open(Box::new(store.clone())).unwrap();
+        let restored = repository.add("restored", "user", "pw").unwrap().id();
+        let purged = repository.add("purged", "user", "pw").unwrap().id();
+
+        assert!(
+            repository.purge(&purged).is_err(),
+            "\n!!! Entry outside of the trash was purged",
+        );
+
+        repository.delete(&restored).unwrap();
+        repository.delete(&purged).unwrap();
+        assert!(
+            repository.find_by_name("restored").is_empty(),
+            "\n!!! Entry inside the trash was found by name",
+        );
+
+        repository.restore(&restored).unwrap();
+        repository.purge(&purged).unwrap();
+
+        assert_eq!(
+            ids(&store),
+            vec![restored.clone()],
+            "\n!!! Purged entry is still stored",
+        );
+        assert!(
+            !repository.get(&restored).unwrap().is_deleted(),
+            "\n!!! Restored entry is still inside the trash",
+        );
+    }
+
+    #[test]
+    fn trash_purges_expired_entries() {
+        let day = 24 * 60 * 60;
+        let now = chrono::Local::now().timestamp();
+        let mut expired = entry("expired");
+        expired.deleted_at = Some(now - 31 * day);
+        let mut recent = entry("recent");
+        recent.deleted_at = Some(now - 29 * day);
+        let active = entry("active");
+        let store = MemoryStore::with_entries(vec![expired, recent.clone(), active.clone()]);
+        let mut repository = Repository::open(Box::new(store.clone())).unwrap();
+
+        let purged = repository.purge_expired(30).unwrap();
+
+        assert_eq!(purged, 1, "\n!!! Wrong number of purged entries");
+        assert_eq!(
+            ids(&store),
+            vec![recent.id(), active.id()],
+            "\n!!! Wrong entries were purged",
+        );
+    }
+
+    #[test]
+    fn deleting_again_keeps_the_time_of_deletion() {
+        let deleted_at = chrono::Local::now().timestamp() - 29 * 24 * 60 * 60;
+        let mut deleted = entry("deleted");
+        deleted.deleted_at = Some(deleted_at);
+        let store = MemoryStore::with_entries(vec![deleted.clone()]);
+        let mut repository = Repository::open(Box::new(store)).unwrap();
+
+        repository.delete(&deleted.id()).unwrap();
+
+        assert_eq!(
+            repository.get(&deleted.id()).unwrap().deleted_at,
+            Some(deleted_at),
+            "\n!!! Deleting an entry inside the trash delayed its purge",
+        );
+    }
+
+    fn attachment_folder() -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("vault-attachments-{}", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn attachments_are_encrypted_and_purged() {
+        let folder = attachment_folder();
+        let mut repository = Repository::open(Box::new(MemoryStore::new()))
+            .unwrap()
+            .with_attachment_folder(AttachmentFolder::new(&folder));
+        let id = repository.add("entry", "user", "pw").unwrap().id();
+        let data = b"recovery codes: 1234 5678".to_vec();
+
+        let entry = repository.add_attachment(&id, "codes.txt", &data).unwrap();
+        let attachment = entry.attachments()[0].clone();
+        let stored = std::fs::read(folder.join(&attachment.id)).unwrap();
+
+        assert_eq!(attachment.name, "codes.txt", "\n!!! Wrong name of the attachment");
+        assert_eq!(attachment.size, data.len() as u64, "\n!!! Wrong size of the attachment");
+        assert!(
+            !stored.windows(data.len()).any(|w| w == data.as_slice()),
+            "\n!!! Attachment is stored in plain text",
+        );
+        assert_eq!(
+            repository.read_attachment(&id, &attachment.id).unwrap(),
+            data,
+            "\n!!! Read attachment differs from the original",
+        );
+
+        repository.delete(&id).unwrap();
+        assert!(
+            folder.join(&attachment.id).exists(),
+            "\n!!! Attachment of an entry inside the trash was deleted",
+        );
+        repository.purge(&id).unwrap();
+        assert!(
+            !folder.join(&attachment.id).exists(),
+            "\n!!! Attachment of a purged entry was kept",
+        );
+
+        std::fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn too_large_attachments_are_rejected() {
+        let folder = attachment_folder();
+        let mut repository = Repository::open(Box::new(MemoryStore::new()))
+            .unwrap()
+            .with_attachment_folder(AttachmentFolder::new(&folder));
+        let id = repository.add("entry", "user", "pw").unwrap().id();
+        let data = vec![0u8; MAX_ATTACHMENT_SIZE as usize + 1];
+
+        assert_eq!(
+            repository.add_attachment(&id, "large.bin", &data).unwrap_err().code(),
+            "[DB_AT_A-1]",
+            "\n!!! Too large attachment was accepted",
+        );
+        assert!(
+            repository.get(&id).unwrap().attachments().is_empty(),
+            "\n!!! Rejected attachment was added to the entry",
+        );
+        assert!(!folder.exists(), "\n!!! Rejected attachment was written");
+    }
+
+    #[test]
+    fn orphaned_attachments_are_cleaned() {
+        let folder = attachment_folder();
+        let mut repository = Repository::open(Box::new(MemoryStore::new()))
+            .unwrap()
+            .with_attachment_folder(AttachmentFolder::new(&folder));
+        let kept = repository.add("kept", "user", "pw").unwrap().id();
+        repository.add_attachment(&kept, "kept.txt", b"kept").unwrap();
+        let kept_file = repository.get(&kept).unwrap().attachments()[0].id.clone();
+
+        // a file which could not be deleted together with its entry
+        AttachmentFolder::new(&folder)
+            .write("orphaned", b"removed")
+            .unwrap();
+
+        assert_eq!(
+            repository.clean_attachments().unwrap(),
+            1,
+            "\n!!! Wrong number of cleaned attachments",
+        );
+        assert_eq!(
+            AttachmentFolder::new(&folder).ids().unwrap(),
+            vec![kept_file],
+            "\n!!! Wrong attachments were cleaned",
+        );
+
+        std::fs::remove_dir_all(&folder).unwrap();
+    }
+
+    #[test]
+    fn undo_keeps_attachments_in_place() {
+        let folder = attachment_folder();
+        let mut repository = Repository::open(Box::new(MemoryStore::new()))
+            .unwrap()
+            .with_attachment_folder(AttachmentFolder::new(&folder));
+        let mut history = History::new();
+        let added = repository.add("entry", "user", "pw").unwrap().clone();
+        let id = added.id();
+        history.record(Action::Added(added));
+
+        let before = repository
+            .add_attachment(&id, "codes.txt", b"1234")
+            .unwrap()
+            .clone();
+        let attachment = before.attachments()[0].id.clone();
+        let after = repository.update(&id, "entry", "user", "new").unwrap().clone();
+        history.record(Action::Edited {
+            before: Box::new(before),
+            after: Box::new(after),
+        });
+        repository.remove_attachment(&id, &attachment).unwrap();
+
+        history.undo(&mut repository).unwrap();
+        assert!(
+            repository.get(&id).unwrap().attachments().is_empty(),
+            "\n!!! The undo brought back an attachment whose file is deleted",
+        );
+        let added = repository
+            .add_attachment(&id, "new.txt", b"5678")
+            .unwrap()
+            .attachments()[0]
+            .id
+            .clone();
+        history.redo(&mut repository).unwrap();
+        assert_eq!(
+            repository.read_attachment(&id, &added).unwrap(),
+            b"5678",
+            "\n!!! The redo dropped an attachment",
+        );
+
+        history.undo(&mut repository).unwrap();
+        history.undo(&mut repository).unwrap();
+        assert_eq!(
+            AttachmentFolder::new(&folder).ids().unwrap(),
+            vec![added.clone()],
+            "\n!!! The files of an entry undone into the trash were deleted",
+        );
+        history.redo(&mut repository).unwrap();
+        assert_eq!(
+            repository.read_attachment(&id, &added).unwrap(),
+            b"5678",
+            "\n!!! The entry came back without its attachment",
+        );
+
+        let _ = std::fs::remove_dir_all(&folder);
+    }
+
+    #[test]
+    fn folder_paths() {
+        assert_eq!(
+            folder::normalize(" Work//AWS/ prod/"),
+            "Work/AWS/prod",
+            "\n!!! Path was not cleaned up",
+        );
+        assert_eq!(folder::parent("Work/AWS"), "Work", "\n!!! Wrong parent");
+        assert_eq!(folder::parent("Work"), "", "\n!!! Wrong parent of top folder");
+        assert!(
+            folder::is_inside("Work/AWS", "Work") && !folder::is_inside("Workshop", "Work"),
+            "\n!!! Subfolders are not detected correctly",
+        );
+        assert_eq!(
+            folder::renamed("Work/AWS/prod", "Work/AWS", "Cloud"),
+            Some(String::from("Cloud/prod")),
+            "\n!!! Path was not renamed",
+        );
+    }
+
+    #[test]
+    fn folder_tree_is_built() {
+        let mut prod = entry("prod");
+        prod.folder = String::from("Work/AWS");
+        let top = entry("top");
+        let tree = FolderNode::build(
+            &[String::from("Private"), String::from("Work/Empty")],
+            &[&prod, &top],
+        );
+
+        let paths = tree
+            .folders
+            .iter()
+            .map(|f| f.path.clone())
+            .collect::<Vec<String>>();
+        assert_eq!(paths, vec!["Private", "Work"], "\n!!! Wrong top folders");
+        assert_eq!(tree.entries, vec![top.id()], "\n!!! Wrong top entries");
+        assert_eq!(
+            tree.folders[1].folders[0].path,
+            "Work/AWS",
+            "\n!!! Subfolders are not sorted by name",
+        );
+        assert_eq!(
+            tree.folders[1].folders[0].entries,
+            vec![prod.id()],
+            "\n!!! Entry is not inside its folder",
+        );
+        assert_eq!(tree.count(), 2, "\n!!! Wrong number of entries");
+    }
+
+    #[test]
+    fn repository_moves_renames_and_deletes_folders() {
+        let store = MemoryStore::new();
+        let mut repository = Repository::open(Box::new(store.clone())).unwrap();
+        let prod = repository.add("prod", "user", "pw").unwrap().id();
+        let dev = repository.add("dev", "user", "pw").unwrap().id();
+        repository.move_to_folder(&prod, "Work/AWS/prod").unwrap();
+        repository.move_to_folder(&dev, "Work/AWS").unwrap();
+
+        assert_eq!(
+            repository.rename_folder("Work/AWS", "Cloud").unwrap(),
+            2,
+            "\n!!! Not all entries were moved",
+        );
+        assert_eq!(
+            repository.get(&prod).unwrap().folder(),
+            "Cloud/prod",
+            "\n!!! Subfolder was not moved",
+        );
+        assert!(
+            repository.rename_folder("Cloud", "Cloud/inner").is_err(),
+            "\n!!! Folder was moved into itself",
+        );
+
+        repository
+            .delete_folder("Cloud/prod", FolderContents::MoveToParent)
+            .unwrap();
+        assert_eq!(
+            repository.get(&prod).unwrap().folder(),
+            "Cloud",
+            "\n!!! Entry was not moved into the parent",
+        );
+
+        repository.delete_folder("Cloud", FolderContents::Trash).unwrap();
+        let reopened = Repository::open(Box::new(store)).unwrap();
+        assert_eq!(
+            reopened.trash().len(),
+            2,
+            "\n!!! Entries of the deleted folder were not moved into the trash",
+        );
+    }
+
+    #[test]
+    fn history_undo_and_redo_add() {
+        let store = MemoryStore::new();
+        let mut repository = Repository::open(Box::new(store.clone())).unwrap();
+        let mut history = History::new();
+        let added = repository.add("added", "user", "pw").unwrap().clone();
+        history.record(Action::Added(added.clone()));
+
+        history.undo(&mut repository).unwrap();
+        assert!(
+            repository.list().is_empty(),
+            "\n!!! Undone add is still listed"
+        );
+        assert_eq!(
+            ids(&store),
+            vec![added.id()],
+            "\n!!! Undone add was deleted for good instead of moved into the trash"
+        );
+
+        history.redo(&mut repository).unwrap();
+        assert_eq!(
+            repository.list().len(),
+            1,
+            "\n!!! Redone add was not restored"
+        );
+    }
+
+    #[test]
+    fn history_keeps_later_changes() {
+        let mut repository = Repository::open(Box::new(MemoryStore::new())).unwrap();
+        let mut history = History::new();
+        let before = repository.add("name", "user", "old").unwrap().clone();
+        let id = before.id();
+        let after = repository.update(&id, "renamed", "user", "new").unwrap().clone();
+        history.record(Action::Edited {
+            before: Box::new(before),
+            after: Box::new(after),
+        });
+        repository.set_favorite(&id, true).unwrap();
+        repository.set_tags(&id, &["work"]).unwrap();
+        repository.move_to_folder(&id, "Work").unwrap();
+
+        history.undo(&mut repository).unwrap();
+        let entry = repository.get(&id).unwrap();
+        assert_eq!(entry.name(), "name", "\n!!! The edit was not undone");
+        assert!(
+            entry.is_favorite() && entry.folder() == "Work" && entry.tags() == ["work"],
+            "\n!!! The undo reverted changes which were not part of the edit"
+        );
+
+        repository.delete(&id).unwrap();
+        history.redo(&mut repository).unwrap();
+        assert!(
+            repository.get(&id).unwrap().is_deleted(),
+            "\n!!! The redo took the entry out of the trash"
+        );
+    }
+
+    #[test]
+    fn history_undo_and_redo_edit() {
+        let mut repository = Repository::open(Box::new(MemoryStore::new())).unwrap();
+        let mut history = History::new();
+        let before = repository.add("name", "user", "old").unwrap().clone();
+        let after = repository
+            .update(&before.id(), "name", "user", "new")
+            .unwrap()
+            .clone();
+        history.record(Action::Edited {
+            before: Box::new(before),
+            after: Box::new(after),
+        });
+
+        let password = |repository: &Repository| {
+            let details = repository.list()[0].get_details().unwrap();
+            details.password.expose().clone()
+        };
+
+        history.undo(&mut repository).unwrap();
+        assert_eq!(password(&repository), "old", "\n!!! Edit was not undone");
+
+        history.redo(&mut repository).unwrap();
+        assert_eq!(password(&repository), "new", "\n!!! Edit was not redone");
+    }
+
+    #[test]
+    fn history_undo_delete_restores_entry() {
+        let mut repository = Repository::open(Box::new(MemoryStore::new())).unwrap();
+        let mut history = History::new();
+        let entry = repository.add("deleted", "user", "pw").unwrap().clone();
+        repository.delete(&entry.id()).unwrap();
+        history.record(Action::Deleted(entry));
+
+        history.undo(&mut repository).unwrap();
+
+        assert_eq!(
+            repository.list().len(),
+            1,
+            "\n!!! Undone delete is still inside the trash"
+        );
+        assert!(history.can_redo(), "\n!!! Undone delete can not be redone");
+    }
+
+    #[test]
+    fn history_record_drops_redo() {
+        let mut repository = Repository::open(Box::new(MemoryStore::new())).unwrap();
+        let mut history = History::new();
+        let first = repository.add("first", "user", "pw").unwrap().clone();
+        history.record(Action::Added(first));
+        history.undo(&mut repository).unwrap();
+
+        let second = repository.add("second", "user", "pw").unwrap().clone();
+        history.record(Action::Added(second));
+
+        assert!(!history.can_redo(), "\n!!! Redo survived a new action");
+        assert!(
+            history.redo(&mut repository).unwrap().is_none(),
+            "\n!!! Redo without actions did something"
+        );
+    }
+
+    #[test]
+    fn edits_change_the_values() {
+        let mut repository = Repository::open(Box::new(MemoryStore::new())).unwrap();
+        let added = repository.add("key", "user", "pw").unwrap().clone();
+        let id = added.id();
+
+        let used = repository.mark_used(&id).unwrap();
+        assert!(
+            used.same_values(&added),
+            "\n!!! Using an entry changed its values"
+        );
+        let edited = repository.update(&id, "key", "user", "pw").unwrap();
+        assert!(
+            !edited.same_values(&added),
+            "\n!!! An edit with the same values went unnoticed"
+        );
+    }
+
+    #[test]
+    fn revision_follows_changes() {
+        let mut repository = Repository::open(Box::new(MemoryStore::new())).unwrap();
+        let other = Repository::open(Box::new(MemoryStore::new())).unwrap();
+        assert_ne!(
+            repository.revision(),
+            other.revision(),
+            "\n!!! Two repositories share a revision"
+        );
+
+        let opened = repository.revision();
+        let id = repository.add("key", "user", "pw").unwrap().id();
+        let added = repository.revision();
+        assert_ne!(added, opened, "\n!!! Adding an entry kept the revision");
+
+        repository.get(&id).unwrap();
+        repository.list();
+        assert_eq!(
+            repository.revision(),
+            added,
+            "\n!!! Reading the entries changed the revision"
+        );
+        repository.delete(&id).unwrap();
+        assert_ne!(
+            repository.revision(),
+            added,
+            "\n!!! Deleting an entry kept the revision"
+        );
+    }
+}
@@ -3,7 +3,11 @@ use serde::{Deserialize, Serialize};
 use super::*;
 
 pub const DB_PATH: &str = "./data/db.json";
+pub const SQLITE_PATH: &str = "./data/db.sqlite";
+pub const CONFIG_PATH: &str = "./data/vault.json";
 pub const MASTER_PATH: &str = "./data/m.json";
+pub const AUDIT_PATH: &str = "./data/audit.log";
+pub const ATTACHMENT_FOLDER: &str = "./data/attachments";
 pub const DATA_FOLDER: &str = "./data";
 
 #[derive(Debug, Deserialize, Serialize)]
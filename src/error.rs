@@ -0,0 +1,640 @@
+//! The errors of all subsystems.
+//!
+//! Every variant has a stable code, which is shown to the user together with
+//! a message. Both are only defined here, inside the `describe` function of the
+//! error type of each subsystem.
+
+use std::{error::Error, fmt, io, string::FromUtf8Error};
+
+/// The error type returned by all fallible functions of the crate.
+#[derive(Debug)]
+pub enum InternalError {
+    Crypto(CryptoError),
+    Db(DbError),
+    Settings(SettingsError),
+    Agent(AgentError),
+    Socket(SocketError),
+    Cli(CliError),
+    Bus(BusError),
+}
+impl InternalError {
+    pub fn code(&self) -> String {
+        String::from(self.describe().0)
+    }
+
+    pub fn message(&self) -> String {
+        String::from(self.describe().1)
+    }
+
+    pub fn toast_message(&self) -> String {
+        format!("{} - {}", self.message(), self.code())
+    }
+
+    fn describe(&self) -> (&'static str, &'static str) {
+        match self {
+            InternalError::Crypto(err) => err.describe(),
+            InternalError::Db(err) => err.describe(),
+            InternalError::Settings(err) => err.describe(),
+            InternalError::Agent(err) => err.describe(),
+            InternalError::Socket(err) => err.describe(),
+            InternalError::Cli(err) => err.describe(),
+            InternalError::Bus(err) => err.describe(),
+        }
+    }
+}
+impl fmt::Display for InternalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.toast_message())
+    }
+}
+impl Error for InternalError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            InternalError::Crypto(err) => Some(err),
+            InternalError::Db(err) => Some(err),
+            InternalError::Settings(err) => Some(err),
+            InternalError::Agent(err) => Some(err),
+            InternalError::Socket(err) => Some(err),
+            InternalError::Cli(err) => Some(err),
+            InternalError::Bus(err) => Some(err),
+        }
+    }
+}
+impl From<CryptoError> for InternalError {
+    fn from(err: CryptoError) -> Self {
+        InternalError::Crypto(err)
+    }
+}
+impl From<DbError> for InternalError {
+    fn from(err: DbError) -> Self {
+        InternalError::Db(err)
+    }
+}
+impl From<SettingsError> for InternalError {
+    fn from(err: SettingsError) -> Self {
+        InternalError::Settings(err)
+    }
+}
+impl From<AgentError> for InternalError {
+    fn from(err: AgentError) -> Self {
+        InternalError::Agent(err)
+    }
+}
+impl From<SocketError> for InternalError {
+    fn from(err: SocketError) -> Self {
+        InternalError::Socket(err)
+    }
+}
+impl From<CliError> for InternalError {
+    fn from(err: CliError) -> Self {
+        InternalError::Cli(err)
+    }
+}
+impl From<BusError> for InternalError {
+    fn from(err: BusError) -> Self {
+        InternalError::Bus(err)
+    }
+}
+
+/// Errors of the `cryptography` module.
+#[derive(Debug)]
+pub enum CryptoError {
+    Encrypt,
+    HexDecode(hex::FromHexError),
+    Decrypt,
+    Utf8(FromUtf8Error),
+    /// None of the character sets are allowed for a generated password.
+    NoCharacters,
+    /// The generated password can not hold one character of every set.
+    PasswordTooShort,
+    /// The key of an attachment is not 32 bytes long.
+    InvalidKey,
+}
+impl CryptoError {
+    pub fn code(&self) -> &'static str {
+        self.describe().0
+    }
+
+    fn describe(&self) -> (&'static str, &'static str) {
+        match self {
+            CryptoError::Encrypt => ("[CR_EP-1]", "Failed to encrypt"),
+            CryptoError::HexDecode(_) => ("[CR_DP-1]", "Failed to decode HEX String into Vec."),
+            CryptoError::Decrypt => ("[CR_DP-2]", "Failed to decrypt data."),
+            CryptoError::Utf8(_) => (
+                "[CR_DP-3]",
+                "Failed to convert Vector of Bytes into String.",
+            ),
+            CryptoError::NoCharacters => (
+                "[CR_GP-1]",
+                "At least one kind of character has to be allowed.",
+            ),
+            CryptoError::PasswordTooShort => (
+                "[CR_GP-2]",
+                "The password is too short for the allowed kinds of characters.",
+            ),
+            CryptoError::InvalidKey => ("[CR_EB-1]", "The key has to be 32 bytes long."),
+        }
+    }
+}
+impl fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.describe().1)
+    }
+}
+impl Error for CryptoError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CryptoError::HexDecode(err) => Some(err),
+            CryptoError::Utf8(err) => Some(err),
+            CryptoError::Encrypt
+            | CryptoError::Decrypt
+            | CryptoError::NoCharacters
+            | CryptoError::PasswordTooShort
+            | CryptoError::InvalidKey => None,
+        }
+    }
+}
+
+/// Errors of the `data_base` module.
+#[derive(Debug)]
+pub enum DbError {
+    MasterParse(serde_json::Error),
+    MasterRead(io::Error),
+    /// The master file could not be opened, which is the case on first use.
+    MasterOpen(io::Error),
+    FolderCreate(io::Error),
+    FolderCheck(io::Error),
+    MasterSerialize(serde_json::Error),
+    MasterWrite(io::Error),
+    MasterWriteNew(io::Error),
+    MasterCreate(io::Error),
+    EntriesParse(serde_json::Error),
+    EntriesRead(io::Error),
+    EntriesSerialize(serde_json::Error),
+    EntriesWrite(io::Error),
+    EntriesReplace(io::Error),
+    ConfigParse(serde_json::Error),
+    ConfigRead(io::Error),
+    ConfigSerialize(serde_json::Error),
+    ConfigWrite(io::Error),
+    EntryExists,
+    /// The repository has no entry with the id.
+    EntryNotFound,
+    /// The store has no entry with the id of an updated one.
+    UpdatedNotFound,
+    /// The store has no entry with the id of a deleted one.
+    DeletedNotFound,
+    NotInTrash,
+    /// A folder was moved into itself.
+    FolderInsideItself,
+    PayloadSerialize(serde_json::Error),
+    PayloadParse(serde_json::Error),
+    /// The values of an entry which is not a login are missing.
+    PayloadMissing,
+    InvalidCardNumber,
+    InvalidExpiry,
+    InvalidCvv,
+    /// The file is larger than an attachment is allowed to be.
+    AttachmentTooLarge,
+    /// The attachments of the entry would be larger than allowed together.
+    AttachmentsFull,
+    AttachmentSource(io::Error),
+    AttachmentWrite(io::Error),
+    AttachmentRead(io::Error),
+    AttachmentNotFound,
+    AttachmentRemove(io::Error),
+    AttachmentFolder(io::Error),
+    AttachmentExport(io::Error),
+    StoreLocked,
+    /// The entries could not be locked to apply changes.
+    StoreLockedForChange,
+    PreserveBroken(io::Error),
+    BackupNotFound,
+    AuditSerialize(serde_json::Error),
+    AuditWrite(io::Error),
+    AuditRead(io::Error),
+    SqliteOpen(rusqlite::Error),
+    SqliteOpenMemory(rusqlite::Error),
+    SqliteSchema(rusqlite::Error),
+    SqlitePrepare(rusqlite::Error),
+    SqliteQuery(rusqlite::Error),
+    SqliteRow(rusqlite::Error),
+    SqliteEntryParse(serde_json::Error),
+    SqliteBegin(rusqlite::Error),
+    SqliteSerialize(serde_json::Error),
+    SqliteApply(rusqlite::Error),
+    /// No row of the database has the id of an updated entry.
+    SqliteNotFound,
+    SqliteCommit(rusqlite::Error),
+}
+impl DbError {
+    pub fn code(&self) -> &'static str {
+        self.describe().0
+    }
+
+    fn describe(&self) -> (&'static str, &'static str) {
+        match self {
+            DbError::MasterParse(_) => ("[DB_G_GM-1]", "Failed to convert buffer"),
+            DbError::MasterRead(_) => ("[DB_G_GM-2]", "Failed to read contents"),
+            DbError::MasterOpen(_) => ("[DB_G_GM-3]", "Failed to load file"),
+            DbError::FolderCreate(_) => ("[DB_P_SM-1]", "Failed to create the data folder."),
+            DbError::FolderCheck(_) => {
+                ("[DB_P_SM-5]", "Could not check if the data folder exists.")
+            }
+            DbError::MasterSerialize(_) => ("[DB_P_SM-6]", "Failed to write buffer"),
+            DbError::MasterWrite(_) => ("[DB_P_SM-2]", "Failed to save buffer in file"),
+            DbError::MasterWriteNew(_) => ("[DB_P_SM-3]", "Failed to save buffer in new file"),
+            DbError::MasterCreate(_) => ("[DB_P_SM-4]", "Failed to create file"),
+            DbError::EntriesParse(_) => ("[DB_G_GE-1]", "Failed to convert buffer"),
+            DbError::EntriesRead(_) => ("[DB_G_GE-2]", "Failed to read contents"),
+            DbError::EntriesSerialize(_) => ("[DB_P_SCL-1]", "Failed to write buffer"),
+            DbError::EntriesWrite(_) => ("[DB_P_SCL-2]", "Failed to save buffer in file"),
+            DbError::EntriesReplace(_) => ("[DB_P_SCL-3]", "Failed to replace the old file"),
+            DbError::ConfigParse(_) => ("[DB_ST_LC-1]", "Failed to convert buffer"),
+            DbError::ConfigRead(_) => ("[DB_ST_LC-2]", "Failed to read contents"),
+            DbError::ConfigSerialize(_) => ("[DB_ST_SC-1]", "Failed to write buffer"),
+            DbError::ConfigWrite(_) => ("[DB_ST_SC-2]", "Failed to save buffer in file"),
+            DbError::EntryExists => ("[DB_ST_AC-1]", "An entry with this id already exists"),
+            DbError::EntryNotFound => ("[DB_R_IO-1]", "Entry not found"),
+            DbError::UpdatedNotFound => ("[DB_ST_AC-2]", "Entry not found"),
+            DbError::DeletedNotFound => ("[DB_ST_AC-3]", "Entry not found"),
+            DbError::NotInTrash => ("[DB_R_P-1]", "Only entries inside the trash can be purged"),
+            DbError::FolderInsideItself => ("[DB_R_F-1]", "A folder can not be moved into itself"),
+            DbError::PayloadSerialize(_) => ("[DB_E_PL-1]", "Failed to write buffer"),
+            DbError::PayloadParse(_) => ("[DB_E_PL-2]", "Failed to convert buffer"),
+            DbError::PayloadMissing => ("[DB_E_PL-3]", "The values of the entry are missing"),
+            DbError::InvalidCardNumber => ("[DB_E_V-1]", "The card number is not valid"),
+            DbError::InvalidExpiry => ("[DB_E_V-2]", "The expiry has to be given as MM/YY"),
+            DbError::InvalidCvv => ("[DB_E_V-3]", "The CVV has to be 3 or 4 digits"),
+            DbError::AttachmentTooLarge => ("[DB_AT_A-1]", "The file is too large to be attached"),
+            DbError::AttachmentsFull => {
+                ("[DB_AT_A-2]", "The entry can not hold any more attachments")
+            }
+            DbError::AttachmentSource(_) => ("[DB_AT_A-3]", "Failed to read the file"),
+            DbError::AttachmentWrite(_) => ("[DB_AT_A-4]", "Failed to save the attachment"),
+            DbError::AttachmentRead(_) => ("[DB_AT_R-1]", "Failed to read the attachment"),
+            DbError::AttachmentNotFound => ("[DB_AT_R-2]", "Attachment not found"),
+            DbError::AttachmentRemove(_) => ("[DB_AT_D-1]", "Failed to delete the attachment"),
+            DbError::AttachmentFolder(_) => ("[DB_AT_C-1]", "Failed to read the attachments"),
+            DbError::AttachmentExport(_) => ("[DB_AT_S-1]", "Failed to save the file"),
+            DbError::StoreLocked => ("[DB_ME_L-1]", "Failed to lock entries"),
+            DbError::StoreLockedForChange => ("[DB_ME_T-1]", "Failed to lock entries"),
+            DbError::PreserveBroken(_) => ("[DB_RC_PB-1]", "Failed to move the broken file aside"),
+            DbError::BackupNotFound => ("[DB_RC_RB-1]", "The backup file does not exist"),
+            DbError::AuditSerialize(_) => ("[DB_AU_A-1]", "Failed to write buffer"),
+            DbError::AuditWrite(_) => ("[DB_AU_A-2]", "Failed to write the audit log"),
+            DbError::AuditRead(_) => ("[DB_AU_V-1]", "Failed to read the audit log"),
+            DbError::SqliteOpen(_) => ("[DB_SQ_O-1]", "Failed to open the database"),
+            DbError::SqliteOpenMemory(_) => ("[DB_SQ_O-2]", "Failed to open the database"),
+            DbError::SqliteSchema(_) => ("[DB_SQ_O-3]", "Failed to create the entries table"),
+            DbError::SqlitePrepare(_) => ("[DB_SQ_L-1]", "Failed to prepare the query"),
+            DbError::SqliteQuery(_) => ("[DB_SQ_L-2]", "Failed to query the entries"),
+            DbError::SqliteRow(_) => ("[DB_SQ_L-3]", "Failed to read row"),
+            DbError::SqliteEntryParse(_) => ("[DB_SQ_L-4]", "Failed to convert buffer"),
+            DbError::SqliteBegin(_) => ("[DB_SQ_T-1]", "Failed to start the transaction"),
+            DbError::SqliteSerialize(_) => ("[DB_SQ_T-2]", "Failed to write buffer"),
+            DbError::SqliteNotFound => ("[DB_SQ_T-3]", "Entry not found"),
+            DbError::SqliteApply(_) => ("[DB_SQ_T-4]", "Failed to apply the change"),
+            DbError::SqliteCommit(_) => ("[DB_SQ_T-5]", "Failed to commit the transaction"),
+        }
+    }
+}
+impl fmt::Display for DbError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.describe().1)
+    }
+}
+impl Error for DbError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DbError::MasterRead(err)
+            | DbError::MasterOpen(err)
+            | DbError::FolderCreate(err)
+            | DbError::FolderCheck(err)
+            | DbError::MasterWrite(err)
+            | DbError::MasterWriteNew(err)
+            | DbError::MasterCreate(err)
+            | DbError::EntriesRead(err)
+            | DbError::EntriesWrite(err)
+            | DbError::EntriesReplace(err)
+            | DbError::ConfigRead(err)
+            | DbError::ConfigWrite(err)
+            | DbError::PreserveBroken(err)
+            | DbError::AuditWrite(err)
+            | DbError::AuditRead(err)
+            | DbError::AttachmentSource(err)
+            | DbError::AttachmentWrite(err)
+            | DbError::AttachmentRead(err)
+            | DbError::AttachmentRemove(err)
+            | DbError::AttachmentFolder(err)
+            | DbError::AttachmentExport(err) => Some(err),
+            DbError::MasterParse(err)
+            | DbError::MasterSerialize(err)
+            | DbError::EntriesParse(err)
+            | DbError::EntriesSerialize(err)
+            | DbError::ConfigParse(err)
+            | DbError::ConfigSerialize(err)
+            | DbError::PayloadSerialize(err)
+            | DbError::PayloadParse(err)
+            | DbError::SqliteEntryParse(err)
+            | DbError::SqliteSerialize(err)
+            | DbError::AuditSerialize(err) => Some(err),
+            DbError::SqliteOpen(err)
+            | DbError::SqliteOpenMemory(err)
+            | DbError::SqliteSchema(err)
+            | DbError::SqlitePrepare(err)
+            | DbError::SqliteQuery(err)
+            | DbError::SqliteRow(err)
+            | DbError::SqliteBegin(err)
+            | DbError::SqliteApply(err)
+            | DbError::SqliteCommit(err) => Some(err),
+            DbError::EntryExists
+            | DbError::EntryNotFound
+            | DbError::UpdatedNotFound
+            | DbError::DeletedNotFound
+            | DbError::SqliteNotFound
+            | DbError::NotInTrash
+            | DbError::FolderInsideItself
+            | DbError::PayloadMissing
+            | DbError::InvalidCardNumber
+            | DbError::InvalidExpiry
+            | DbError::InvalidCvv
+            | DbError::AttachmentTooLarge
+            | DbError::AttachmentsFull
+            | DbError::AttachmentNotFound
+            | DbError::StoreLocked
+            | DbError::StoreLockedForChange
+            | DbError::BackupNotFound => None,
+        }
+    }
+}
+
+/// Errors of the settings of the user interface.
+#[derive(Debug)]
+pub enum SettingsError {
+    Serialize(serde_json::Error),
+    Write(io::Error),
+    WriteNew(io::Error),
+    Create(io::Error),
+}
+impl SettingsError {
+    pub fn code(&self) -> &'static str {
+        self.describe().0
+    }
+
+    fn describe(&self) -> (&'static str, &'static str) {
+        match self {
+            SettingsError::Serialize(_) => ("[UI_U_SS-1]", "Failed to write buffer"),
+            SettingsError::Write(_) => ("[UI_U_SS-2]", "Failed to save buffer"),
+            SettingsError::WriteNew(_) => ("[UI_U_SS-3]", "Failed to save buffer in new file"),
+            SettingsError::Create(_) => ("[UI_U_SS-4]", "Failed to create file"),
+        }
+    }
+}
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.describe().1)
+    }
+}
+impl Error for SettingsError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SettingsError::Serialize(err) => Some(err),
+            SettingsError::Write(err)
+            | SettingsError::WriteNew(err)
+            | SettingsError::Create(err) => Some(err),
+        }
+    }
+}
+
+/// Errors of the `ssh_agent` module.
+#[derive(Debug)]
+pub enum AgentError {
+    KeyParse(ssh_key::Error),
+    /// The passphrase of the key is wrong.
+    KeyDecrypt(ssh_key::Error),
+    /// Only ed25519, RSA and ECDSA keys are served.
+    UnsupportedKey,
+}
+impl AgentError {
+    pub fn code(&self) -> &'static str {
+        self.describe().0
+    }
+
+    fn describe(&self) -> (&'static str, &'static str) {
+        match self {
+            AgentError::KeyParse(_) => ("[AG_K-1]", "The private key is not in the OpenSSH format"),
+            AgentError::KeyDecrypt(_) => ("[AG_K-2]", "The passphrase of the key is wrong"),
+            AgentError::UnsupportedKey => (
+                "[AG_K-3]",
+                "Only ed25519, RSA and ECDSA keys can be used",
+            ),
+        }
+    }
+}
+impl fmt::Display for AgentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.describe().1)
+    }
+}
+impl Error for AgentError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AgentError::KeyParse(err) | AgentError::KeyDecrypt(err) => Some(err),
+            AgentError::UnsupportedKey => None,
+        }
+    }
+}
+
+/// Errors of the `local_socket` module.
+#[derive(Debug)]
+pub enum SocketError {
+    /// Another program is already listening on the socket.
+    InUse,
+    Remove(io::Error),
+    Bind(io::Error),
+    Permissions(io::Error),
+    /// The token clients have to send could not be stored.
+    Token(io::Error),
+    /// Another kind of file is in the place of the socket.
+    NotSocket,
+}
+impl SocketError {
+    pub fn code(&self) -> &'static str {
+        self.describe().0
+    }
+
+    fn describe(&self) -> (&'static str, &'static str) {
+        match self {
+            SocketError::InUse => ("[SO_B-1]", "Another program uses the socket"),
+            SocketError::Remove(_) => ("[SO_B-2]", "Failed to remove the old socket"),
+            SocketError::Bind(_) => ("[SO_B-3]", "Failed to open the socket"),
+            SocketError::Permissions(_) => (
+                "[SO_B-4]",
+                "Failed to restrict the socket to the current user",
+            ),
+            SocketError::Token(_) => ("[SO_B-5]", "Failed to store the token of the socket"),
+            SocketError::NotSocket => ("[SO_B-6]", "Another file is in the place of the socket"),
+        }
+    }
+}
+impl fmt::Display for SocketError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.describe().1)
+    }
+}
+impl Error for SocketError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SocketError::Remove(err)
+            | SocketError::Bind(err)
+            | SocketError::Permissions(err)
+            | SocketError::Token(err) => Some(err),
+            SocketError::InUse | SocketError::NotSocket => None,
+        }
+    }
+}
+
+/// Errors of the `cli` module.
+#[derive(Debug)]
+pub enum CliError {
+    /// The argument is not known.
+    UnknownArgument(String),
+    /// The reference does not look like `NAME=entry:<entry>/<field>`.
+    InvalidReference(String),
+    MissingProgram,
+    /// The template or the output file was not given.
+    MissingFile,
+    /// The folder of the app could not be entered or left.
+    AppFolder(io::Error),
+    Prompt(io::Error),
+    WrongMaster,
+    /// No entry has the name, the names of all entries are kept to show
+    /// them.
+    UnknownEntry {
+        name: String,
+        available: Vec<String>,
+    },
+    /// More than one entry has the name.
+    AmbiguousEntry(String),
+    /// The entry has no field with the name, the fields it has are kept to
+    /// show them.
+    UnknownField {
+        entry: String,
+        field: String,
+        available: Vec<String>,
+    },
+    Spawn(io::Error),
+    ReadTemplate(io::Error),
+    /// A placeholder of the vault on the line can not be read.
+    InvalidPlaceholder(usize),
+    WriteOutput(io::Error),
+    /// Reading the request of git or answering it failed.
+    Git(io::Error),
+    /// The vault has no login of the registry. Docker looks for exactly
+    /// this message.
+    CredentialsNotFound,
+    /// The request of docker can not be read.
+    InvalidCredential,
+    /// Reading the request of docker or answering it failed.
+    Docker(io::Error),
+}
+impl CliError {
+    pub fn code(&self) -> &'static str {
+        self.describe().0
+    }
+
+    fn describe(&self) -> (&'static str, &'static str) {
+        match self {
+            CliError::UnknownArgument(_) => ("[CL_A-1]", "Unknown argument"),
+            CliError::InvalidReference(_) => (
+                "[CL_A-2]",
+                "Secrets are referenced like NAME=entry:<entry>/<field>",
+            ),
+            CliError::MissingProgram => ("[CL_A-3]", "No command to run was given"),
+            CliError::MissingFile => (
+                "[CL_A-4]",
+                "The template and the output have to be given with -i and -o",
+            ),
+            CliError::AppFolder(_) => ("[CL_S-1]", "Failed to enter the folder of the app"),
+            CliError::Prompt(_) => ("[CL_M-1]", "Failed to read the master password"),
+            CliError::WrongMaster => ("[CL_M-2]", "The master password is wrong"),
+            CliError::UnknownEntry { .. } => ("[CL_R-1]", "There is no entry with this name"),
+            CliError::AmbiguousEntry(_) => ("[CL_R-2]", "More than one entry has this name"),
+            CliError::UnknownField { .. } => ("[CL_R-3]", "The entry has no field with this name"),
+            CliError::Spawn(_) => ("[CL_E-1]", "Failed to start the command"),
+            CliError::ReadTemplate(_) => ("[CL_T-1]", "Failed to read the template"),
+            CliError::InvalidPlaceholder(_) => (
+                "[CL_T-2]",
+                "Placeholders are written like {{ vault \"<entry>\" \"<field>\" }}",
+            ),
+            CliError::WriteOutput(_) => ("[CL_T-3]", "Failed to write the output"),
+            CliError::Git(_) => ("[CL_G-1]", "Failed to talk to git"),
+            CliError::CredentialsNotFound => {
+                ("[CL_D-1]", "credentials not found in native keychain")
+            }
+            CliError::InvalidCredential => ("[CL_D-2]", "The request of docker can not be read"),
+            CliError::Docker(_) => ("[CL_D-3]", "Failed to talk to docker"),
+        }
+    }
+}
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.describe().1)
+    }
+}
+impl Error for CliError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CliError::AppFolder(err)
+            | CliError::Prompt(err)
+            | CliError::Spawn(err)
+            | CliError::ReadTemplate(err)
+            | CliError::WriteOutput(err)
+            | CliError::Git(err)
+            | CliError::Docker(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Errors of the `secret_service` module.
+#[derive(Debug)]
+pub enum BusError {
+    /// The session bus can not be reached.
+    Connect(Box<dyn Error + Send + Sync>),
+    /// Another program, like the keyring of the desktop, already provides the
+    /// Secret Service.
+    NameTaken,
+    /// The collections or items could not be published on the bus.
+    Publish(Box<dyn Error + Send + Sync>),
+}
+impl BusError {
+    pub fn code(&self) -> &'static str {
+        self.describe().0
+    }
+
+    fn describe(&self) -> (&'static str, &'static str) {
+        match self {
+            BusError::Connect(_) => ("[BU_C-1]", "Failed to connect to the session bus"),
+            BusError::NameTaken => ("[BU_C-2]", "Another program provides the Secret Service"),
+            BusError::Publish(_) => (
+                "[BU_P-1]",
+                "Failed to publish the entries on the session bus",
+            ),
+        }
+    }
+}
+impl fmt::Display for BusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.describe().1)
+    }
+}
+impl Error for BusError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BusError::Connect(err) | BusError::Publish(err) => Some(err.as_ref()),
+            BusError::NameTaken => None,
+        }
+    }
+}
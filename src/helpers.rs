@@ -1,28 +1,70 @@
-#[derive(Debug)]
-pub struct InternalError {
-    code: String,
-    message: String,
-}
-impl InternalError {
-    pub fn new(code: &str, message: &str) -> Self {
-        Self {
-            code: String::from(code),
-            message: String::from(message),
-        }
+use std::{
+    cmp::Ordering,
+    fmt, fs,
+    io::{self, Write},
+    iter::Peekable,
+    path::{Path, PathBuf},
+    str::Chars,
+};
+
+/// Holds a **decrypted** value.
+///
+/// The value can only be read with [`Secret::expose`]. Formatting a secret
+/// never shows its value, so it can not end up inside a log line by accident.
+#[derive(Clone, Default, PartialEq)]
+pub struct Secret<T>(T);
+impl<T> Secret<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
     }
-    pub fn code(&self) -> String {
-        String::from(&self.code)
+
+    pub fn expose(&self) -> &T {
+        &self.0
     }
-    pub fn message(&self) -> String {
-        String::from(&self.message)
+}
+impl<T> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[REDACTED]")
     }
-    pub fn toast_message(&self) -> String {
-        format!("{} - {}", &self.message, &self.code)
+}
+impl<T> fmt::Display for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[REDACTED]")
     }
 }
 
+/// Moves into the folder of the executable, where the app keeps its data, for
+/// when it is started from somewhere else. Returns the folder it was in.
+pub fn enter_app_folder() -> io::Result<PathBuf> {
+    let previous = std::env::current_dir()?;
+    if let Some(folder) = std::env::current_exe()?.parent() {
+        std::env::set_current_dir(folder)?;
+    }
+
+    Ok(previous)
+}
+
+/// Writes the file so only the current user can read it, which also
+/// restricts a file that already exists. Other systems keep the permissions
+/// of the folder.
+pub fn write_private(path: &Path, content: &[u8]) -> io::Result<()> {
+    let mut options = fs::File::options();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let mut file = options.open(path)?;
+    // the mode is only used for new files
+    #[cfg(unix)]
+    file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+    file.write_all(content)
+}
+
 pub fn timestamp_as_date(timestamp: i64) -> String {
-    let date_time = chrono::DateTime::from_timestamp(timestamp, 0).expect("invalid timestamp");
+    let date_time = match chrono::DateTime::from_timestamp(timestamp, 0) {
+        Some(d) => d,
+        None => return String::from("Unknown"),
+    };
     let local: chrono::DateTime<chrono::Local> = chrono::DateTime::from(date_time);
     let date_string = local.naive_local().to_string();
     let parts = date_string.split(' ').collect::<Vec<&str>>();
@@ -44,3 +86,104 @@ pub fn timestamp_as_date(timestamp: i64) -> String {
 
     local_datetime.join(" ")
 }
+
+/// Checks if all characters of the query appear in the text in the same
+/// order, ignoring the case. Returns `None` if not, else a score which is
+/// higher the closer together the characters are and the more of them start
+/// a word.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text = text.to_lowercase().chars().collect::<Vec<char>>();
+    let mut score = 0;
+    let mut position = 0;
+    let mut last_match: Option<usize> = None;
+
+    for wanted in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = text[position..].iter().position(|c| *c == wanted)? + position;
+
+        let starts_word = found == 0 || !text[found - 1].is_alphanumeric();
+        score += match last_match {
+            Some(last) if last + 1 == found => 5,
+            _ if starts_word => 3,
+            _ => 1,
+        };
+        // gaps between the characters make a match worse
+        if let Some(last) = last_match {
+            score -= (found - last - 1).min(3) as i32;
+        }
+
+        last_match = Some(found);
+        position = found + 1;
+    }
+
+    Some(score)
+}
+
+/// Compares two texts like a human would, ignoring the case and comparing
+/// numbers by their value, so `Item 2` comes before `item 10`.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (a, b) = (a.to_lowercase(), b.to_lowercase());
+    let (mut a, mut b) = (a.chars().peekable(), b.chars().peekable());
+
+    loop {
+        match (a.peek(), b.peek()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let x = take_number(&mut a);
+                let y = take_number(&mut b);
+                // without leading zeros the longer number is the bigger one
+                let ordering = x.len().cmp(&y.len()).then_with(|| x.cmp(&y));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(x), Some(y)) => {
+                let ordering = x.cmp(y);
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a.next();
+                b.next();
+            }
+        }
+    }
+}
+
+fn take_number(chars: &mut Peekable<Chars>) -> String {
+    let mut number = String::new();
+    while let Some(c) = chars.next_if(|c| c.is_ascii_digit()) {
+        number.push(c);
+    }
+
+    String::from(number.trim_start_matches('0'))
+}
+
+/// The origin of an url, which is its scheme, host and port, e.g.
+/// `https://github.com` for `https://GitHub.com/login`. Urls without a scheme
+/// are taken as `https`, and the default port of the scheme is left out.
+pub fn url_origin(url: &str) -> Option<String> {
+    let (scheme, rest) = match url.trim().split_once("://") {
+        Some((scheme, rest)) => (scheme.to_lowercase(), rest),
+        None => (String::from("https"), url.trim()),
+    };
+    let authority = rest.split(['/', '?', '#']).next()?;
+    // a username and password inside the url are no part of the origin
+    let authority = authority.rsplit('@').next()?.to_lowercase();
+
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) if !authority.ends_with(']') => (host, Some(port)),
+        _ => (authority.as_str(), None),
+    };
+    if host.is_empty() || scheme.is_empty() {
+        return None;
+    }
+
+    match (scheme.as_str(), port) {
+        (_, Some(port)) if port.parse::<u16>().is_err() => None,
+        ("http", Some("80")) | ("https", Some("443")) | (_, None) => {
+            Some(format!("{}://{}", scheme, host))
+        }
+        (_, Some(port)) => Some(format!("{}://{}:{}", scheme, host, port)),
+    }
+}
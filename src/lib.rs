@@ -1,5 +1,17 @@
+pub mod cli;
 pub mod cryptography;
 pub mod data_base;
+pub mod error;
 pub mod helpers;
+#[cfg(unix)]
+pub mod local_api;
+#[cfg(unix)]
+pub mod local_socket;
+pub mod logging;
+pub mod native_messaging;
+#[cfg(target_os = "linux")]
+pub mod secret_service;
+#[cfg(unix)]
+pub mod ssh_agent;
 pub mod ui;
 pub mod env;
\ No newline at end of file
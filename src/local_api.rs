@@ -0,0 +1,287 @@
+//! The API scripts use to get secrets out of the unlocked vault.
+//!
+//! Clients connect to a Unix socket only the current user can use and send
+//! one JSON request per line, each answered by one JSON line. Every request
+//! has to hold the token of the session, which is stored next to the socket
+//! and changes whenever the API is started. The app asks the user before a
+//! client gets access to anything.
+
+use std::{
+    fmt, fs,
+    io::{self, BufRead, BufReader, Read, Write},
+    os::unix::net::UnixStream,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Sender},
+    },
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cryptography::generate_token,
+    error::{InternalError, SocketError},
+    helpers::write_private,
+    local_socket::{LocalSocket, Peer},
+};
+
+mod test;
+
+/// The socket the app listens on for clients.
+pub const API_SOCKET: &str = "./data/api.sock";
+/// The file holding the token of the session.
+pub const TOKEN_PATH: &str = "./data/api.token";
+/// Longer lines are refused and end the connection.
+pub const MAX_REQUEST_SIZE: usize = 64 * 1024;
+/// Longer client names are refused.
+const MAX_CLIENT_LENGTH: usize = 64;
+/// How long the user has to allow a request.
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// What a client can ask for.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ApiRequest {
+    /// The names of all entries.
+    List,
+    /// One field of an entry, e.g. the `password` of `GitHub`.
+    Get { entry: String, field: String },
+}
+
+/// One line a client sends. The client names itself, so the user knows who
+/// asks. Nothing checks the name, so what the user allows is remembered for
+/// the process of the client instead.
+#[derive(Deserialize, Serialize, Clone, PartialEq)]
+pub struct ApiMessage {
+    pub token: String,
+    pub client: String,
+    #[serde(flatten)]
+    pub request: ApiRequest,
+}
+
+#[derive(Deserialize, Serialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ApiResponse {
+    Names {
+        names: Vec<String>,
+    },
+    /// The **decrypted** value of the field.
+    Field {
+        value: String,
+    },
+    Error {
+        error: ApiRefusal,
+    },
+}
+impl fmt::Debug for ApiResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiResponse::Names { names } => write!(f, "Names {{ names: {:?} }}", names),
+            ApiResponse::Field { .. } => write!(f, "Field {{ .. }}"),
+            ApiResponse::Error { error } => write!(f, "Error {{ error: {:?} }}", error),
+        }
+    }
+}
+impl From<ApiRefusal> for ApiResponse {
+    fn from(error: ApiRefusal) -> Self {
+        ApiResponse::Error { error }
+    }
+}
+
+/// Why a request was not answered.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiRefusal {
+    /// The vault was locked in the meantime.
+    Locked,
+    InvalidToken,
+    /// The user did not allow it.
+    Denied,
+    /// There is no entry with the name, or more than one.
+    NotFound,
+    /// The entry has no field with the name.
+    UnknownField,
+    InvalidRequest,
+}
+
+/// A request of a client the app has to answer.
+#[derive(Debug)]
+pub struct ApiCall {
+    pub client: String,
+    /// The process on the other side of the socket, if the system tells it.
+    pub peer: Option<Peer>,
+    pub request: ApiRequest,
+    pub reply: Sender<ApiResponse>,
+}
+impl ApiCall {
+    /// The name of the client together with its program, for the user and
+    /// the logs.
+    pub fn label(&self) -> String {
+        match &self.peer {
+            Some(peer) => format!("{} ({})", self.client, peer.program.display()),
+            None => self.client.clone(),
+        }
+    }
+}
+
+/// The API while it runs. It is stopped when it is dropped, which also
+/// removes the token.
+pub struct LocalApi {
+    socket: LocalSocket,
+    token_path: PathBuf,
+    token: String,
+}
+impl LocalApi {
+    /// Starts to listen on the socket with a new token, which is written to
+    /// `token_path`. `wake` is called after every request, so the app can
+    /// handle it right away.
+    pub fn start(
+        path: impl AsRef<Path>,
+        token_path: impl AsRef<Path>,
+        calls: Sender<ApiCall>,
+        wake: impl Fn() + Send + Sync + 'static,
+    ) -> Result<Self, InternalError> {
+        let token = generate_token();
+        let socket = {
+            let token = token.clone();
+            LocalSocket::bind(path, move |stream, stop| {
+                answer(stream, &token, &calls, &wake, stop)
+            })?
+        };
+
+        let token_path = token_path.as_ref().to_path_buf();
+        if let Err(err) = write_private(&token_path, token.as_bytes()) {
+            return Err(SocketError::Token(err).into());
+        }
+        log::info!("Local API listening on {}", socket.path().display());
+
+        Ok(Self {
+            socket,
+            token_path,
+            token,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        self.socket.path()
+    }
+
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+}
+impl Drop for LocalApi {
+    fn drop(&mut self) {
+        if let Err(err) = fs::remove_file(&self.token_path) {
+            log::warn!("Failed to remove {}: {:?}", self.token_path.display(), err);
+        }
+        log::info!("Local API stopped");
+    }
+}
+
+/// Answers the requests of one client until it disconnects.
+fn answer(
+    stream: UnixStream,
+    token: &str,
+    calls: &Sender<ApiCall>,
+    wake: &(dyn Fn() + Send + Sync),
+    stop: &AtomicBool,
+) -> io::Result<()> {
+    let peer = Peer::of(&stream);
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut stream = stream;
+    while let Some(line) = read_line(&mut reader, stop)? {
+        if line.trim_ascii().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_slice::<ApiMessage>(&line) {
+            Ok(message) => handle(message, peer.clone(), token, calls, wake),
+            Err(_) => ApiRefusal::InvalidRequest.into(),
+        };
+        let mut buffer = serde_json::to_vec(&response)?;
+        buffer.push(b'\n');
+        stream.write_all(&buffer)?;
+    }
+
+    Ok(())
+}
+
+/// Reads one line without its line break. Returns `None` if the client
+/// disconnected or the socket was closed.
+fn read_line(reader: &mut BufReader<UnixStream>, stop: &AtomicBool) -> io::Result<Option<Vec<u8>>> {
+    let mut line = Vec::<u8>::new();
+    loop {
+        if stop.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+
+        let limit = (MAX_REQUEST_SIZE + 1 - line.len()) as u64;
+        match reader.by_ref().take(limit).read_until(b'\n', &mut line) {
+            Ok(0) if line.is_empty() => return Ok(None),
+            Ok(_) if line.ends_with(b"\n") => {
+                line.pop();
+                return Ok(Some(line));
+            }
+            Ok(_) if line.len() > MAX_REQUEST_SIZE => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "the request is too large",
+                ))
+            }
+            // the client closed its side after the last line
+            Ok(_) => return Ok(Some(line)),
+            Err(err)
+                if err.kind() == io::ErrorKind::WouldBlock
+                    || err.kind() == io::ErrorKind::TimedOut
+                    || err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+fn handle(
+    message: ApiMessage,
+    peer: Option<Peer>,
+    token: &str,
+    calls: &Sender<ApiCall>,
+    wake: &(dyn Fn() + Send + Sync),
+) -> ApiResponse {
+    if !tokens_match(&message.token, token) {
+        log::warn!("Refused {:?} because of a wrong token", message.client);
+        return ApiRefusal::InvalidToken.into();
+    }
+    let client = message.client.trim();
+    if client.is_empty() || client.len() > MAX_CLIENT_LENGTH {
+        return ApiRefusal::InvalidRequest.into();
+    }
+
+    let (reply, answer) = mpsc::channel();
+    let asked = calls.send(ApiCall {
+        client: String::from(client),
+        peer,
+        request: message.request,
+        reply,
+    });
+    if asked.is_err() {
+        return ApiRefusal::Locked.into();
+    }
+    wake();
+
+    answer
+        .recv_timeout(CONFIRM_TIMEOUT)
+        .unwrap_or(ApiRefusal::Denied.into())
+}
+
+/// Compares the tokens in constant time, so the time of a wrong guess does
+/// not tell how much of it was right.
+fn tokens_match(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
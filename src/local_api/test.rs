@@ -0,0 +1,170 @@
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        io::{BufRead, BufReader, Write},
+        os::unix::{fs::PermissionsExt, net::UnixStream},
+        path::PathBuf,
+        sync::mpsc,
+        thread,
+    };
+
+    use crate::{
+        error::{InternalError, SocketError},
+        local_api::{ApiRefusal, ApiRequest, ApiResponse, LocalApi},
+    };
+
+    fn temp_path(extension: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("vault-api-{}.{}", uuid::Uuid::new_v4(), extension))
+    }
+
+    fn ask(stream: &mut UnixStream, line: &str) -> ApiResponse {
+        stream.write_all(format!("{}\n", line).as_bytes()).unwrap();
+        let mut answer = String::new();
+        BufReader::new(stream.try_clone().unwrap())
+            .read_line(&mut answer)
+            .unwrap();
+
+        serde_json::from_str(&answer).unwrap()
+    }
+
+    #[test]
+    fn clients_need_the_token() {
+        let (socket, token_path) = (temp_path("sock"), temp_path("token"));
+        let (sender, calls) = mpsc::channel();
+        let api = LocalApi::start(&socket, &token_path, sender, || {}).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&token_path).unwrap(),
+            api.token(),
+            "\n!!! The token file holds another token",
+        );
+        for path in [&socket, &token_path] {
+            assert_eq!(
+                fs::metadata(path).unwrap().permissions().mode() & 0o777,
+                0o600,
+                "\n!!! Other users can use {}",
+                path.display(),
+            );
+        }
+
+        let mut stream = UnixStream::connect(&socket).unwrap();
+        assert_eq!(
+            ask(
+                &mut stream,
+                r#"{"token":"guess","client":"deploy","type":"list"}"#
+            ),
+            ApiResponse::Error {
+                error: ApiRefusal::InvalidToken
+            },
+            "\n!!! A wrong token was accepted",
+        );
+        assert!(
+            calls.try_recv().is_err(),
+            "\n!!! A request with a wrong token was passed on"
+        );
+
+        drop(api);
+        assert!(
+            !socket.exists() && !token_path.exists(),
+            "\n!!! The socket or token was left behind",
+        );
+    }
+
+    #[test]
+    fn other_files_are_kept() {
+        let (socket, token_path) = (temp_path("sock"), temp_path("token"));
+        fs::write(&socket, "notes").unwrap();
+        let (sender, _calls) = mpsc::channel();
+
+        assert!(
+            matches!(
+                LocalApi::start(&socket, &token_path, sender, || {}),
+                Err(InternalError::Socket(SocketError::NotSocket))
+            ),
+            "\n!!! The API started in the place of another file",
+        );
+        assert_eq!(
+            fs::read_to_string(&socket).unwrap(),
+            "notes",
+            "\n!!! The file was replaced by the socket",
+        );
+        fs::remove_file(&socket).unwrap();
+    }
+
+    #[test]
+    fn requests_are_passed_to_the_app() {
+        let (socket, token_path) = (temp_path("sock"), temp_path("token"));
+        let (sender, calls) = mpsc::channel();
+        let api = LocalApi::start(&socket, &token_path, sender, || {}).unwrap();
+        let app = thread::spawn(move || {
+            for call in calls {
+                assert_eq!(
+                    call.client, "deploy",
+                    "\n!!! The client name was not trimmed"
+                );
+                let peer = call
+                    .peer
+                    .as_ref()
+                    .expect("\n!!! The client process is unknown");
+                assert_eq!(
+                    (peer.pid, &peer.program),
+                    (std::process::id(), &std::env::current_exe().unwrap()),
+                    "\n!!! The client was taken for another process",
+                );
+                let response = match call.request {
+                    ApiRequest::List => ApiResponse::Names {
+                        names: vec![String::from("prod-db")],
+                    },
+                    ApiRequest::Get { entry, field } if entry == "prod-db" => ApiResponse::Field {
+                        value: format!("{} of {}", field, entry),
+                    },
+                    ApiRequest::Get { .. } => ApiRefusal::NotFound.into(),
+                };
+                call.reply.send(response).unwrap();
+            }
+        });
+
+        let message = |request: &str| {
+            format!(
+                r#"{{"token":"{}","client":" deploy ",{}}}"#,
+                api.token(),
+                request
+            )
+        };
+        let mut stream = UnixStream::connect(&socket).unwrap();
+        assert_eq!(
+            ask(&mut stream, &message(r#""type":"list""#)),
+            ApiResponse::Names {
+                names: vec![String::from("prod-db")]
+            },
+        );
+        assert_eq!(
+            ask(
+                &mut stream,
+                &message(r#""type":"get","entry":"prod-db","field":"password""#)
+            ),
+            ApiResponse::Field {
+                value: String::from("password of prod-db")
+            },
+            "\n!!! The field was not sent",
+        );
+        assert_eq!(
+            ask(&mut stream, "not json"),
+            ApiResponse::Error {
+                error: ApiRefusal::InvalidRequest
+            },
+        );
+        assert_eq!(
+            ask(&mut stream, &message(r#""type":"delete""#)),
+            ApiResponse::Error {
+                error: ApiRefusal::InvalidRequest
+            },
+            "\n!!! An unknown request was accepted",
+        );
+
+        drop(stream);
+        drop(api);
+        app.join().unwrap();
+    }
+}
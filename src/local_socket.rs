@@ -0,0 +1,228 @@
+//! A Unix socket for other programs of the same user.
+//!
+//! The socket can only be used by the current user. Every client is served on
+//! a thread of its own until it disconnects or the socket is closed.
+
+use std::{
+    fs,
+    io::{self, Read},
+    os::unix::{
+        fs::{DirBuilderExt, FileTypeExt, PermissionsExt},
+        net::{UnixListener, UnixStream},
+    },
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use crate::error::{InternalError, SocketError};
+
+/// How often the threads check if the socket was closed.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The process on the other side of a connection, as the system tells it.
+/// Unlike anything the client sends, it can not be made up.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Peer {
+    pub pid: u32,
+    /// The executable the process runs.
+    pub program: PathBuf,
+}
+impl Peer {
+    /// `None` if the system does not tell, which only Linux does.
+    pub fn of(stream: &UnixStream) -> Option<Self> {
+        let pid = peer_pid(stream)?;
+        let program = fs::read_link(format!("/proc/{}/exe", pid)).ok()?;
+
+        Some(Self { pid, program })
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn peer_pid(stream: &UnixStream) -> Option<u32> {
+    use std::os::unix::io::AsRawFd;
+
+    let mut credentials = libc::ucred {
+        pid: 0,
+        uid: 0,
+        gid: 0,
+    };
+    let mut length = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+    // the buffer has the size and layout the option is written with
+    let result = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            &mut credentials as *mut libc::ucred as *mut libc::c_void,
+            &mut length,
+        )
+    };
+    match result == 0 && credentials.pid > 0 {
+        true => Some(credentials.pid as u32),
+        false => None,
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn peer_pid(_stream: &UnixStream) -> Option<u32> {
+    None
+}
+
+/// A listening socket. It is closed when it is dropped, which also removes
+/// its file.
+pub struct LocalSocket {
+    path: PathBuf,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+impl LocalSocket {
+    /// Starts to listen on the path and passes every client to `serve`,
+    /// together with the flag telling when the socket is closed. Reads of the
+    /// client time out regularly so the flag can be checked, see
+    /// [`read_full`].
+    pub fn bind(
+        path: impl AsRef<Path>,
+        serve: impl Fn(UnixStream, &AtomicBool) -> io::Result<()> + Send + Sync + 'static,
+    ) -> Result<Self, InternalError> {
+        let path = path.as_ref().to_path_buf();
+        if let Ok(metadata) = fs::symlink_metadata(&path) {
+            // only a socket is replaced, any other file is kept
+            if !metadata.file_type().is_socket() {
+                return Err(SocketError::NotSocket.into());
+            }
+            // a socket without anyone listening is left over from a crash
+            if UnixStream::connect(&path).is_ok() {
+                return Err(SocketError::InUse.into());
+            }
+            if let Err(err) = fs::remove_file(&path) {
+                return Err(SocketError::Remove(err).into());
+            }
+        }
+
+        let listener = bind_private(&path)?;
+        if let Err(err) = listener.set_nonblocking(true) {
+            let _ = fs::remove_file(&path);
+            return Err(SocketError::Bind(err).into());
+        }
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let stop = stop.clone();
+            let serve = Arc::new(serve);
+            thread::spawn(move || listen(listener, serve, stop))
+        };
+
+        Ok(Self {
+            path,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+impl Drop for LocalSocket {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+        if let Err(err) = fs::remove_file(&self.path) {
+            log::warn!("Failed to remove {}: {:?}", self.path.display(), err);
+        }
+    }
+}
+
+/// Binds the socket inside a folder only the current user can enter and
+/// moves it to the path once it is restricted as well, so no one else can
+/// connect in between.
+fn bind_private(path: &Path) -> Result<UnixListener, SocketError> {
+    let parent = match path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => Path::new("."),
+    };
+    let folder = parent.join(format!(".socket-{}", uuid::Uuid::new_v4().simple()));
+    if let Err(err) = fs::DirBuilder::new().mode(0o700).create(&folder) {
+        return Err(SocketError::Bind(err));
+    }
+
+    let private = folder.join("socket");
+    let bound = match UnixListener::bind(&private) {
+        Ok(listener) => fs::set_permissions(&private, fs::Permissions::from_mode(0o600))
+            .map_err(SocketError::Permissions)
+            .and_then(|_| fs::rename(&private, path).map_err(SocketError::Bind))
+            .map(|_| listener),
+        Err(err) => Err(SocketError::Bind(err)),
+    };
+    // the socket is only left inside the folder if it was not moved
+    let _ = fs::remove_file(&private);
+    let _ = fs::remove_dir(&folder);
+
+    bound
+}
+
+fn listen<S>(listener: UnixListener, serve: Arc<S>, stop: Arc<AtomicBool>)
+where
+    S: Fn(UnixStream, &AtomicBool) -> io::Result<()> + Send + Sync + 'static,
+{
+    let mut clients = Vec::<JoinHandle<()>>::new();
+    while !stop.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                let (serve, stop) = (serve.clone(), stop.clone());
+                clients.push(thread::spawn(move || {
+                    let served = stream
+                        .set_nonblocking(false)
+                        .and_then(|_| stream.set_read_timeout(Some(POLL_INTERVAL)))
+                        .and_then(|_| serve(stream, &stop));
+                    if let Err(err) = served {
+                        log::debug!("Connection closed: {:?}", err);
+                    }
+                }));
+            }
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+            Err(err) => {
+                log::warn!("Failed to accept a connection: {:?}", err);
+                thread::sleep(POLL_INTERVAL);
+            }
+        }
+        clients.retain(|c| !c.is_finished());
+    }
+
+    for client in clients {
+        let _ = client.join();
+    }
+}
+
+/// Fills the buffer. Returns `false` if the client disconnected or the socket
+/// was closed.
+pub fn read_full(
+    stream: &mut UnixStream,
+    buffer: &mut [u8],
+    stop: &AtomicBool,
+) -> io::Result<bool> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        if stop.load(Ordering::Relaxed) {
+            return Ok(false);
+        }
+        match stream.read(&mut buffer[filled..]) {
+            Ok(0) => return Ok(false),
+            Ok(read) => filled += read,
+            Err(err)
+                if err.kind() == io::ErrorKind::WouldBlock
+                    || err.kind() == io::ErrorKind::TimedOut
+                    || err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(true)
+}
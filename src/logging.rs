@@ -0,0 +1,149 @@
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use serde::{Deserialize, Serialize};
+
+mod test;
+
+const LOG_PATH: &str = "./data/vault.log";
+/// Size at which the log file is rotated.
+const MAX_LOG_BYTES: u64 = 1024 * 1024;
+/// How many rotated log files are kept next to the current one.
+const KEPT_LOGS: usize = 3;
+/// Overrides the level of the settings, e.g. `VAULT_LOG=debug`.
+const LEVEL_VAR: &str = "VAULT_LOG";
+
+/// The lowest level of messages which are written into the log file.
+#[derive(Debug, Deserialize, Serialize, Default, Clone, Copy, PartialEq)]
+pub enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+impl LogLevel {
+    fn filter(&self) -> LevelFilter {
+        match self {
+            LogLevel::Off => LevelFilter::Off,
+            LogLevel::Error => LevelFilter::Error,
+            LogLevel::Warn => LevelFilter::Warn,
+            LogLevel::Info => LevelFilter::Info,
+            LogLevel::Debug => LevelFilter::Debug,
+            LogLevel::Trace => LevelFilter::Trace,
+        }
+    }
+}
+
+/// Starts writing log messages into the log file inside the data folder.
+///
+/// Calling it again only changes the level.
+pub fn init(level: LogLevel) {
+    // only the first logger can be set, which is the one we want to keep
+    let _ = log::set_boxed_logger(Box::new(FileLogger::new(LOG_PATH, MAX_LOG_BYTES)));
+    set_level(level);
+}
+
+/// Changes the level of the log, unless it is overridden by the environment.
+pub fn set_level(level: LogLevel) {
+    let filter = match std::env::var(LEVEL_VAR) {
+        Ok(var) => var.parse::<LevelFilter>().unwrap_or(level.filter()),
+        Err(_) => level.filter(),
+    };
+
+    log::set_max_level(filter);
+}
+
+/// Writes the log into a file, which is rotated once it gets too big.
+///
+/// Logging must never stop the app, so failing to write is ignored. Messages
+/// written before the data folder exists are dropped.
+struct FileLogger {
+    path: PathBuf,
+    max_bytes: u64,
+    file: Mutex<Option<File>>,
+}
+impl FileLogger {
+    fn new(path: impl AsRef<Path>, max_bytes: u64) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            max_bytes,
+            file: Mutex::new(None),
+        }
+    }
+
+    fn write(&self, file: &mut Option<File>, line: &str) -> io::Result<()> {
+        let size = match fs::metadata(&self.path) {
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        };
+        if size > 0 && size + line.len() as u64 > self.max_bytes {
+            *file = None;
+            rotate(&self.path)?;
+        }
+
+        let f = match file {
+            Some(f) => f,
+            None => file.insert(File::options().create(true).append(true).open(&self.path)?),
+        };
+
+        f.write_all(line.as_bytes())
+    }
+}
+impl Log for FileLogger {
+    /// The messages of the dependencies are only logged from warnings up,
+    /// since e.g. the rendering is very chatty.
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.target().starts_with("vault") || metadata.level() <= Level::Warn
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let line = format!(
+            "{} [{}] {}: {}\n",
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+
+        if let Ok(mut file) = self.file.lock() {
+            let _ = self.write(&mut file, &line);
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            if let Some(f) = file.as_mut() {
+                let _ = f.flush();
+            }
+        }
+    }
+}
+
+/// Moves every log file one number up, so `vault.log` becomes `vault.log.1`.
+/// The oldest one is dropped.
+fn rotate(path: &Path) -> io::Result<()> {
+    for index in (1..KEPT_LOGS).rev() {
+        let from = rotated(path, index);
+        if from.exists() {
+            fs::rename(&from, rotated(path, index + 1))?;
+        }
+    }
+
+    fs::rename(path, rotated(path, 1))
+}
+
+fn rotated(path: &Path, index: usize) -> PathBuf {
+    PathBuf::from(format!("{}.{}", path.display(), index))
+}
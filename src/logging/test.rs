@@ -0,0 +1,64 @@
+#[cfg(test)]
+mod tests {
+    use log::{Level, Log, Record};
+
+    use crate::{data_base::Entry, logging::FileLogger};
+
+    fn log_line(logger: &FileLogger, message: &str) {
+        logger.log(
+            &Record::builder()
+                .args(format_args!("{}", message))
+                .level(Level::Info)
+                .target("vault::test")
+                .build(),
+        );
+    }
+
+    #[test]
+    fn log_file_is_rotated() {
+        let dir = std::env::temp_dir().join(format!("vault-log-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir(&dir).unwrap();
+        let path = dir.join("vault.log");
+        let logger = FileLogger::new(&path, 100);
+
+        for index in 0..10 {
+            log_line(&logger, &format!("line number {}", index));
+        }
+
+        let current = std::fs::read_to_string(&path).unwrap();
+        assert!(
+            current.contains("line number 9"),
+            "\n!!! Last line is not inside the current log file",
+        );
+        assert!(current.len() <= 100, "\n!!! Log file was not rotated",);
+        assert!(
+            dir.join("vault.log.1").exists() && dir.join("vault.log.3").exists(),
+            "\n!!! Rotated log files were not kept",
+        );
+        assert!(
+            !dir.join("vault.log.4").exists(),
+            "\n!!! Too many rotated log files were kept",
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn secrets_are_redacted() {
+        let details = Entry::new("name", "secret user", "secret password")
+            .unwrap()
+            .get_details()
+            .unwrap();
+        let formatted = format!("{:?} {}", details, details.password);
+
+        assert!(
+            !formatted.contains("secret user") && !formatted.contains("secret password"),
+            "\n!!! Decrypted values are part of the formatted entry",
+        );
+        assert_eq!(
+            details.password.expose(),
+            "secret password",
+            "\n!!! Secret does not hold the value anymore",
+        );
+    }
+}
@@ -1,7 +1,32 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use vault::ui::setup;
+use std::io::IsTerminal;
+
+use vault::{cli, native_messaging, ui::setup};
 
 fn main() {
-    setup();
-}
\ No newline at end of file
+    let args = std::env::args().collect::<Vec<String>>();
+    let interactive = std::io::stdin().is_terminal();
+    if let Some(extension) = native_messaging::host_caller(&args, interactive) {
+        if let Err(err) = native_messaging::run(&extension) {
+            eprintln!("The native messaging host failed: {}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+    if let Some(command) = cli::command(&args) {
+        let code = match command.and_then(cli::execute) {
+            Ok(code) => code,
+            Err(err) => {
+                eprintln!("{}", cli::report(&err));
+                1
+            }
+        };
+        std::process::exit(code);
+    }
+
+    if let Err(err) = setup() {
+        eprintln!("Failed to start Vault: {}", err);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,267 @@
+//! The host the browser extension talks to.
+//!
+//! Browsers start the app as a native messaging host and talk to it over
+//! stdin and stdout. Every message is JSON with its length in front, as a
+//! `u32` in the byte order of the system. The host does not open the vault
+//! itself, it passes the requests on to the running app, which only answers
+//! while the vault is unlocked and the user allowed it.
+
+use std::{
+    fmt,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    helpers::enter_app_folder,
+    logging::{self, LogLevel},
+};
+
+#[cfg(unix)]
+mod bridge;
+mod test;
+
+#[cfg(unix)]
+pub use bridge::{BrowserBridge, BrowserRequest};
+
+/// Starts the host by hand, e.g. `vault --native-messaging <extension>`.
+pub const HOST_FLAG: &str = "--native-messaging";
+/// The socket the app listens on for the host.
+pub const BRIDGE_SOCKET: &str = "./data/browser.sock";
+/// Overrides [`BRIDGE_SOCKET`], e.g. to talk to another instance.
+const SOCKET_VAR: &str = "VAULT_BROWSER_SOCKET";
+/// Larger requests of the extension are refused.
+pub const MAX_REQUEST_SIZE: usize = 64 * 1024;
+/// The largest message browsers accept from a host.
+pub const MAX_RESPONSE_SIZE: usize = 1024 * 1024;
+
+/// What the extension can ask for.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Request {
+    /// If the vault is unlocked.
+    Status,
+    /// The logins of a site, e.g. `https://github.com`.
+    Credentials { origin: String },
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Response {
+    Status {
+        unlocked: bool,
+    },
+    Credentials {
+        origin: String,
+        credentials: Vec<Credential>,
+    },
+    Error {
+        error: Refusal,
+    },
+}
+
+/// Why a request was not answered.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Refusal {
+    /// The app is not running or the vault is locked.
+    Locked,
+    /// The user did not allow it.
+    Denied,
+    InvalidRequest,
+    /// The app gave no valid answer.
+    Failed,
+}
+
+/// The **decrypted** login of an entry.
+#[derive(Deserialize, Serialize, Clone, PartialEq)]
+pub struct Credential {
+    pub name: String,
+    pub username: String,
+    pub password: String,
+}
+impl fmt::Debug for Credential {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Credential {{ name: {:?}, .. }}", self.name)
+    }
+}
+
+/// A request the host passes on to the app, together with the extension
+/// which sent it.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct BridgeRequest {
+    pub extension: String,
+    pub request: Request,
+}
+
+/// The extension which started the app as its host, if it was. Chrome passes
+/// the origin of the extension, Firefox the absolute path of the host
+/// manifest followed by the id of the extension.
+///
+/// Browsers never start the host from a terminal, so an `interactive` start
+/// with arguments like the ones of Firefox is left to the command line.
+pub fn host_caller(args: &[String], interactive: bool) -> Option<String> {
+    match args {
+        [_, flag, extension, ..] if flag == HOST_FLAG => Some(extension.clone()),
+        [_, flag] if flag == HOST_FLAG => Some(String::from("unknown")),
+        [_, origin, ..] if origin.starts_with("chrome-extension://") => Some(origin.clone()),
+        [_, manifest, extension]
+            if !interactive && is_firefox_manifest(manifest) && is_firefox_id(extension) =>
+        {
+            Some(extension.clone())
+        }
+        _ => None,
+    }
+}
+
+fn is_firefox_manifest(path: &str) -> bool {
+    Path::new(path).is_absolute() && path.ends_with(".json")
+}
+
+/// Firefox ids look like an email address or a UUID inside braces, e.g.
+/// `vault@example.org`.
+fn is_firefox_id(id: &str) -> bool {
+    let email = id.split_once('@').is_some_and(|(name, domain)| {
+        !name.is_empty() && !domain.is_empty() && !id.contains(char::is_whitespace)
+    });
+    let uuid = id.starts_with('{') && id.ends_with('}') && id.len() > 2;
+
+    email || uuid
+}
+
+/// Runs the host until the browser closes it.
+///
+/// Browsers start the host from wherever they like, so it moves into the
+/// folder of the executable first.
+pub fn run(extension: &str) -> io::Result<()> {
+    enter_app_folder()?;
+    logging::init(LogLevel::default());
+    log::info!("Native messaging host started for {}", extension);
+
+    let socket = match std::env::var(SOCKET_VAR) {
+        Ok(path) => PathBuf::from(path),
+        Err(_) => PathBuf::from(BRIDGE_SOCKET),
+    };
+    let served = serve(
+        &mut io::stdin().lock(),
+        &mut io::stdout().lock(),
+        extension,
+        |request| forward(&socket, request),
+    );
+    log::info!("Native messaging host stopped");
+
+    served
+}
+
+/// Answers the messages of the browser until it closes the input. `forward`
+/// passes every valid request on to the app.
+pub fn serve(
+    input: &mut impl Read,
+    output: &mut impl Write,
+    extension: &str,
+    mut forward: impl FnMut(&BridgeRequest) -> Response,
+) -> io::Result<()> {
+    while let Some(message) = read_message(input, MAX_REQUEST_SIZE)? {
+        let response = match serde_json::from_slice::<Request>(&message) {
+            Ok(request) => forward(&BridgeRequest {
+                extension: String::from(extension),
+                request,
+            }),
+            Err(err) => {
+                log::warn!("Invalid request of {}: {}", extension, err);
+                Response::Error {
+                    error: Refusal::InvalidRequest,
+                }
+            }
+        };
+        write_message(output, &serde_json::to_vec(&response)?)?;
+    }
+
+    Ok(())
+}
+
+/// Reads one message. Returns `None` once the input was closed.
+pub fn read_message(reader: &mut impl Read, max_size: usize) -> io::Result<Option<Vec<u8>>> {
+    let mut length = [0u8; 4];
+    match reader.read_exact(&mut length) {
+        Ok(_) => {}
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err),
+    }
+
+    let length = u32::from_ne_bytes(length) as usize;
+    if length > max_size {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "the message is too large",
+        ));
+    }
+    let mut message = vec![0u8; length];
+    reader.read_exact(&mut message)?;
+
+    Ok(Some(message))
+}
+
+pub fn write_message(writer: &mut impl Write, message: &[u8]) -> io::Result<()> {
+    if message.len() > MAX_RESPONSE_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the message is too large",
+        ));
+    }
+
+    writer.write_all(&(message.len() as u32).to_ne_bytes())?;
+    writer.write_all(message)?;
+    writer.flush()
+}
+
+/// The answer while the app does not listen, which means the vault is
+/// locked.
+fn not_running(request: &BridgeRequest) -> Response {
+    match request.request {
+        Request::Status => Response::Status { unlocked: false },
+        Request::Credentials { .. } => Response::Error {
+            error: Refusal::Locked,
+        },
+    }
+}
+
+/// Passes the request on to the app listening on the socket.
+#[cfg(unix)]
+pub fn forward(socket: &Path, request: &BridgeRequest) -> Response {
+    let mut stream = match std::os::unix::net::UnixStream::connect(socket) {
+        Ok(s) => s,
+        Err(_) => return not_running(request),
+    };
+
+    let answered = serde_json::to_vec(request)
+        .map_err(io::Error::from)
+        .and_then(|buffer| write_message(&mut stream, &buffer))
+        .and_then(|_| read_message(&mut stream, MAX_RESPONSE_SIZE));
+    match answered {
+        Ok(Some(buffer)) => match serde_json::from_slice::<Response>(&buffer) {
+            Ok(response) => response,
+            Err(err) => {
+                log::warn!("Invalid answer of the app: {}", err);
+                Response::Error {
+                    error: Refusal::Failed,
+                }
+            }
+        },
+        Ok(None) => not_running(request),
+        Err(err) => {
+            log::warn!("Failed to ask the app: {:?}", err);
+            Response::Error {
+                error: Refusal::Failed,
+            }
+        }
+    }
+}
+
+/// The app can only be asked over a Unix socket, so it is never running.
+#[cfg(not(unix))]
+pub fn forward(_socket: &Path, request: &BridgeRequest) -> Response {
+    not_running(request)
+}
@@ -0,0 +1,121 @@
+use std::{
+    io,
+    os::unix::net::UnixStream,
+    path::Path,
+    sync::{
+        atomic::AtomicBool,
+        mpsc::{self, Sender},
+    },
+    time::Duration,
+};
+
+use crate::{
+    error::InternalError,
+    local_socket::{read_full, LocalSocket},
+};
+
+use super::*;
+
+/// How long the user has to allow a request.
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Logins the app is asked for. The reply holds the logins, or nothing
+/// if the user refused.
+#[derive(Debug)]
+pub struct BrowserRequest {
+    pub extension: String,
+    pub origin: String,
+    pub reply: Sender<Option<Vec<Credential>>>,
+}
+
+/// The socket the host passes the requests of the extension on to. It is
+/// closed when it is dropped.
+pub struct BrowserBridge {
+    _socket: LocalSocket,
+}
+impl BrowserBridge {
+    /// Starts to listen on the socket. `wake` is called after every
+    /// request, so the app can handle it right away.
+    pub fn start(
+        path: impl AsRef<Path>,
+        requests: Sender<BrowserRequest>,
+        wake: impl Fn() + Send + Sync + 'static,
+    ) -> Result<Self, InternalError> {
+        let socket = LocalSocket::bind(path, move |stream, stop| {
+            answer(stream, &requests, &wake, stop)
+        })?;
+        log::info!("Browser bridge listening on {}", socket.path().display());
+
+        Ok(Self { _socket: socket })
+    }
+}
+impl Drop for BrowserBridge {
+    fn drop(&mut self) {
+        log::info!("Browser bridge stopped");
+    }
+}
+
+/// Answers the requests of one host until it disconnects.
+fn answer(
+    mut stream: UnixStream,
+    requests: &Sender<BrowserRequest>,
+    wake: &(dyn Fn() + Send + Sync),
+    stop: &AtomicBool,
+) -> io::Result<()> {
+    loop {
+        let mut length = [0u8; 4];
+        if !read_full(&mut stream, &mut length, stop)? {
+            return Ok(());
+        }
+        let length = u32::from_ne_bytes(length) as usize;
+        if length > MAX_REQUEST_SIZE {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid length"));
+        }
+        let mut message = vec![0u8; length];
+        if !read_full(&mut stream, &mut message, stop)? {
+            return Ok(());
+        }
+
+        let response = match serde_json::from_slice::<BridgeRequest>(&message) {
+            Ok(request) => handle(request, requests, wake),
+            Err(_) => Response::Error {
+                error: Refusal::InvalidRequest,
+            },
+        };
+        write_message(&mut stream, &serde_json::to_vec(&response)?)?;
+    }
+}
+
+fn handle(
+    request: BridgeRequest,
+    requests: &Sender<BrowserRequest>,
+    wake: &(dyn Fn() + Send + Sync),
+) -> Response {
+    let origin = match request.request {
+        Request::Status => return Response::Status { unlocked: true },
+        Request::Credentials { origin } => origin,
+    };
+
+    let (reply, answer) = mpsc::channel();
+    let asked = requests.send(BrowserRequest {
+        extension: request.extension,
+        origin: origin.clone(),
+        reply,
+    });
+    if asked.is_err() {
+        return Response::Error {
+            error: Refusal::Locked,
+        };
+    }
+    wake();
+
+    match answer.recv_timeout(CONFIRM_TIMEOUT) {
+        Ok(Some(credentials)) => Response::Credentials {
+            origin,
+            credentials,
+        },
+        _ => Response::Error {
+            error: Refusal::Denied,
+        },
+    }
+}
@@ -0,0 +1,171 @@
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::native_messaging::{
+        forward, host_caller, read_message, serve, write_message, BridgeRequest, Credential,
+        Refusal, Request, Response, MAX_REQUEST_SIZE,
+    };
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| String::from(*a)).collect()
+    }
+
+    fn framed(messages: &[&str]) -> Cursor<Vec<u8>> {
+        let mut input = Vec::<u8>::new();
+        for message in messages {
+            write_message(&mut input, message.as_bytes()).unwrap();
+        }
+        Cursor::new(input)
+    }
+
+    fn responses(output: Vec<u8>) -> Vec<Response> {
+        let mut output = Cursor::new(output);
+        let mut responses = Vec::<Response>::new();
+        while let Some(message) = read_message(&mut output, usize::MAX).unwrap() {
+            responses.push(serde_json::from_slice(&message).unwrap());
+        }
+        responses
+    }
+
+    #[test]
+    fn host_is_started_by_browsers() {
+        assert_eq!(
+            host_caller(&args(&["vault", "chrome-extension://abc/"]), false),
+            Some(String::from("chrome-extension://abc/")),
+            "\n!!! The origin of a Chrome extension was not taken",
+        );
+        assert_eq!(
+            host_caller(
+                &args(&["vault", "/etc/vault.json", "vault@example.org"]),
+                false
+            ),
+            Some(String::from("vault@example.org")),
+            "\n!!! The id of a Firefox extension was not taken",
+        );
+        assert_eq!(
+            host_caller(&args(&["vault", "--native-messaging", "test"]), true),
+            Some(String::from("test")),
+        );
+        assert_eq!(
+            host_caller(&args(&["vault"]), false),
+            None,
+            "\n!!! The app was started as a host without any arguments",
+        );
+    }
+
+    #[test]
+    fn commands_are_not_taken_for_firefox() {
+        for (line, interactive) in [
+            (&["vault", "/etc/vault.json", "vault@example.org"][..], true),
+            (&["vault", "backup.json", "vault@example.org"][..], false),
+            (&["vault", "/etc/vault.json", "out.yml"][..], false),
+            (
+                &["vault", "/etc/vault.json", "vault@example.org", "-v"][..],
+                false,
+            ),
+        ] {
+            assert_eq!(
+                host_caller(&args(line), interactive),
+                None,
+                "\n!!! {:?} started the host",
+                line,
+            );
+        }
+        assert_eq!(
+            host_caller(
+                &args(&[
+                    "vault",
+                    "/etc/vault.json",
+                    "{9f1c6a34-0000-4000-8000-123456789abc}"
+                ]),
+                false
+            ),
+            Some(String::from("{9f1c6a34-0000-4000-8000-123456789abc}")),
+            "\n!!! The UUID of a Firefox extension was not taken",
+        );
+    }
+
+    #[test]
+    fn host_answers_every_request() {
+        let mut input = framed(&[
+            r#"{"type":"status"}"#,
+            r#"{"type":"credentials","origin":"https://github.com"}"#,
+            r#"{"type":"unknown"}"#,
+        ]);
+        let mut output = Vec::<u8>::new();
+        let mut forwarded = Vec::<BridgeRequest>::new();
+
+        serve(&mut input, &mut output, "test", |request| {
+            forwarded.push(request.clone());
+            match &request.request {
+                Request::Status => Response::Status { unlocked: true },
+                Request::Credentials { origin } => Response::Credentials {
+                    origin: origin.clone(),
+                    credentials: vec![Credential {
+                        name: String::from("GitHub"),
+                        username: String::from("user"),
+                        password: String::from("pw"),
+                    }],
+                },
+            }
+        })
+        .unwrap();
+
+        assert_eq!(
+            forwarded.len(),
+            2,
+            "\n!!! The invalid request was passed on"
+        );
+        assert_eq!(forwarded[1].extension, "test");
+        let responses = responses(output);
+        assert_eq!(responses.len(), 3, "\n!!! Not every request was answered");
+        assert_eq!(responses[0], Response::Status { unlocked: true });
+        assert!(matches!(
+            &responses[1],
+            Response::Credentials { credentials, .. } if credentials[0].password == "pw"
+        ));
+        assert_eq!(
+            responses[2],
+            Response::Error {
+                error: Refusal::InvalidRequest
+            },
+        );
+    }
+
+    #[test]
+    fn host_refuses_large_messages() {
+        let mut input = Cursor::new(((MAX_REQUEST_SIZE + 1) as u32).to_ne_bytes().to_vec());
+        assert!(
+            read_message(&mut input, MAX_REQUEST_SIZE).is_err(),
+            "\n!!! A too large message was read",
+        );
+    }
+
+    #[test]
+    fn vault_is_locked_without_the_app() {
+        let socket =
+            std::env::temp_dir().join(format!("vault-browser-{}.sock", uuid::Uuid::new_v4()));
+        let request = |request| BridgeRequest {
+            extension: String::from("test"),
+            request,
+        };
+
+        assert_eq!(
+            forward(&socket, &request(Request::Status)),
+            Response::Status { unlocked: false },
+        );
+        assert_eq!(
+            forward(
+                &socket,
+                &request(Request::Credentials {
+                    origin: String::from("https://github.com")
+                })
+            ),
+            Response::Error {
+                error: Refusal::Locked
+            },
+            "\n!!! Logins were asked for without the app listening",
+        );
+    }
+}
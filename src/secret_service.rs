@@ -0,0 +1,632 @@
+//! The Secret Service of freedesktop.org, which apps using libsecret store
+//! and look up their passwords with.
+//!
+//! While the vault is unlocked it owns the name `org.freedesktop.secrets` on
+//! the session bus. The entries on the top level form the collection `vault`,
+//! which is also the `default` one, and every folder forms a collection of its
+//! own. Every entry with a secret is an item of its collection, the secret
+//! being its password or the field which takes its place.
+//!
+//! Labels and attributes are open to every app of the user, just like the
+//! files of the vault. Secrets are only sent, and entries only changed, once
+//! the user allowed the app.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::{mpsc::Sender, Arc, Mutex, RwLock},
+    thread,
+};
+
+use zbus::{
+    blocking::{Connection, ConnectionBuilder},
+    fdo::{RequestNameFlags, RequestNameReply},
+    zvariant::ObjectPath,
+    SignalContext,
+};
+
+use crate::{
+    data_base::{
+        folder::{normalize, FolderNode},
+        repository::Repository,
+    },
+    error::{BusError, DbError, InternalError},
+    helpers::Secret,
+};
+
+use objects::{Collection, Item, Service, Shared};
+
+mod encryption;
+mod objects;
+mod test;
+
+pub const SERVICE_NAME: &str = "org.freedesktop.secrets";
+pub const SERVICE_PATH: &str = "/org/freedesktop/secrets";
+/// The collection of the entries on the top level.
+pub const VAULT_COLLECTION: &str = "vault";
+/// The only alias, which names the collection apps store into by default.
+pub const DEFAULT_ALIAS: &str = "default";
+
+/// The collections and items as they are shown on the bus.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Catalog {
+    pub collections: Vec<CollectionInfo>,
+}
+impl Catalog {
+    /// The top level and every folder, each with its entries which have a
+    /// secret.
+    pub fn build(repository: &Repository, folders: &[String]) -> Self {
+        let tree = FolderNode::build(folders, &repository.list());
+        let mut catalog = Catalog::default();
+        catalog.add(&tree, repository);
+
+        catalog
+    }
+
+    fn add(&mut self, node: &FolderNode, repository: &Repository) {
+        let items = node
+            .entries
+            .iter()
+            .filter_map(|id| repository.get(id))
+            .filter(|e| e.kind().secret_index().is_some())
+            .map(|e| ItemInfo {
+                entry: e.id(),
+                label: e.name(),
+                attributes: e.attributes().clone(),
+                created: e.created_at().max(0) as u64,
+                modified: e.last_edited().max(0) as u64,
+            })
+            .collect();
+        self.collections.push(CollectionInfo {
+            id: collection_id(&node.path),
+            folder: node.path.clone(),
+            label: match node.path.is_empty() {
+                true => String::from("Vault"),
+                false => node.path.clone(),
+            },
+            items,
+        });
+
+        for folder in &node.folders {
+            self.add(folder, repository);
+        }
+    }
+
+    pub fn collection(&self, id: &str) -> Option<&CollectionInfo> {
+        self.collections.iter().find(|c| c.id == id)
+    }
+
+    /// The item together with the collection it is inside of.
+    pub fn item(&self, path: &str) -> Option<(&CollectionInfo, &ItemInfo)> {
+        let (collection, item) = path
+            .strip_prefix(SERVICE_PATH)?
+            .strip_prefix("/collection/")?
+            .split_once('/')?;
+        let collection = self.collection(collection)?;
+        let item = collection
+            .items
+            .iter()
+            .find(|i| item_id(&i.entry) == item)?;
+
+        Some((collection, item))
+    }
+}
+
+/// A folder, or the top level, as a collection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CollectionInfo {
+    /// The last part of the object path.
+    pub id: String,
+    /// Empty for the top level.
+    pub folder: String,
+    pub label: String,
+    pub items: Vec<ItemInfo>,
+}
+impl CollectionInfo {
+    pub fn path(&self) -> String {
+        format!("{}/collection/{}", SERVICE_PATH, self.id)
+    }
+
+    pub fn item_path(&self, item: &ItemInfo) -> String {
+        format!("{}/{}", self.path(), item_id(&item.entry))
+    }
+}
+
+/// An entry as an item.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemInfo {
+    /// The id of the entry.
+    pub entry: String,
+    pub label: String,
+    pub attributes: BTreeMap<String, String>,
+    pub created: u64,
+    pub modified: u64,
+}
+
+/// Object paths only allow letters, digits and `_`, so the path of a folder
+/// is written in hex.
+pub fn collection_id(folder: &str) -> String {
+    match folder.is_empty() {
+        true => String::from(VAULT_COLLECTION),
+        false => format!("folder_{}", hex::encode(folder)),
+    }
+}
+
+fn item_id(entry: &str) -> String {
+    entry.replace('-', "_")
+}
+
+/// What an app asks the vault to do.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServiceRequest {
+    /// Send the secret of the entry.
+    GetSecret {
+        entry: String,
+    },
+    SetSecret {
+        entry: String,
+        secret: Secret<String>,
+    },
+    SetLabel {
+        entry: String,
+        label: String,
+    },
+    SetAttributes {
+        entry: String,
+        attributes: BTreeMap<String, String>,
+    },
+    /// Add an entry to the folder. With `replace`, an entry of the folder
+    /// with the same attributes is changed instead, if there is one.
+    CreateItem {
+        folder: String,
+        label: String,
+        attributes: BTreeMap<String, String>,
+        secret: Secret<String>,
+        replace: bool,
+    },
+    /// Move the entry into the trash.
+    DeleteItem {
+        entry: String,
+    },
+    /// Add a folder named after the label.
+    CreateCollection {
+        label: String,
+    },
+}
+impl ServiceRequest {
+    /// What the request does with the vault, which the user has to allow.
+    pub fn access(&self, repository: &Repository) -> Access {
+        match self {
+            ServiceRequest::GetSecret { entry } => Access::Read(entry.clone()),
+            ServiceRequest::SetSecret { entry, .. }
+            | ServiceRequest::SetLabel { entry, .. }
+            | ServiceRequest::SetAttributes { entry, .. }
+            | ServiceRequest::DeleteItem { entry } => Access::Change(entry.clone()),
+            ServiceRequest::CreateItem {
+                folder,
+                attributes,
+                replace,
+                ..
+            } => match replace {
+                true => {
+                    replaced(repository, folder, attributes).map_or(Access::Add, Access::Change)
+                }
+                false => Access::Add,
+            },
+            ServiceRequest::CreateCollection { .. } => Access::Add,
+        }
+    }
+}
+
+/// What the user has to allow, with the id of the entry.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Access {
+    Read(String),
+    Change(String),
+    /// Adding entries or folders, which touches no entry of the user.
+    Add,
+}
+impl Access {
+    /// `None` if no entry is touched.
+    pub fn entry(&self) -> Option<&str> {
+        match self {
+            Access::Read(id) | Access::Change(id) => Some(id),
+            Access::Add => None,
+        }
+    }
+}
+
+/// A request of an app the vault has to answer.
+#[derive(Debug)]
+pub struct ServiceCall {
+    pub client: ServiceClient,
+    pub request: ServiceRequest,
+    pub reply: async_channel::Sender<ServiceResponse>,
+}
+
+/// The app which sent a request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServiceClient {
+    /// The unique name of its connection to the bus, which no other app can
+    /// take. `None` if the request does not tell, like setting a property.
+    pub sender: Option<String>,
+    /// The name of its process. Any process can take any name, so it is only
+    /// shown to the user.
+    pub label: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServiceResponse {
+    /// The **decrypted** secret.
+    Secret(Secret<String>),
+    /// The id of the entry which was added or changed.
+    Item(String),
+    /// The path of the folder which was added.
+    Collection(String),
+    Done,
+    Refused(ServiceRefusal),
+}
+impl From<ServiceRefusal> for ServiceResponse {
+    fn from(refusal: ServiceRefusal) -> Self {
+        ServiceResponse::Refused(refusal)
+    }
+}
+
+/// Why a request was not answered.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ServiceRefusal {
+    /// The vault was locked in the meantime.
+    Locked,
+    /// The user did not allow it.
+    Denied,
+    /// The entry does not exist anymore, or has no secret.
+    NoSuchObject,
+    /// The request can not be done, or storing the change failed.
+    Failed,
+}
+
+/// Does what the request asks for, once the user allowed it. A folder which
+/// is added is also added to `folders`.
+pub fn apply(
+    repository: &mut Repository,
+    folders: &mut Vec<String>,
+    request: &ServiceRequest,
+) -> ServiceResponse {
+    match try_apply(repository, folders, request) {
+        Ok(response) => response,
+        Err(InternalError::Db(DbError::EntryNotFound)) => ServiceRefusal::NoSuchObject.into(),
+        Err(err) => {
+            log::error!("Secret Service: failed to answer a request: {:?}", err);
+            ServiceRefusal::Failed.into()
+        }
+    }
+}
+
+fn try_apply(
+    repository: &mut Repository,
+    folders: &mut Vec<String>,
+    request: &ServiceRequest,
+) -> Result<ServiceResponse, InternalError> {
+    Ok(match request {
+        ServiceRequest::GetSecret { entry } => match secret_of(repository, entry)? {
+            Some(secret) => ServiceResponse::Secret(secret),
+            None => ServiceRefusal::NoSuchObject.into(),
+        },
+        ServiceRequest::SetSecret { entry, secret } => {
+            if !set_secret(repository, entry, None, secret)? {
+                return Ok(ServiceRefusal::NoSuchObject.into());
+            }
+            ServiceResponse::Done
+        }
+        ServiceRequest::SetLabel { entry, label } => {
+            let details = match repository.get(entry) {
+                Some(e) => e.get_details()?,
+                None => return Ok(ServiceRefusal::NoSuchObject.into()),
+            };
+            repository.update_payload(entry, label, details.payload.expose())?;
+            ServiceResponse::Done
+        }
+        ServiceRequest::SetAttributes { entry, attributes } => {
+            repository.set_attributes(entry, attributes.clone())?;
+            ServiceResponse::Done
+        }
+        ServiceRequest::CreateItem {
+            folder,
+            label,
+            attributes,
+            secret,
+            replace,
+        } => {
+            let existing = match replace {
+                true => replaced(repository, folder, attributes),
+                false => None,
+            };
+            if let Some(id) = existing {
+                set_secret(repository, &id, Some(label), secret)?;
+                return Ok(ServiceResponse::Item(id));
+            }
+
+            let id = repository.add(label, "", secret.expose())?.id();
+            if !folder.is_empty() {
+                repository.move_to_folder(&id, folder)?;
+            }
+            repository.set_attributes(&id, attributes.clone())?;
+            log::info!("Secret Service: added {}", id);
+            ServiceResponse::Item(id)
+        }
+        ServiceRequest::DeleteItem { entry } => {
+            repository.delete(entry)?;
+            log::info!("Secret Service: moved {} into the trash", entry);
+            ServiceResponse::Done
+        }
+        ServiceRequest::CreateCollection { label } => {
+            let folder = normalize(label);
+            if folder.is_empty() {
+                return Ok(ServiceRefusal::Failed.into());
+            }
+            if !folders.contains(&folder) {
+                folders.push(folder.clone());
+            }
+            ServiceResponse::Collection(folder)
+        }
+    })
+}
+
+/// The **decrypted** secret of the entry, `None` if it has none or is
+/// inside the trash.
+fn secret_of(repository: &Repository, id: &str) -> Result<Option<Secret<String>>, InternalError> {
+    let entry = match repository.get(id) {
+        Some(e) if !e.is_deleted() => e,
+        _ => return Ok(None),
+    };
+    let details = entry.get_details()?;
+
+    Ok(details
+        .payload
+        .expose()
+        .secret()
+        .map(|(_, value)| Secret::new(String::from(value))))
+}
+
+/// Changes the secret, and the name if one is given. Returns `false` if the
+/// entry has no secret.
+fn set_secret(
+    repository: &mut Repository,
+    id: &str,
+    name: Option<&str>,
+    secret: &Secret<String>,
+) -> Result<bool, InternalError> {
+    let details = match repository.get(id) {
+        Some(e) => e.get_details()?,
+        None => return Err(DbError::EntryNotFound.into()),
+    };
+    let mut payload = details.payload.expose().clone();
+    let index = match payload.kind().secret_index() {
+        Some(i) => i,
+        None => return Ok(false),
+    };
+    *payload.values_mut()[index] = secret.expose().clone();
+    repository.update_payload(id, name.unwrap_or(&details.name), &payload)?;
+
+    Ok(true)
+}
+
+/// The entry of the folder with the same attributes, which a new item
+/// replaces.
+fn replaced(
+    repository: &Repository,
+    folder: &str,
+    attributes: &BTreeMap<String, String>,
+) -> Option<String> {
+    if attributes.is_empty() {
+        return None;
+    }
+
+    repository
+        .list()
+        .into_iter()
+        .find(|e| {
+            e.folder() == folder
+                && e.kind().secret_index().is_some()
+                && e.attributes() == attributes
+        })
+        .map(|e| e.id())
+}
+
+/// The service while it runs. It leaves the bus when it is dropped.
+pub struct SecretService {
+    connection: Connection,
+    shared: Arc<Shared>,
+    /// The paths of the collections and items which are on the bus.
+    published: HashSet<String>,
+    /// Stops watching for apps which leave the bus once it is closed.
+    stop: async_channel::Sender<()>,
+}
+impl SecretService {
+    /// Connects to the session bus, or the bus at the address, and takes the
+    /// name of the service. Every request is passed on to `calls`, and `wake`
+    /// is called after each, so the app can answer it right away.
+    pub fn start(
+        address: Option<&str>,
+        calls: Sender<ServiceCall>,
+        wake: impl Fn() + Send + Sync + 'static,
+    ) -> Result<Self, InternalError> {
+        let builder = match address {
+            Some(address) => ConnectionBuilder::address(address),
+            None => ConnectionBuilder::session(),
+        };
+        let connection = match builder.and_then(|b| b.build()) {
+            Ok(c) => c,
+            Err(err) => return Err(BusError::Connect(Box::new(err)).into()),
+        };
+
+        let shared = Arc::new(Shared {
+            catalog: RwLock::new(Catalog::default()),
+            calls,
+            wake: Box::new(wake),
+            sessions: Mutex::new(HashMap::new()),
+        });
+        let service = Service {
+            shared: shared.clone(),
+        };
+        if let Err(err) = connection.object_server().at(SERVICE_PATH, service) {
+            return Err(BusError::Publish(Box::new(err)).into());
+        }
+        let alias = Collection {
+            shared: shared.clone(),
+            id: String::from(VAULT_COLLECTION),
+        };
+        let alias_path = format!("{}/aliases/{}", SERVICE_PATH, DEFAULT_ALIAS);
+        if let Err(err) = connection.object_server().at(alias_path, alias) {
+            return Err(BusError::Publish(Box::new(err)).into());
+        }
+
+        let flags = RequestNameFlags::DoNotQueue.into();
+        match connection.request_name_with_flags(SERVICE_NAME, flags) {
+            Ok(RequestNameReply::PrimaryOwner | RequestNameReply::AlreadyOwner) => {}
+            Ok(_) => return Err(BusError::NameTaken.into()),
+            Err(err) => return Err(BusError::Connect(Box::new(err)).into()),
+        }
+        log::info!("Secret Service started");
+
+        let (stop, stopped) = async_channel::bounded(1);
+        let watcher = (connection.inner().clone(), shared.clone());
+        thread::spawn(move || {
+            async_io::block_on(objects::watch_clients(watcher.0, watcher.1, stopped))
+        });
+
+        Ok(Self {
+            connection,
+            shared,
+            published: HashSet::new(),
+            stop,
+        })
+    }
+
+    /// Shows the collections and items of the catalog on the bus. Objects
+    /// which are new are added and the ones which are gone removed, which
+    /// apps are told about.
+    pub fn publish(&mut self, catalog: Catalog) -> Result<(), InternalError> {
+        let same = match self.shared.catalog.read() {
+            Ok(current) => *current == catalog,
+            Err(_) => false,
+        };
+        if same {
+            return Ok(());
+        }
+
+        let mut paths = HashSet::<String>::new();
+        for collection in &catalog.collections {
+            paths.insert(collection.path());
+            for item in &collection.items {
+                paths.insert(collection.item_path(item));
+            }
+        }
+        if let Ok(mut current) = self.shared.catalog.write() {
+            *current = catalog.clone();
+        }
+
+        let server = self.connection.object_server();
+        let gone = self
+            .published
+            .difference(&paths)
+            .cloned()
+            .collect::<Vec<String>>();
+        for path in gone {
+            let removed = match catalog_object(&path) {
+                CatalogObject::Collection => server.remove::<Collection, _>(path.as_str()),
+                CatalogObject::Item(_) => server.remove::<Item, _>(path.as_str()),
+            };
+            if let Err(err) = removed {
+                return Err(BusError::Publish(Box::new(err)).into());
+            }
+            self.published.remove(&path);
+            self.announce(&path, false);
+        }
+
+        for collection in &catalog.collections {
+            let mut added = Vec::<(String, zbus::Result<bool>)>::new();
+            let object = Collection {
+                shared: self.shared.clone(),
+                id: collection.id.clone(),
+            };
+            added.push((collection.path(), server.at(collection.path(), object)));
+            for item in &collection.items {
+                let object = Item {
+                    shared: self.shared.clone(),
+                    collection: collection.id.clone(),
+                    entry: item.entry.clone(),
+                };
+                let path = collection.item_path(item);
+                added.push((path.clone(), server.at(path, object)));
+            }
+
+            for (path, result) in added {
+                match result {
+                    Ok(true) => {
+                        self.published.insert(path.clone());
+                        self.announce(&path, true);
+                    }
+                    Ok(false) => {}
+                    Err(err) => return Err(BusError::Publish(Box::new(err)).into()),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sends the signal telling that the collection or item was created or
+    /// deleted. Apps which miss it only miss an update.
+    fn announce(&self, path: &str, created: bool) {
+        let object = match ObjectPath::try_from(path) {
+            Ok(p) => p,
+            Err(_) => return,
+        };
+        let connection = self.connection.inner();
+        let sent = match catalog_object(path) {
+            CatalogObject::Collection => {
+                SignalContext::new(connection, SERVICE_PATH).and_then(|ctx| match created {
+                    true => zbus::block_on(Service::collection_created(&ctx, object)),
+                    false => zbus::block_on(Service::collection_deleted(&ctx, object)),
+                })
+            }
+            CatalogObject::Item(collection) => SignalContext::new(connection, collection.as_str())
+                .and_then(|ctx| match created {
+                    true => zbus::block_on(Collection::item_created(&ctx, object)),
+                    false => zbus::block_on(Collection::item_deleted(&ctx, object)),
+                }),
+        };
+        if let Err(err) = sent {
+            log::warn!("Secret Service: failed to announce {}: {:?}", path, err);
+        }
+    }
+}
+impl Drop for SecretService {
+    fn drop(&mut self) {
+        self.stop.close();
+        if let Err(err) = self.connection.release_name(SERVICE_NAME) {
+            log::warn!("Secret Service: failed to release the name: {:?}", err);
+        }
+        log::info!("Secret Service stopped");
+    }
+}
+
+enum CatalogObject {
+    Collection,
+    /// The path of its collection.
+    Item(String),
+}
+
+/// Collections are directly below `collection`, items below them.
+fn catalog_object(path: &str) -> CatalogObject {
+    let rest = path
+        .strip_prefix(SERVICE_PATH)
+        .and_then(|p| p.strip_prefix("/collection/"))
+        .unwrap_or_default();
+    match rest.split_once('/') {
+        Some((collection, _)) => {
+            CatalogObject::Item(format!("{}/collection/{}", SERVICE_PATH, collection))
+        }
+        None => CatalogObject::Collection,
+    }
+}
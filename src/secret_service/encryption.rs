@@ -0,0 +1,80 @@
+//! The encrypted sessions, which libsecret asks for before plain ones. The
+//! app and the service agree on a key with Diffie-Hellman in the 1024-bit
+//! group of RFC 2409, derive an AES-128 key from it with HKDF-SHA256 and
+//! encrypt every secret with it in CBC mode.
+
+use aes::cipher::{block_padding::Pkcs7, BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use aes_gcm::aead::{rand_core::RngCore, OsRng};
+use hkdf::Hkdf;
+use rsa::BigUint;
+use sha2::Sha256;
+
+use crate::helpers::Secret;
+
+pub(super) const DH_AES: &str = "dh-ietf1024-sha256-aes128-cbc-pkcs7";
+/// The Second Oakley Group of RFC 2409, whose generator is 2.
+const PRIME: &str = "\
+    FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74020BBEA63B139B22514A0879\
+    8E3404DDEF9519B3CD3A431B302B0A6DF25F14374FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B\
+    0BFF5CB6F406B7EDEE386BFB5A899FA5AE9F24117C4B1FE649286651ECE65381FFFFFFFFFFFFFFFF";
+const PRIME_LENGTH: usize = 128;
+const GENERATOR: u32 = 2;
+const IV_LENGTH: usize = 16;
+
+type Encryptor = cbc::Encryptor<aes::Aes128>;
+type Decryptor = cbc::Decryptor<aes::Aes128>;
+
+/// The key of an encrypted session.
+#[derive(Debug, Clone)]
+pub(super) struct SessionKey(Secret<[u8; 16]>);
+impl SessionKey {
+    /// Agrees on a key with the public key the app sent. Returns the key
+    /// together with the public key of the service, `None` if the one of the
+    /// app is not valid.
+    pub fn agree(app_key: &[u8]) -> Option<(Self, Vec<u8>)> {
+        let prime = BigUint::parse_bytes(PRIME.as_bytes(), 16)?;
+        let app_key = BigUint::from_bytes_be(app_key);
+        let one = BigUint::from(1u32);
+        if app_key <= one || app_key >= &prime - &one {
+            return None;
+        }
+
+        let mut private = [0u8; PRIME_LENGTH];
+        OsRng.fill_bytes(&mut private);
+        let private = BigUint::from_bytes_be(&private);
+        let public = BigUint::from(GENERATOR).modpow(&private, &prime);
+        let shared = app_key.modpow(&private, &prime).to_bytes_be();
+
+        // libsecret pads the shared secret to the length of the prime
+        let mut input = vec![0u8; PRIME_LENGTH - shared.len()];
+        input.extend_from_slice(&shared);
+        let mut key = [0u8; 16];
+        Hkdf::<Sha256>::new(None, &input)
+            .expand(&[], &mut key)
+            .ok()?;
+
+        Some((Self(Secret::new(key)), public.to_bytes_be()))
+    }
+
+    /// Encrypts the value with a new IV. Returns the IV, which is sent as the
+    /// parameters of the secret, together with the encrypted value.
+    pub fn encrypt(&self, value: &[u8]) -> (Vec<u8>, Vec<u8>) {
+        let mut iv = [0u8; IV_LENGTH];
+        OsRng.fill_bytes(&mut iv);
+        let encrypted = Encryptor::new(self.0.expose().into(), &iv.into())
+            .encrypt_padded_vec_mut::<Pkcs7>(value);
+
+        (iv.to_vec(), encrypted)
+    }
+
+    /// `None` if the value was not encrypted with the key.
+    pub fn decrypt(&self, iv: &[u8], value: &[u8]) -> Option<Vec<u8>> {
+        if iv.len() != IV_LENGTH {
+            return None;
+        }
+
+        Decryptor::new(self.0.expose().into(), iv.into())
+            .decrypt_padded_vec_mut::<Pkcs7>(value)
+            .ok()
+    }
+}
@@ -0,0 +1,835 @@
+//! The objects of the Secret Service on the bus. They only read the catalog
+//! the app published and pass everything else on to the app.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt, fs,
+    sync::{mpsc::Sender, Arc, Mutex, RwLock},
+    time::Duration,
+};
+
+use async_io::Timer;
+use futures_lite::{future, StreamExt};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use zbus::{
+    dbus_interface, fdo,
+    names::{BusName, ErrorName},
+    zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Type, Value},
+    Connection, DBusError, Message, MessageBuilder, MessageHeader, ObjectServer, SignalContext,
+};
+
+use crate::helpers::Secret;
+
+use super::{
+    encryption::{SessionKey, DH_AES},
+    Catalog, ServiceCall, ServiceClient, ServiceRefusal, ServiceRequest, ServiceResponse,
+    DEFAULT_ALIAS, SERVICE_PATH, VAULT_COLLECTION,
+};
+
+/// The algorithm of the sessions for apps which can not encrypt. The bus only
+/// connects processes of the same user, so secrets are sent as they are.
+const PLAIN: &str = "plain";
+const CONTENT_TYPE: &str = "text/plain";
+const LABEL_PROPERTY: &str = "org.freedesktop.Secret.Item.Label";
+const ATTRIBUTES_PROPERTY: &str = "org.freedesktop.Secret.Item.Attributes";
+const COLLECTION_LABEL_PROPERTY: &str = "org.freedesktop.Secret.Collection.Label";
+const UNKNOWN_CLIENT: &str = "unknown";
+/// How long the user has to allow a request.
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// What all objects share with the service.
+pub(super) struct Shared {
+    pub catalog: RwLock<Catalog>,
+    pub calls: Sender<ServiceCall>,
+    pub wake: Box<dyn Fn() + Send + Sync>,
+    /// The open sessions by their paths.
+    pub sessions: Mutex<HashMap<String, OpenSession>>,
+}
+impl Shared {
+    /// Passes the request on to the app and waits for its answer. A request
+    /// the user did not answer in time is denied, and the app drops it once
+    /// it sees nobody waits anymore.
+    async fn ask(&self, client: ServiceClient, request: ServiceRequest) -> ServiceResponse {
+        let (reply, answer) = async_channel::bounded(1);
+        let asked = self.calls.send(ServiceCall {
+            client,
+            request,
+            reply,
+        });
+        if asked.is_err() {
+            return ServiceRefusal::Locked.into();
+        }
+        (self.wake)();
+
+        let timeout = async {
+            Timer::after(CONFIRM_TIMEOUT).await;
+            None
+        };
+        match future::or(async { Some(answer.recv().await) }, timeout).await {
+            Some(Ok(response)) => response,
+            Some(Err(_)) => ServiceRefusal::Locked.into(),
+            None => {
+                drop(answer);
+                (self.wake)();
+                ServiceRefusal::Denied.into()
+            }
+        }
+    }
+
+    fn catalog(&self) -> Catalog {
+        match self.catalog.read() {
+            Ok(catalog) => catalog.clone(),
+            Err(_) => Catalog::default(),
+        }
+    }
+
+    /// Only the app which opened the session may send secrets within it.
+    /// Returns the key of the session, `None` if it is a plain one.
+    fn check_session(
+        &self,
+        session: &ObjectPath<'_>,
+        header: &MessageHeader<'_>,
+    ) -> Result<Option<SessionKey>, SecretError> {
+        let sender = sender_of(header);
+        let open = match (&sender, self.sessions.lock()) {
+            (Some(sender), Ok(sessions)) => sessions
+                .get(session.as_str())
+                .filter(|s| s.owner == *sender)
+                .map(|s| s.key.clone()),
+            _ => None,
+        };
+        open.ok_or(SecretError::NoSession(format!("No session {}", session)))
+    }
+
+    /// Forgets the sessions the app opened and returns their paths.
+    fn close_sessions_of(&self, sender: &str) -> Vec<String> {
+        let mut sessions = match self.sessions.lock() {
+            Ok(s) => s,
+            Err(_) => return Vec::new(),
+        };
+        let paths = sessions
+            .iter()
+            .filter(|(_, session)| session.owner == sender)
+            .map(|(path, _)| path.clone())
+            .collect::<Vec<String>>();
+        for path in &paths {
+            sessions.remove(path);
+        }
+
+        paths
+    }
+
+    /// The items of the collections which have all of the attributes.
+    fn search(
+        &self,
+        collection: Option<&str>,
+        attributes: &HashMap<String, String>,
+    ) -> Vec<OwnedObjectPath> {
+        let catalog = self.catalog();
+        let mut found = Vec::<OwnedObjectPath>::new();
+        for info in &catalog.collections {
+            if collection.is_some_and(|c| c != info.id) {
+                continue;
+            }
+            for item in &info.items {
+                let matches = attributes
+                    .iter()
+                    .all(|(key, value)| item.attributes.get(key) == Some(value));
+                if matches {
+                    found.extend(object_path(&info.item_path(item)));
+                }
+            }
+        }
+
+        found
+    }
+}
+
+/// A session an app opened.
+pub(super) struct OpenSession {
+    /// The unique name of the app.
+    pub owner: String,
+    /// `None` if the secrets are sent as they are.
+    pub key: Option<SessionKey>,
+}
+
+/// A secret as it is sent over the bus.
+#[derive(Deserialize, Serialize, Type, Clone, PartialEq)]
+pub(super) struct SecretValue {
+    session: OwnedObjectPath,
+    parameters: Vec<u8>,
+    value: Vec<u8>,
+    content_type: String,
+}
+impl SecretValue {
+    /// Encrypts the secret with the key of the session, if it has one.
+    fn new(session: OwnedObjectPath, secret: &Secret<String>, key: Option<&SessionKey>) -> Self {
+        let plain = secret.expose().as_bytes();
+        let (parameters, value) = match key {
+            Some(key) => key.encrypt(plain),
+            None => (Vec::new(), plain.to_vec()),
+        };
+
+        Self {
+            session,
+            parameters,
+            value,
+            content_type: String::from(CONTENT_TYPE),
+        }
+    }
+
+    /// The **decrypted** value, which the vault only stores as text.
+    fn text(&self, key: Option<&SessionKey>) -> Result<Secret<String>, SecretError> {
+        let plain = match key {
+            Some(key) => key.decrypt(&self.parameters, &self.value),
+            None => Some(self.value.clone()),
+        };
+        match plain.map(String::from_utf8) {
+            Some(Ok(text)) => Ok(Secret::new(text)),
+            Some(Err(_)) => {
+                Err(fdo::Error::InvalidArgs(String::from("The secret is no text")).into())
+            }
+            None => Err(fdo::Error::InvalidArgs(String::from(
+                "The secret was not encrypted with the key of the session",
+            ))
+            .into()),
+        }
+    }
+}
+impl fmt::Debug for SecretValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "SecretValue {{ session: {}, .. }}",
+            self.session.as_str()
+        )
+    }
+}
+
+/// The errors of the Secret Service, together with the usual ones of D-Bus.
+/// The usual ones keep their names, which libsecret tells apart.
+#[derive(Debug)]
+pub(super) enum SecretError {
+    Fdo(fdo::Error),
+    IsLocked(String),
+    NoSession(String),
+    NoSuchObject(String),
+}
+impl DBusError for SecretError {
+    fn create_reply(&self, call: &MessageHeader<'_>) -> zbus::Result<Message> {
+        match self {
+            SecretError::Fdo(err) => err.create_reply(call),
+            other => MessageBuilder::error(call, other.name())?
+                .build(&(other.description().unwrap_or_default(),)),
+        }
+    }
+
+    fn name(&self) -> ErrorName<'_> {
+        let name = match self {
+            SecretError::Fdo(err) => return err.name(),
+            SecretError::IsLocked(_) => "org.freedesktop.Secret.Error.IsLocked",
+            SecretError::NoSession(_) => "org.freedesktop.Secret.Error.NoSession",
+            SecretError::NoSuchObject(_) => "org.freedesktop.Secret.Error.NoSuchObject",
+        };
+        ErrorName::from_static_str_unchecked(name)
+    }
+
+    fn description(&self) -> Option<&str> {
+        match self {
+            SecretError::Fdo(err) => err.description(),
+            SecretError::IsLocked(message)
+            | SecretError::NoSession(message)
+            | SecretError::NoSuchObject(message) => Some(message),
+        }
+    }
+}
+impl From<fdo::Error> for SecretError {
+    fn from(err: fdo::Error) -> Self {
+        SecretError::Fdo(err)
+    }
+}
+impl From<zbus::Error> for SecretError {
+    fn from(err: zbus::Error) -> Self {
+        SecretError::Fdo(err.into())
+    }
+}
+impl From<ServiceRefusal> for SecretError {
+    fn from(refusal: ServiceRefusal) -> Self {
+        match refusal {
+            ServiceRefusal::Locked => SecretError::IsLocked(String::from("The vault is locked")),
+            ServiceRefusal::Denied => {
+                fdo::Error::AccessDenied(String::from("The user did not allow it")).into()
+            }
+            ServiceRefusal::NoSuchObject => {
+                SecretError::NoSuchObject(String::from("The entry does not exist"))
+            }
+            ServiceRefusal::Failed => fdo::Error::Failed(String::from("The vault failed")).into(),
+        }
+    }
+}
+
+/// The answer of the app, where anything besides the expected answer is
+/// refused.
+fn expect<T>(
+    response: ServiceResponse,
+    wanted: impl FnOnce(ServiceResponse) -> Option<T>,
+) -> Result<T, SecretError> {
+    match response {
+        ServiceResponse::Refused(refusal) => Err(refusal.into()),
+        other => wanted(other).ok_or_else(|| ServiceRefusal::Failed.into()),
+    }
+}
+
+/// The unique name on the bus of the app which sent the message.
+fn sender_of(header: &MessageHeader<'_>) -> Option<String> {
+    match header.sender() {
+        Ok(Some(sender)) => Some(sender.to_string()),
+        _ => None,
+    }
+}
+
+/// The app which sent the message. It is told apart by its unique name on
+/// the bus, the name of its process is only what the user sees when asked.
+async fn client_of(connection: &Connection, header: &MessageHeader<'_>) -> ServiceClient {
+    let sender = match sender_of(header) {
+        Some(sender) => sender,
+        None => return unknown_client(),
+    };
+    let pid = match (
+        fdo::DBusProxy::new(connection).await,
+        BusName::try_from(sender.as_str()),
+    ) {
+        (Ok(proxy), Ok(name)) => proxy.get_connection_unix_process_id(name).await.ok(),
+        _ => None,
+    };
+    let label = pid
+        .and_then(|pid| fs::read_to_string(format!("/proc/{}/comm", pid)).ok())
+        .map(|name| String::from(name.trim()))
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| sender.clone());
+
+    ServiceClient {
+        sender: Some(sender),
+        label,
+    }
+}
+
+/// Closes the sessions of every app which leaves the bus, until `stop` is
+/// closed.
+pub(super) async fn watch_clients(
+    connection: Connection,
+    shared: Arc<Shared>,
+    stop: async_channel::Receiver<()>,
+) {
+    let watch = async {
+        let changes = match fdo::DBusProxy::new(&connection).await {
+            Ok(proxy) => proxy.receive_name_owner_changed().await,
+            Err(err) => Err(err),
+        };
+        let mut changes = match changes {
+            Ok(c) => c,
+            Err(err) => {
+                log::error!("Secret Service: failed to watch the apps: {:?}", err);
+                return;
+            }
+        };
+        while let Some(change) = changes.next().await {
+            let gone = match change.args() {
+                Ok(args) if args.new_owner().is_none() => args.name().to_string(),
+                _ => continue,
+            };
+            for path in shared.close_sessions_of(&gone) {
+                let server = connection.object_server();
+                if let Err(err) = server.remove::<Session, _>(path.as_str()).await {
+                    log::warn!("Secret Service: failed to close {}: {:?}", path, err);
+                }
+            }
+        }
+    };
+
+    future::or(watch, async {
+        let _ = stop.recv().await;
+    })
+    .await;
+}
+
+/// Setting properties does not tell who set them.
+fn unknown_client() -> ServiceClient {
+    ServiceClient {
+        sender: None,
+        label: String::from(UNKNOWN_CLIENT),
+    }
+}
+
+fn object_path(path: &str) -> Option<OwnedObjectPath> {
+    OwnedObjectPath::try_from(path).ok()
+}
+
+/// The path which stands for "no prompt" and "no object".
+fn no_object() -> OwnedObjectPath {
+    OwnedObjectPath::from(ObjectPath::from_static_str_unchecked("/"))
+}
+
+fn collection_path(id: &str) -> OwnedObjectPath {
+    object_path(&format!("{}/collection/{}", SERVICE_PATH, id)).unwrap_or_else(no_object)
+}
+
+fn string_property(properties: &HashMap<String, OwnedValue>, name: &str) -> String {
+    properties
+        .get(name)
+        .and_then(|v| String::try_from(v.clone()).ok())
+        .unwrap_or_default()
+}
+
+pub(super) struct Service {
+    pub shared: Arc<Shared>,
+}
+
+#[dbus_interface(name = "org.freedesktop.Secret.Service")]
+impl Service {
+    async fn open_session(
+        &self,
+        algorithm: &str,
+        input: Value<'_>,
+        #[zbus(header)] header: MessageHeader<'_>,
+        #[zbus(object_server)] server: &ObjectServer,
+    ) -> Result<(OwnedValue, OwnedObjectPath), SecretError> {
+        let (key, output) = match algorithm {
+            PLAIN => (None, Value::from("")),
+            DH_AES => {
+                let agreed = Vec::<u8>::try_from(input)
+                    .ok()
+                    .and_then(|app_key| SessionKey::agree(&app_key));
+                match agreed {
+                    Some((key, public_key)) => (Some(key), Value::from(public_key)),
+                    None => {
+                        let message = String::from("The public key is not valid");
+                        return Err(fdo::Error::InvalidArgs(message).into());
+                    }
+                }
+            }
+            _ => {
+                let message = format!("{} is not supported", algorithm);
+                return Err(fdo::Error::NotSupported(message).into());
+            }
+        };
+        let sender = match sender_of(&header) {
+            Some(s) => s,
+            None => return Err(fdo::Error::AccessDenied(String::from("The app is unknown")).into()),
+        };
+
+        let path = format!("{}/session/{}", SERVICE_PATH, Uuid::new_v4().simple());
+        let session = Session {
+            shared: self.shared.clone(),
+            path: path.clone(),
+        };
+        server.at(path.as_str(), session).await?;
+        if let Ok(mut sessions) = self.shared.sessions.lock() {
+            sessions.insert(path.clone(), OpenSession { owner: sender, key });
+        }
+
+        Ok((output.into(), object_path(&path).unwrap_or_else(no_object)))
+    }
+
+    async fn create_collection(
+        &self,
+        properties: HashMap<String, OwnedValue>,
+        alias: &str,
+        #[zbus(header)] header: MessageHeader<'_>,
+        #[zbus(connection)] connection: &Connection,
+    ) -> Result<(OwnedObjectPath, OwnedObjectPath), SecretError> {
+        if alias == DEFAULT_ALIAS {
+            return Ok((collection_path(VAULT_COLLECTION), no_object()));
+        }
+
+        let label = string_property(&properties, COLLECTION_LABEL_PROPERTY);
+        let client = client_of(connection, &header).await;
+        let response = self
+            .shared
+            .ask(client, ServiceRequest::CreateCollection { label })
+            .await;
+        let folder = expect(response, |r| match r {
+            ServiceResponse::Collection(folder) => Some(folder),
+            _ => None,
+        })?;
+
+        Ok((collection_path(&super::collection_id(&folder)), no_object()))
+    }
+
+    /// Every item is unlocked while the vault is.
+    async fn search_items(
+        &self,
+        attributes: HashMap<String, String>,
+    ) -> (Vec<OwnedObjectPath>, Vec<OwnedObjectPath>) {
+        (self.shared.search(None, &attributes), Vec::new())
+    }
+
+    async fn unlock(
+        &self,
+        objects: Vec<OwnedObjectPath>,
+    ) -> (Vec<OwnedObjectPath>, OwnedObjectPath) {
+        (objects, no_object())
+    }
+
+    /// The vault is only locked inside the app.
+    async fn lock(
+        &self,
+        _objects: Vec<OwnedObjectPath>,
+    ) -> (Vec<OwnedObjectPath>, OwnedObjectPath) {
+        (Vec::new(), no_object())
+    }
+
+    /// The secrets the user allowed, the others are left out.
+    async fn get_secrets(
+        &self,
+        items: Vec<OwnedObjectPath>,
+        session: ObjectPath<'_>,
+        #[zbus(header)] header: MessageHeader<'_>,
+        #[zbus(connection)] connection: &Connection,
+    ) -> Result<HashMap<OwnedObjectPath, SecretValue>, SecretError> {
+        let key = self.shared.check_session(&session, &header)?;
+        let client = client_of(connection, &header).await;
+
+        let mut secrets = HashMap::<OwnedObjectPath, SecretValue>::new();
+        for item in items {
+            let entry = match self.shared.catalog().item(item.as_str()) {
+                Some((_, info)) => info.entry.clone(),
+                None => continue,
+            };
+            let response = self
+                .shared
+                .ask(client.clone(), ServiceRequest::GetSecret { entry })
+                .await;
+            match response {
+                ServiceResponse::Secret(secret) => {
+                    let value = SecretValue::new(session.clone().into(), &secret, key.as_ref());
+                    secrets.insert(item, value);
+                }
+                ServiceResponse::Refused(ServiceRefusal::Locked) => {
+                    return Err(ServiceRefusal::Locked.into())
+                }
+                _ => {}
+            }
+        }
+
+        Ok(secrets)
+    }
+
+    async fn read_alias(&self, name: &str) -> OwnedObjectPath {
+        match name {
+            DEFAULT_ALIAS => collection_path(VAULT_COLLECTION),
+            _ => no_object(),
+        }
+    }
+
+    async fn set_alias(&self, _name: &str, _collection: ObjectPath<'_>) -> Result<(), SecretError> {
+        Err(fdo::Error::NotSupported(String::from("The default collection is the vault")).into())
+    }
+
+    #[dbus_interface(property)]
+    async fn collections(&self) -> Vec<OwnedObjectPath> {
+        self.shared
+            .catalog()
+            .collections
+            .iter()
+            .filter_map(|c| object_path(&c.path()))
+            .collect()
+    }
+
+    #[dbus_interface(signal)]
+    pub async fn collection_created(
+        ctx: &SignalContext<'_>,
+        collection: ObjectPath<'_>,
+    ) -> zbus::Result<()>;
+
+    #[dbus_interface(signal)]
+    pub async fn collection_deleted(
+        ctx: &SignalContext<'_>,
+        collection: ObjectPath<'_>,
+    ) -> zbus::Result<()>;
+}
+
+/// A folder, or the top level, of the vault.
+pub(super) struct Collection {
+    pub shared: Arc<Shared>,
+    pub id: String,
+}
+
+#[dbus_interface(name = "org.freedesktop.Secret.Collection")]
+impl Collection {
+    /// Folders are only deleted inside the app.
+    async fn delete(&self) -> Result<OwnedObjectPath, SecretError> {
+        Err(fdo::Error::NotSupported(String::from("Folders are deleted inside the vault")).into())
+    }
+
+    async fn search_items(&self, attributes: HashMap<String, String>) -> Vec<OwnedObjectPath> {
+        self.shared.search(Some(&self.id), &attributes)
+    }
+
+    async fn create_item(
+        &self,
+        properties: HashMap<String, OwnedValue>,
+        secret: SecretValue,
+        replace: bool,
+        #[zbus(header)] header: MessageHeader<'_>,
+        #[zbus(connection)] connection: &Connection,
+    ) -> Result<(OwnedObjectPath, OwnedObjectPath), SecretError> {
+        let key = self.shared.check_session(&secret.session, &header)?;
+        let folder = match self.shared.catalog().collection(&self.id) {
+            Some(info) => info.folder.clone(),
+            None => return Err(ServiceRefusal::NoSuchObject.into()),
+        };
+        let attributes = properties
+            .get(ATTRIBUTES_PROPERTY)
+            .and_then(|v| HashMap::<String, String>::try_from(v.clone()).ok())
+            .unwrap_or_default();
+        let request = ServiceRequest::CreateItem {
+            folder,
+            label: string_property(&properties, LABEL_PROPERTY),
+            attributes: attributes.into_iter().collect(),
+            secret: secret.text(key.as_ref())?,
+            replace,
+        };
+
+        let client = client_of(connection, &header).await;
+        let response = self.shared.ask(client, request).await;
+        let entry = expect(response, |r| match r {
+            ServiceResponse::Item(entry) => Some(entry),
+            _ => None,
+        })?;
+
+        // the app published the item before it answered
+        let path = self.shared.catalog().collections.iter().find_map(|c| {
+            c.items
+                .iter()
+                .find(|i| i.entry == entry)
+                .map(|i| c.item_path(i))
+        });
+        match path.as_deref().and_then(object_path) {
+            Some(path) => Ok((path, no_object())),
+            None => Err(ServiceRefusal::NoSuchObject.into()),
+        }
+    }
+
+    #[dbus_interface(property)]
+    async fn items(&self) -> Vec<OwnedObjectPath> {
+        match self.shared.catalog().collection(&self.id) {
+            Some(info) => info
+                .items
+                .iter()
+                .filter_map(|i| object_path(&info.item_path(i)))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    #[dbus_interface(property)]
+    async fn label(&self) -> String {
+        self.shared
+            .catalog()
+            .collection(&self.id)
+            .map(|c| c.label.clone())
+            .unwrap_or_default()
+    }
+
+    #[dbus_interface(property)]
+    async fn locked(&self) -> bool {
+        false
+    }
+
+    /// When the oldest item was created.
+    #[dbus_interface(property)]
+    async fn created(&self) -> u64 {
+        let catalog = self.shared.catalog();
+        let items = catalog.collection(&self.id).map(|c| c.items.as_slice());
+        items
+            .unwrap_or_default()
+            .iter()
+            .map(|i| i.created)
+            .min()
+            .unwrap_or_default()
+    }
+
+    /// When an item was changed the last time.
+    #[dbus_interface(property)]
+    async fn modified(&self) -> u64 {
+        let catalog = self.shared.catalog();
+        let items = catalog.collection(&self.id).map(|c| c.items.as_slice());
+        items
+            .unwrap_or_default()
+            .iter()
+            .map(|i| i.modified)
+            .max()
+            .unwrap_or_default()
+    }
+
+    #[dbus_interface(signal)]
+    pub async fn item_created(ctx: &SignalContext<'_>, item: ObjectPath<'_>) -> zbus::Result<()>;
+
+    #[dbus_interface(signal)]
+    pub async fn item_deleted(ctx: &SignalContext<'_>, item: ObjectPath<'_>) -> zbus::Result<()>;
+}
+
+/// An entry of the vault.
+pub(super) struct Item {
+    pub shared: Arc<Shared>,
+    pub collection: String,
+    pub entry: String,
+}
+impl Item {
+    fn info<T>(&self, read: impl FnOnce(&super::ItemInfo) -> T) -> Option<T> {
+        let catalog = self.shared.catalog();
+        let info = catalog
+            .collection(&self.collection)?
+            .items
+            .iter()
+            .find(|i| i.entry == self.entry)?;
+
+        Some(read(info))
+    }
+
+    async fn ask(&self, client: ServiceClient, request: ServiceRequest) -> Result<(), SecretError> {
+        let response = self.shared.ask(client, request).await;
+        expect(response, |r| match r {
+            ServiceResponse::Done => Some(()),
+            _ => None,
+        })
+    }
+}
+
+#[dbus_interface(name = "org.freedesktop.Secret.Item")]
+impl Item {
+    /// Moves the entry into the trash.
+    async fn delete(
+        &self,
+        #[zbus(header)] header: MessageHeader<'_>,
+        #[zbus(connection)] connection: &Connection,
+    ) -> Result<OwnedObjectPath, SecretError> {
+        let client = client_of(connection, &header).await;
+        let request = ServiceRequest::DeleteItem {
+            entry: self.entry.clone(),
+        };
+        self.ask(client, request).await?;
+
+        Ok(no_object())
+    }
+
+    async fn get_secret(
+        &self,
+        session: ObjectPath<'_>,
+        #[zbus(header)] header: MessageHeader<'_>,
+        #[zbus(connection)] connection: &Connection,
+    ) -> Result<SecretValue, SecretError> {
+        let key = self.shared.check_session(&session, &header)?;
+        let client = client_of(connection, &header).await;
+        let request = ServiceRequest::GetSecret {
+            entry: self.entry.clone(),
+        };
+        let response = self.shared.ask(client, request).await;
+        let secret = expect(response, |r| match r {
+            ServiceResponse::Secret(secret) => Some(secret),
+            _ => None,
+        })?;
+
+        Ok(SecretValue::new(session.into(), &secret, key.as_ref()))
+    }
+
+    async fn set_secret(
+        &self,
+        secret: SecretValue,
+        #[zbus(header)] header: MessageHeader<'_>,
+        #[zbus(connection)] connection: &Connection,
+    ) -> Result<(), SecretError> {
+        let key = self.shared.check_session(&secret.session, &header)?;
+        let client = client_of(connection, &header).await;
+        let request = ServiceRequest::SetSecret {
+            entry: self.entry.clone(),
+            secret: secret.text(key.as_ref())?,
+        };
+
+        self.ask(client, request).await
+    }
+
+    #[dbus_interface(property)]
+    async fn locked(&self) -> bool {
+        false
+    }
+
+    #[dbus_interface(property)]
+    async fn attributes(&self) -> HashMap<String, String> {
+        self.info(|i| i.attributes.clone().into_iter().collect())
+            .unwrap_or_default()
+    }
+
+    #[dbus_interface(property)]
+    async fn set_attributes(&self, attributes: HashMap<String, String>) -> zbus::Result<()> {
+        let request = ServiceRequest::SetAttributes {
+            entry: self.entry.clone(),
+            attributes: attributes.into_iter().collect::<BTreeMap<String, String>>(),
+        };
+        self.ask(unknown_client(), request)
+            .await
+            .map_err(|_| refused("The attributes were not stored"))
+    }
+
+    #[dbus_interface(property)]
+    async fn label(&self) -> String {
+        self.info(|i| i.label.clone()).unwrap_or_default()
+    }
+
+    #[dbus_interface(property)]
+    async fn set_label(&self, label: String) -> zbus::Result<()> {
+        let request = ServiceRequest::SetLabel {
+            entry: self.entry.clone(),
+            label,
+        };
+        self.ask(unknown_client(), request)
+            .await
+            .map_err(|_| refused("The label was not stored"))
+    }
+
+    #[dbus_interface(property)]
+    async fn created(&self) -> u64 {
+        self.info(|i| i.created).unwrap_or_default()
+    }
+
+    #[dbus_interface(property)]
+    async fn modified(&self) -> u64 {
+        self.info(|i| i.modified).unwrap_or_default()
+    }
+}
+
+/// The error of a property which was not set. Setters answer with the errors
+/// of zbus rather than the ones of the Secret Service.
+fn refused(message: &str) -> zbus::Error {
+    zbus::Error::FDO(Box::new(fdo::Error::Failed(String::from(message))))
+}
+
+/// The session secrets are sent within.
+pub(super) struct Session {
+    shared: Arc<Shared>,
+    path: String,
+}
+
+#[dbus_interface(name = "org.freedesktop.Secret.Session")]
+impl Session {
+    async fn close(
+        &self,
+        #[zbus(header)] header: MessageHeader<'_>,
+        #[zbus(object_server)] server: &ObjectServer,
+    ) -> fdo::Result<()> {
+        if let Ok(mut sessions) = self.shared.sessions.lock() {
+            let owner = sessions.get(&self.path).map(|s| &s.owner);
+            if owner != sender_of(&header).as_ref() {
+                return Err(fdo::Error::AccessDenied(String::from(
+                    "The session belongs to another app",
+                )));
+            }
+            sessions.remove(&self.path);
+        }
+        server.remove::<Session, _>(self.path.as_str()).await?;
+
+        Ok(())
+    }
+}
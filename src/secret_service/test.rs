@@ -0,0 +1,255 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{
+        data_base::{
+            kind::{EntryKind, Payload},
+            repository::Repository,
+            store::MemoryStore,
+        },
+        helpers::Secret,
+        secret_service::{
+            apply, collection_id, Access, Catalog, ServiceRefusal, ServiceRequest, ServiceResponse,
+            SERVICE_PATH, VAULT_COLLECTION,
+        },
+    };
+
+    fn attributes(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (String::from(*k), String::from(*v)))
+            .collect()
+    }
+
+    fn create_item(folder: &str, secret: &str, replace: bool) -> ServiceRequest {
+        ServiceRequest::CreateItem {
+            folder: String::from(folder),
+            label: String::from("Password for mail"),
+            attributes: attributes(&[("service", "mail"), ("username", "anna")]),
+            secret: Secret::new(String::from(secret)),
+            replace,
+        }
+    }
+
+    fn added(response: ServiceResponse) -> String {
+        match response {
+            ServiceResponse::Item(id) => id,
+            other => panic!("\n!!! No item was added: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn folders_are_collections() {
+        let mut repository = Repository::open(Box::new(MemoryStore::new())).unwrap();
+        let login = repository.add("mail", "anna", "hunter2").unwrap().id();
+        let note = repository
+            .add_payload(
+                "recovery codes",
+                &Payload::Note {
+                    text: String::from("1234"),
+                },
+            )
+            .unwrap()
+            .id();
+        repository.move_to_folder(&note, "work").unwrap();
+        let identity = repository
+            .add_payload("me", &Payload::empty(EntryKind::Identity))
+            .unwrap()
+            .id();
+        let folders = vec![String::from("work")];
+
+        let catalog = Catalog::build(&repository, &folders);
+        let vault = catalog.collection(VAULT_COLLECTION).unwrap();
+        assert_eq!(
+            vault
+                .items
+                .iter()
+                .map(|i| i.entry.clone())
+                .collect::<Vec<String>>(),
+            vec![login.clone()],
+            "\n!!! The top level holds other items, entries without a secret must be left out",
+        );
+        let work = catalog.collection(&collection_id("work")).unwrap();
+        assert_eq!(work.label, "work", "\n!!! The folder is labelled otherwise");
+        assert_eq!(
+            work.items.len(),
+            1,
+            "\n!!! The note is not inside its folder"
+        );
+        assert!(
+            !catalog
+                .collections
+                .iter()
+                .any(|c| c.items.iter().any(|i| i.entry == identity)),
+            "\n!!! An identity has no secret to send",
+        );
+
+        let path = vault.item_path(&vault.items[0]);
+        assert!(
+            path.starts_with(SERVICE_PATH) && !path.contains('-'),
+            "\n!!! {} is not a valid object path of the service",
+            path,
+        );
+        assert_eq!(
+            catalog.item(&path).map(|(_, i)| i.entry.clone()),
+            Some(login),
+            "\n!!! The item is not found by its path",
+        );
+    }
+
+    #[test]
+    fn items_are_created_and_replaced() {
+        let mut repository = Repository::open(Box::new(MemoryStore::new())).unwrap();
+        let mut folders = Vec::<String>::new();
+
+        let request = create_item("", "first", true);
+        assert_eq!(
+            request.access(&repository),
+            Access::Add,
+            "\n!!! Adding an entry must be allowed first",
+        );
+        let id = added(apply(&mut repository, &mut folders, &request));
+        assert_eq!(
+            repository.get(&id).unwrap().attributes(),
+            &attributes(&[("service", "mail"), ("username", "anna")]),
+            "\n!!! The attributes were not stored",
+        );
+
+        let request = create_item("", "second", true);
+        assert_eq!(
+            request.access(&repository),
+            Access::Change(id.clone()),
+            "\n!!! Replacing the secret of an entry must be allowed first",
+        );
+        assert_eq!(
+            added(apply(&mut repository, &mut folders, &request)),
+            id,
+            "\n!!! The entry with the same attributes was not replaced",
+        );
+        assert_eq!(
+            apply(
+                &mut repository,
+                &mut folders,
+                &ServiceRequest::GetSecret { entry: id.clone() }
+            ),
+            ServiceResponse::Secret(Secret::new(String::from("second"))),
+            "\n!!! The secret was not replaced",
+        );
+
+        let other = added(apply(
+            &mut repository,
+            &mut folders,
+            &create_item("", "third", false),
+        ));
+        assert_ne!(
+            other, id,
+            "\n!!! An entry was replaced without being asked to"
+        );
+    }
+
+    #[test]
+    fn secrets_are_changed_and_deleted() {
+        let mut repository = Repository::open(Box::new(MemoryStore::new())).unwrap();
+        let mut folders = Vec::<String>::new();
+        let id = repository.add("mail", "anna", "hunter2").unwrap().id();
+
+        let request = ServiceRequest::SetSecret {
+            entry: id.clone(),
+            secret: Secret::new(String::from("correct horse")),
+        };
+        assert_eq!(
+            apply(&mut repository, &mut folders, &request),
+            ServiceResponse::Done,
+        );
+        let request = ServiceRequest::SetLabel {
+            entry: id.clone(),
+            label: String::from("webmail"),
+        };
+        assert_eq!(
+            request.access(&repository),
+            Access::Change(id.clone()),
+            "\n!!! Renaming an entry must be allowed first",
+        );
+        let request = ServiceRequest::SetAttributes {
+            entry: id.clone(),
+            attributes: attributes(&[("service", "mail")]),
+        };
+        assert_eq!(
+            request.access(&repository),
+            Access::Change(id.clone()),
+            "\n!!! Changing what an entry is found by must be allowed first",
+        );
+
+        let details = repository.get(&id).unwrap().get_details().unwrap();
+        assert_eq!(
+            details.password.expose(),
+            "correct horse",
+            "\n!!! The password was not changed",
+        );
+        assert_eq!(
+            details.user_name.expose(),
+            "anna",
+            "\n!!! The user name must be kept",
+        );
+
+        let request = ServiceRequest::DeleteItem { entry: id.clone() };
+        assert_eq!(request.access(&repository), Access::Change(id.clone()));
+        apply(&mut repository, &mut folders, &request);
+        assert_eq!(
+            apply(
+                &mut repository,
+                &mut folders,
+                &ServiceRequest::GetSecret { entry: id.clone() }
+            ),
+            ServiceRefusal::NoSuchObject.into(),
+            "\n!!! The secret of an entry inside the trash was sent",
+        );
+        assert!(
+            Catalog::build(&repository, &folders).collections[0]
+                .items
+                .is_empty(),
+            "\n!!! An entry inside the trash is still an item",
+        );
+    }
+
+    #[test]
+    fn collections_add_folders() {
+        let mut repository = Repository::open(Box::new(MemoryStore::new())).unwrap();
+        let mut folders = Vec::<String>::new();
+
+        let request = ServiceRequest::CreateCollection {
+            label: String::from("Chat apps"),
+        };
+        assert_eq!(
+            request.access(&repository),
+            Access::Add,
+            "\n!!! Adding a folder must be allowed first",
+        );
+        let folder = match apply(&mut repository, &mut folders, &request) {
+            ServiceResponse::Collection(f) => f,
+            other => panic!("\n!!! No folder was added: {:?}", other),
+        };
+        assert_eq!(
+            folders,
+            vec![folder.clone()],
+            "\n!!! The folder was not stored"
+        );
+        apply(&mut repository, &mut folders, &request);
+        assert_eq!(folders.len(), 1, "\n!!! The folder was added twice");
+
+        let id = added(apply(
+            &mut repository,
+            &mut folders,
+            &create_item(&folder, "secret", true),
+        ));
+        let catalog = Catalog::build(&repository, &folders);
+        assert_eq!(
+            catalog
+                .collection(&collection_id(&folder))
+                .map(|c| c.items.iter().any(|i| i.entry == id)),
+            Some(true),
+            "\n!!! The item was not added to the collection of the folder",
+        );
+    }
+}
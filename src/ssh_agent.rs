@@ -0,0 +1,351 @@
+//! An SSH agent serving the keys stored inside the vault.
+//!
+//! The agent listens on a Unix socket while the vault is unlocked. It does not
+//! hold any keys itself, every request is passed on to the app as an
+//! [`AgentRequest`], which answers it from the entries and asks the user
+//! before anything is signed.
+
+use std::{
+    io::{self, Write},
+    os::unix::net::UnixStream,
+    path::Path,
+    sync::{
+        atomic::AtomicBool,
+        mpsc::{self, Sender},
+    },
+    time::Duration,
+};
+
+use rsa::{
+    pkcs1v15,
+    sha2::{Sha256, Sha512},
+    signature::{SignatureEncoding, Signer},
+    BigUint, RsaPrivateKey,
+};
+use ssh_key::{private::KeypairData, Algorithm, EcdsaCurve, HashAlg, Mpint, PrivateKey};
+
+use crate::{
+    error::{AgentError, InternalError},
+    local_socket::{read_full, LocalSocket},
+};
+
+mod test;
+
+const SSH_AGENT_FAILURE: u8 = 5;
+const SSH_AGENTC_REQUEST_IDENTITIES: u8 = 11;
+const SSH_AGENT_IDENTITIES_ANSWER: u8 = 12;
+const SSH_AGENTC_SIGN_REQUEST: u8 = 13;
+const SSH_AGENT_SIGN_RESPONSE: u8 = 14;
+/// Flags of a sign request asking for an RSA signature with SHA-256 or
+/// SHA-512 instead of SHA-1.
+pub const SSH_AGENT_RSA_SHA2_256: u32 = 2;
+pub const SSH_AGENT_RSA_SHA2_512: u32 = 4;
+
+/// Larger messages are refused, no request of a client comes close to it.
+const MAX_MESSAGE_SIZE: usize = 256 * 1024;
+/// How long the app has to list the keys.
+const IDENTITIES_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long the user has to confirm a signature.
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// An ed25519, RSA or ECDSA key of an entry, ready to sign.
+#[derive(Clone)]
+pub struct AgentKey {
+    /// The public key in the wire format, which identifies the key.
+    pub blob: Vec<u8>,
+    secret: SecretKey,
+}
+
+/// The decrypted private part of a key.
+#[derive(Clone)]
+enum SecretKey {
+    /// Built from the components by hand, `ssh-key` passes the first prime
+    /// twice when it does it, which fails to sign.
+    Rsa(RsaPrivateKey),
+    /// An ed25519 or ECDSA key, which `ssh-key` signs with itself.
+    Other(PrivateKey),
+}
+
+impl AgentKey {
+    /// Parses the private key of an entry in the OpenSSH format, decrypting
+    /// it with the passphrase if it has one.
+    pub fn parse(private_key: &str, passphrase: &str) -> Result<Self, InternalError> {
+        let mut key = match PrivateKey::from_openssh(private_key.trim()) {
+            Ok(k) => k,
+            Err(err) => return Err(AgentError::KeyParse(err).into()),
+        };
+        if key.is_encrypted() {
+            key = match key.decrypt(passphrase) {
+                Ok(k) => k,
+                Err(err) => return Err(AgentError::KeyDecrypt(err).into()),
+            };
+        }
+
+        let blob = match key.public_key().to_bytes() {
+            Ok(b) => b,
+            Err(err) => return Err(AgentError::KeyParse(err).into()),
+        };
+        let secret = match (key.key_data(), key.algorithm()) {
+            (KeypairData::Rsa(keypair), _) => {
+                let n = big_uint(&keypair.public.n);
+                let e = big_uint(&keypair.public.e);
+                let d = big_uint(&keypair.private.d);
+                let primes = vec![big_uint(&keypair.private.p), big_uint(&keypair.private.q)];
+                match RsaPrivateKey::from_components(n, e, d, primes) {
+                    Ok(k) => SecretKey::Rsa(k),
+                    Err(_) => return Err(AgentError::KeyParse(ssh_key::Error::Crypto).into()),
+                }
+            }
+            (_, Algorithm::Ed25519)
+            | (
+                _,
+                Algorithm::Ecdsa {
+                    curve: EcdsaCurve::NistP256 | EcdsaCurve::NistP384 | EcdsaCurve::NistP521,
+                },
+            ) => SecretKey::Other(key),
+            _ => return Err(AgentError::UnsupportedKey.into()),
+        };
+
+        Ok(Self { blob, secret })
+    }
+
+    /// Signs the data, returning the signature in the wire format. RSA keys
+    /// sign with the hash the flags of the request ask for, `None` if they
+    /// ask for SHA-1, which is not offered.
+    pub fn sign(&self, data: &[u8], flags: u32) -> Option<Vec<u8>> {
+        let key = match &self.secret {
+            SecretKey::Rsa(key) => key,
+            SecretKey::Other(key) => {
+                let signature = match key.try_sign(data) {
+                    Ok(s) => s,
+                    Err(err) => {
+                        log::error!("Failed to sign with the key: {:?}", err);
+                        return None;
+                    }
+                };
+                return Some(encode_signature(
+                    signature.algorithm(),
+                    signature.as_bytes(),
+                ));
+            }
+        };
+
+        let (hash, signed) = if flags & SSH_AGENT_RSA_SHA2_512 != 0 {
+            let signing_key = pkcs1v15::SigningKey::<Sha512>::new(key.clone());
+            (
+                HashAlg::Sha512,
+                signing_key.try_sign(data).map(|s| s.to_vec()),
+            )
+        } else if flags & SSH_AGENT_RSA_SHA2_256 != 0 {
+            let signing_key = pkcs1v15::SigningKey::<Sha256>::new(key.clone());
+            (
+                HashAlg::Sha256,
+                signing_key.try_sign(data).map(|s| s.to_vec()),
+            )
+        } else {
+            log::info!("Refused an RSA signature with SHA-1");
+            return None;
+        };
+        match signed {
+            Ok(signature) => Some(encode_signature(
+                Algorithm::Rsa { hash: Some(hash) },
+                &signature,
+            )),
+            Err(err) => {
+                log::error!("Failed to sign with the key: {:?}", err);
+                None
+            }
+        }
+    }
+}
+
+fn big_uint(value: &Mpint) -> BigUint {
+    BigUint::from_bytes_be(value.as_positive_bytes().unwrap_or_default())
+}
+
+fn encode_signature(algorithm: Algorithm, signature: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::<u8>::new();
+    put_string(&mut encoded, algorithm.as_str().as_bytes());
+    put_string(&mut encoded, signature);
+
+    encoded
+}
+
+/// A key offered to the clients.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Identity {
+    pub blob: Vec<u8>,
+    /// Shown by e.g. `ssh-add -l`, the name of the entry.
+    pub comment: String,
+}
+
+/// What the agent needs from the app.
+#[derive(Debug)]
+pub enum AgentRequest {
+    /// All keys which can be used.
+    Identities { reply: Sender<Vec<Identity>> },
+    /// Asks the user to sign the data with the key. The reply holds the
+    /// signature in the wire format, or nothing if the user refused.
+    Sign {
+        blob: Vec<u8>,
+        data: Vec<u8>,
+        /// Which hash an RSA key signs with.
+        flags: u32,
+        reply: Sender<Option<Vec<u8>>>,
+    },
+}
+
+/// A running agent. It stops when it is dropped, which also removes the
+/// socket.
+pub struct SshAgent {
+    socket: LocalSocket,
+}
+impl SshAgent {
+    /// Starts to listen on the socket, which only the current user can
+    /// connect to. `wake` is called after every request, so the app can
+    /// handle it right away.
+    pub fn start(
+        path: impl AsRef<Path>,
+        requests: Sender<AgentRequest>,
+        wake: impl Fn() + Send + Sync + 'static,
+    ) -> Result<Self, InternalError> {
+        let socket = LocalSocket::bind(path, move |stream, stop| {
+            serve(stream, &requests, &wake, stop)
+        })?;
+        log::info!("SSH agent listening on {}", socket.path().display());
+
+        Ok(Self { socket })
+    }
+
+    pub fn path(&self) -> &Path {
+        self.socket.path()
+    }
+}
+impl Drop for SshAgent {
+    fn drop(&mut self) {
+        log::info!("SSH agent stopped");
+    }
+}
+
+/// Answers the messages of one client until it disconnects.
+fn serve(
+    mut stream: UnixStream,
+    requests: &Sender<AgentRequest>,
+    wake: &(dyn Fn() + Send + Sync),
+    stop: &AtomicBool,
+) -> io::Result<()> {
+    loop {
+        let mut length = [0u8; 4];
+        if !read_full(&mut stream, &mut length, stop)? {
+            return Ok(());
+        }
+        let length = u32::from_be_bytes(length) as usize;
+        if length == 0 || length > MAX_MESSAGE_SIZE {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid length"));
+        }
+        let mut message = vec![0u8; length];
+        if !read_full(&mut stream, &mut message, stop)? {
+            return Ok(());
+        }
+
+        let response = handle(&message, requests, wake);
+        let mut framed = (response.len() as u32).to_be_bytes().to_vec();
+        framed.extend_from_slice(&response);
+        stream.write_all(&framed)?;
+    }
+}
+
+/// Answers one message, anything not understood gets a failure.
+pub(crate) fn handle(
+    message: &[u8],
+    requests: &Sender<AgentRequest>,
+    wake: &(dyn Fn() + Send + Sync),
+) -> Vec<u8> {
+    let mut reader = Reader(message);
+    let response = match reader.u8() {
+        Some(SSH_AGENTC_REQUEST_IDENTITIES) => identities(requests, wake),
+        Some(SSH_AGENTC_SIGN_REQUEST) => match (reader.string(), reader.string(), reader.u32()) {
+            (Some(blob), Some(data), flags) => sign(blob, data, flags.unwrap_or(0), requests, wake),
+            _ => None,
+        },
+        _ => None,
+    };
+
+    response.unwrap_or(vec![SSH_AGENT_FAILURE])
+}
+
+fn identities(requests: &Sender<AgentRequest>, wake: &(dyn Fn() + Send + Sync)) -> Option<Vec<u8>> {
+    let (reply, answer) = mpsc::channel();
+    requests.send(AgentRequest::Identities { reply }).ok()?;
+    wake();
+    let identities = answer.recv_timeout(IDENTITIES_TIMEOUT).ok()?;
+
+    let mut response = vec![SSH_AGENT_IDENTITIES_ANSWER];
+    response.extend_from_slice(&(identities.len() as u32).to_be_bytes());
+    for identity in identities {
+        put_string(&mut response, &identity.blob);
+        put_string(&mut response, identity.comment.as_bytes());
+    }
+
+    Some(response)
+}
+
+fn sign(
+    blob: &[u8],
+    data: &[u8],
+    flags: u32,
+    requests: &Sender<AgentRequest>,
+    wake: &(dyn Fn() + Send + Sync),
+) -> Option<Vec<u8>> {
+    let (reply, answer) = mpsc::channel();
+    let request = AgentRequest::Sign {
+        blob: blob.to_vec(),
+        data: data.to_vec(),
+        flags,
+        reply,
+    };
+    requests.send(request).ok()?;
+    wake();
+    let signature = answer.recv_timeout(CONFIRM_TIMEOUT).ok()??;
+
+    let mut response = vec![SSH_AGENT_SIGN_RESPONSE];
+    put_string(&mut response, &signature);
+
+    Some(response)
+}
+
+/// Appends the data with its length in front, which is how the protocol
+/// encodes strings.
+pub(crate) fn put_string(buffer: &mut Vec<u8>, data: &[u8]) {
+    buffer.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    buffer.extend_from_slice(data);
+}
+
+/// Reads the fields of a message from its start.
+pub(crate) struct Reader<'a>(pub &'a [u8]);
+impl<'a> Reader<'a> {
+    pub fn u8(&mut self) -> Option<u8> {
+        let (first, rest) = self.0.split_first()?;
+        self.0 = rest;
+        Some(*first)
+    }
+
+    pub fn u32(&mut self) -> Option<u32> {
+        if self.0.len() < 4 {
+            return None;
+        }
+        let (value, rest) = self.0.split_at(4);
+        self.0 = rest;
+        Some(u32::from_be_bytes([value[0], value[1], value[2], value[3]]))
+    }
+
+    pub fn string(&mut self) -> Option<&'a [u8]> {
+        let length = self.u32()? as usize;
+        if self.0.len() < length {
+            return None;
+        }
+        let (value, rest) = self.0.split_at(length);
+        self.0 = rest;
+        Some(value)
+    }
+}
@@ -1,17 +1,52 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use attachments::AttachmentDialog;
 use eframe::egui::{self, Button, Color32, Frame, Label, Layout, Margin, RichText, Ui};
+use folders::{EntryDrag, FolderDialog};
+use palette::Palette;
 use serde::{Deserialize, Serialize};
+use toast::{ToastAction, Toasts};
 use utils::{load_settings, Settings};
 
 use crate::{
     cryptography::decrypt_text,
     data_base::{
-        get::{get_entries, get_master},
-        post::{add_entry, save_current_list, save_master},
+        audit::{AuditAction, AuditLog, VerifiedRecord},
+        folder::load_folders,
+        get::get_master,
+        history::{Action, History},
+        kind::{EntryKind, Payload},
+        post::save_master,
+        repository::{Repository, SortMode},
+        store::{open_backend, open_store, save_config, Backend, VaultConfig, VaultStore},
         Entry, EntryDisplay,
     },
+    error::{DbError, InternalError},
     helpers::timestamp_as_date,
+    logging,
 };
 
+mod attachments;
+mod audit;
+#[cfg(unix)]
+mod browser;
+mod folders;
+mod history;
+mod kinds;
+#[cfg(unix)]
+mod local_api;
+mod palette;
+mod recovery;
+#[cfg(target_os = "linux")]
+mod secret_service;
+mod shortcuts;
+#[cfg(unix)]
+mod ssh_agent;
+mod toast;
+mod trash;
 mod utils;
 
 const GREEN: egui::Color32 = egui::Color32::from_rgb(105, 219, 124);
@@ -26,6 +61,8 @@ const SECONDARY_BRIGHT: egui::Color32 = egui::Color32::from_rgb(233, 236, 239);
 const BORDER_BRIGHT: egui::Color32 = egui::Color32::from_rgb(206, 212, 218);
 const BRIGHT_DEFAULT_GREY: egui::Color32 = egui::Color32::from_rgb(134, 142, 150);
 const CORNER_ROUND: f32 = 2.0;
+/// How many entries the "Recent" section of the list shows.
+const RECENT_ENTRIES: usize = 5;
 
 #[derive(Default, Clone, Deserialize, Serialize, Debug)]
 enum Mode {
@@ -42,7 +79,8 @@ impl Mode {
     }
 }
 
-pub fn setup() {
+/// Starts the user interface and blocks until its window is closed.
+pub fn setup() -> eframe::Result {
     let options = eframe::NativeOptions {
         run_and_return: true,
         viewport: egui::ViewportBuilder::default()
@@ -53,39 +91,98 @@ pub fn setup() {
         ..Default::default()
     };
 
+    logging::init(load_settings().log_level);
+    log::info!("Starting Vault {}", env!("CARGO_PKG_VERSION"));
+
     eframe::run_native(
         "Vault",
         options,
         Box::new(|cc| Ok(Box::new(Vault::new(cc)))),
     )
-    .unwrap();
 }
 
-#[derive(Default)]
 struct Vault {
-    entries: Vec<Entry>,
+    repository: Repository,
+    /// Set when the entries could not be loaded, which shows the recovery
+    /// screen after the login.
+    load_error: Option<InternalError>,
+    /// Set when the master file could not be read, which shows the recovery
+    /// screen instead of the login.
+    master_error: Option<InternalError>,
+    backup_path: String,
+    history: History,
     has_error: Option<bool>,
     selected_entry: Option<EntryDisplay>,
-    toast_message: String,
     state: State,
     logged_in: bool,
     password_visible: bool,
     input_name: String,
     input_user_name: String,
     input_password: String,
+    /// The kind of the entry being added or edited.
+    input_kind: EntryKind,
+    /// The values of the entry being added or edited, if it is not a login.
+    input_payload: Payload,
+    /// The website of a login.
+    input_url: String,
+    /// The tags of the entry, separated by commas.
+    input_tags: String,
     master_password: String,
     verify_master: String,
-    toast_end_time: i64,
+    toasts: Toasts,
     first_use: bool,
     passwords_match: bool,
     delete_dialog: bool,
+    /// The id of the entry inside the trash which should be purged.
+    purge_dialog: Option<String>,
+    /// The entry highlighted inside the list, which is moved with the keyboard.
+    list_cursor: Option<String>,
+    scroll_to_cursor: bool,
+    show_help: bool,
+    /// The open command palette.
+    palette: Option<Palette>,
+    /// The folders created by the user, also the empty ones.
+    folders: Vec<String>,
+    folder_dialog: Option<FolderDialog>,
+    attachment_dialog: Option<AttachmentDialog>,
+    /// The running SSH agent, which only runs while the vault is unlocked.
+    #[cfg(unix)]
+    ssh_agent: Option<ssh_agent::AgentState>,
+    /// The socket of the browser extension, which only listens while the
+    /// vault is unlocked.
+    #[cfg(unix)]
+    browser_bridge: Option<browser::BridgeState>,
+    /// The API for scripts, which only listens while the vault is unlocked.
+    #[cfg(unix)]
+    local_api: Option<local_api::ApiState>,
+    /// The Secret Service for other apps, which only runs while the vault is
+    /// unlocked.
+    #[cfg(target_os = "linux")]
+    secret_service: Option<secret_service::ServiceState>,
+    /// The last input, the vault locks itself after a while without one.
+    last_activity: Instant,
+    audit_records: Vec<VerifiedRecord>,
+    backend: Backend,
     settings: Settings,
 }
 impl Vault {
     fn new(_cc: &eframe::CreationContext<'_>) -> Self {
         let has_error = None;
-        let entries = get_entries().unwrap();
-        let toast_message = String::new();
+        let (repository, load_error) = match open_store().and_then(Vault::open_repository) {
+            Ok(repository) => {
+                match repository.clean_attachments() {
+                    Ok(0) => {}
+                    Ok(cleaned) => log::info!("Deleted {} orphaned attachments", cleaned),
+                    Err(err) => log::warn!("Failed to clean up the attachments: {:?}", err),
+                }
+                (repository, None)
+            }
+            Err(err) => {
+                log::error!("Failed to load the entries: {:?}", err);
+                (Repository::empty(), Some(err))
+            }
+        };
+        let backup_path = String::new();
         let selected_entry = None;
         let state = State::default();
         let logged_in = false;
@@ -93,21 +190,50 @@ impl Vault {
         let input_name = String::new();
         let input_user_name = String::new();
         let input_password = String::new();
+        let input_kind = EntryKind::default();
+        let input_payload = Payload::empty(input_kind);
+        let input_url = String::new();
+        let input_tags = String::new();
         let master_password = String::new();
         let verify_master = String::new();
-        let toast_end_time = 0;
+        let toasts = Toasts::default();
+        let history = History::new();
         let passwords_match = true;
         let delete_dialog = false;
+        let purge_dialog = None;
+        let list_cursor = None;
+        let scroll_to_cursor = false;
+        let show_help = false;
+        let palette = None;
+        let folders = match load_folders() {
+            Ok(folders) => folders,
+            Err(err) => {
+                log::warn!("Failed to load the folders: {:?}", err);
+                Vec::<String>::new()
+            }
+        };
+        let folder_dialog = None;
+        let attachment_dialog = None;
+        let last_activity = Instant::now();
+        let audit_records = Vec::<VerifiedRecord>::new();
+        let backend = Backend::default();
         let settings = load_settings();
-        let first_use = match get_master() {
-            Ok(_) => false,
-            Err(err) => &err.code() == "[DB_G_GM-3]",
+        let (first_use, master_error) = match get_master() {
+            Ok(_) => (false, None),
+            Err(InternalError::Db(DbError::MasterOpen(_))) => (true, None),
+            Err(err) => {
+                log::error!("Failed to read the master file: {:?}", err);
+                (false, Some(err))
+            }
         };
 
         Self {
-            entries,
+            repository,
+            load_error,
+            master_error,
+            backup_path,
+            history,
             has_error,
-            toast_message,
             selected_entry,
             state,
             logged_in,
@@ -115,12 +241,35 @@ impl Vault {
             input_name,
             input_user_name,
             input_password,
+            input_kind,
+            input_payload,
+            input_url,
+            input_tags,
             master_password,
             verify_master,
-            toast_end_time,
+            toasts,
             first_use,
             passwords_match,
             delete_dialog,
+            purge_dialog,
+            list_cursor,
+            scroll_to_cursor,
+            show_help,
+            palette,
+            folders,
+            folder_dialog,
+            attachment_dialog,
+            #[cfg(unix)]
+            ssh_agent: None,
+            #[cfg(unix)]
+            browser_bridge: None,
+            #[cfg(unix)]
+            local_api: None,
+            #[cfg(target_os = "linux")]
+            secret_service: None,
+            last_activity,
+            audit_records,
+            backend,
             settings,
         }
     }
@@ -132,29 +281,63 @@ impl Vault {
             .min_width(ctx.available_rect().width() * 0.25)
             .show(ctx, |ui| {
                 ui.vertical_centered_justified(|panel_ui| {
+                    let mut sort_mode = self.settings.sort_mode;
+                    egui::ComboBox::from_id_source("Sort Mode")
+                        .selected_text(format!("Sort by {}", sort_mode.label()))
+                        .width(panel_ui.available_width())
+                        .show_ui(panel_ui, |c_ui| {
+                            for mode in SortMode::ALL {
+                                c_ui.selectable_value(&mut sort_mode, mode, mode.label());
+                            }
+                        });
+                    if sort_mode != self.settings.sort_mode {
+                        self.set_sort_mode(sort_mode);
+                    }
+                    panel_ui.add_space(5.0);
+
                     egui::ScrollArea::vertical()
                         .max_width(f32::INFINITY)
                         .max_height(ctx.available_rect().height() * 0.8)
                         .auto_shrink(false)
                         .show(panel_ui, |sa_ui| {
                             sa_ui.vertical_centered_justified(|centered_ui| {
-                                for entry in &self.entries {
-                                    let is_selected = if self.selected_entry.is_none() {
-                                        false
-                                    } else {
-                                        self.selected_entry.clone().unwrap().id == entry.id()
-                                    };
-                                    let selected = Vault::selectable_text(
+                                let favorites = Vault::list_items(self.repository.favorites());
+                                let recent =
+                                    Vault::list_items(self.repository.recent(RECENT_ENTRIES));
+                                let all = Vault::list_items(
+                                    self.repository.sorted(self.settings.sort_mode),
+                                );
+
+                                let mut clicked = None;
+                                for (title, items) in [("Favorites", favorites), ("Recent", recent)]
+                                {
+                                    if items.is_empty() {
+                                        continue;
+                                    }
+                                    egui::CollapsingHeader::new(Vault::app_text(
+                                        title,
+                                        14.0,
+                                        Some(BRIGHT_DEFAULT_GREY),
+                                        self.settings.mode.clone(),
+                                    ))
+                                    .default_open(true)
+                                    .show(
                                         centered_ui,
-                                        &entry.name(),
-                                        is_selected,
-                                        &self.settings.mode,
+                                        |c_ui| {
+                                            if let Some(id) = self.list_entries(c_ui, items, false)
+                                            {
+                                                clicked = Some(id);
+                                            }
+                                        },
                                     );
-                                    if selected.clicked() {
-                                        self.password_visible = false;
-                                        self.state = State::DisplayEntry;
-                                        self.selected_entry = Some(entry.get_details().unwrap());
-                                    }
+                                }
+                                let names = all.into_iter().collect::<HashMap<String, String>>();
+                                if let Some(id) = self.show_folder_tree(centered_ui, &names) {
+                                    clicked = Some(id);
+                                }
+
+                                if let Some(id) = clicked {
+                                    self.open_entry(&id);
                                 }
                             });
                         });
@@ -175,30 +358,51 @@ impl Vault {
                         },
                         self.settings.mode.clone(),
                     );
+                    let trash_text = format!("Trash ({})", self.repository.trash().len());
+                    let trash = Vault::app_button(
+                        &trash_text,
+                        16.0,
+                        Some(BRIGHT_DEFAULT_GREY),
+                        self.settings.mode.clone(),
+                    );
+                    let audit = Vault::app_button(
+                        "Audit Log",
+                        16.0,
+                        Some(BRIGHT_DEFAULT_GREY),
+                        self.settings.mode.clone(),
+                    );
+                    let new_folder = Vault::app_button(
+                        "New Folder",
+                        16.0,
+                        Some(BRIGHT_DEFAULT_GREY),
+                        self.settings.mode.clone(),
+                    );
                     let add_entry_button = panel_ui.add(add_entry);
                     panel_ui.add_space(5.0);
+                    let new_folder_button = panel_ui.add(new_folder);
+                    panel_ui.add_space(5.0);
+                    let trash_button = panel_ui.add(trash);
+                    panel_ui.add_space(5.0);
+                    let audit_button = panel_ui.add(audit);
+                    panel_ui.add_space(5.0);
                     let toggle_mode = panel_ui.add(toggle_mode);
                     if add_entry_button.clicked() {
-                        self.selected_entry = None;
-                        self.password_visible = false;
-                        self.state = State::AddingEntry;
-                        self.input_name = String::new();
-                        self.input_user_name = String::new();
-                        self.input_password = String::new();
+                        self.start_adding();
+                    }
+                    if new_folder_button.clicked() {
+                        self.folder_dialog = Some(FolderDialog::Create {
+                            parent: String::new(),
+                            name: String::new(),
+                        });
+                    }
+                    if trash_button.clicked() {
+                        self.open_trash();
+                    }
+                    if audit_button.clicked() {
+                        self.open_audit();
                     }
                     if toggle_mode.clicked() {
-                        self.settings.mode = self.settings.mode.clone().switch();
-                        match utils::save_settings(&self.settings) {
-                            Ok(_) => {}
-                            Err(_) => match utils::save_settings(&self.settings) {
-                                Ok(_) => {}
-                                Err(err) => {
-                                    self.has_error = Some(true);
-                                    self.toast_message = err.toast_message();
-                                    self.set_toast_time();
-                                }
-                            },
-                        }
+                        self.toggle_mode();
                     }
                 });
             });
@@ -210,8 +414,8 @@ impl Vault {
                 if self.delete_dialog {
                     let text = Vault::app_text(
                         &format!(
-                            "Do you want to delete the Entry \"{}\"?",
-                            &self.selected_entry.clone().unwrap().name
+                            "Do you want to move the Entry \"{}\" to the Trash?",
+                            entry.name
                         ),
                         20.0,
                         None,
@@ -244,39 +448,15 @@ impl Vault {
                                     self.delete_dialog = false;
                                 }
                                 if confirm_button.clicked() {
-                                    let mut entry_index = 0;
-                                    for i in 0..self.entries.len() {
-                                        if self.entries[i].id()
-                                            == self.selected_entry.clone().unwrap().id
-                                        {
-                                            entry_index = i;
-                                        }
-                                    }
-                                    let mut clone = self.entries.clone();
-                                    clone.remove(entry_index);
-                                    match save_current_list(clone.clone()) {
-                                        Ok(_) => {
-                                            self.toast_message = String::from("Entry Deleted");
-                                            self.entries = clone;
-                                            self.selected_entry = None;
-                                            self.state = State::None;
-                                            self.delete_dialog = false;
-                                            self.set_toast_time();
-                                        }
-                                        Err(err) => {
-                                            self.toast_message = format!(
-                                                "{}\nPlease try again",
-                                                err.toast_message()
-                                            );
-                                            self.has_error = Some(true);
-                                            self.set_toast_time();
-                                        }
-                                    };
+                                    self.confirm_delete();
                                 }
                             },
                         );
                     }
                 } else {
+                    let mut copied = None;
+                    let mut revealed = false;
+                    let mut copied_field = None;
                     ui.label(Vault::app_text(
                         "Name",
                         12.0,
@@ -291,131 +471,200 @@ impl Vault {
                     ));
                     ui.separator();
 
-                    ui.label(Vault::app_text(
-                        "Username",
-                        12.0,
-                        None,
-                        self.settings.mode.clone(),
-                    ));
-                    ui.with_layout(
-                        Layout::left_to_right(egui::Align::Min).with_main_wrap(true),
-                        |l_ui| {
-                            let ui_width = l_ui.available_width();
-
-                            let text_label = Label::new(Vault::app_text(
-                                &entry.user_name,
-                                18.0,
+                    match entry.kind {
+                        EntryKind::Login => {
+                            ui.label(Vault::app_text(
+                                "Username",
+                                12.0,
                                 None,
                                 self.settings.mode.clone(),
-                            ))
-                            .wrap_mode(egui::TextWrapMode::Wrap);
-
-                            let label_r = l_ui.add(text_label);
-                            let label_rect_width = label_r.rect.width();
-
-                            let added_space = if label_rect_width > ui_width * 0.825 {
-                                ui_width * 0.825 - (label_rect_width - ui_width)
-                            } else {
-                                ui_width * 0.825 - label_r.rect.width()
-                            };
+                            ));
+                            ui.with_layout(
+                                Layout::left_to_right(egui::Align::Min).with_main_wrap(true),
+                                |l_ui| {
+                                    let ui_width = l_ui.available_width();
+
+                                    let text_label = Label::new(Vault::app_text(
+                                        entry.user_name.expose(),
+                                        18.0,
+                                        None,
+                                        self.settings.mode.clone(),
+                                    ))
+                                    .wrap_mode(egui::TextWrapMode::Wrap);
+
+                                    let label_r = l_ui.add(text_label);
+                                    let label_rect_width = label_r.rect.width();
+
+                                    let added_space = if label_rect_width > ui_width * 0.825 {
+                                        ui_width * 0.825 - (label_rect_width - ui_width)
+                                    } else {
+                                        ui_width * 0.825 - label_r.rect.width()
+                                    };
 
-                            l_ui.add_space(added_space);
+                                    l_ui.add_space(added_space);
 
-                            let copy =
-                                Vault::app_button("Copy", 14.0, None, self.settings.mode.clone());
-                            let copy_button = l_ui.add(copy);
+                                    let copy = Vault::app_button(
+                                        "Copy",
+                                        14.0,
+                                        None,
+                                        self.settings.mode.clone(),
+                                    );
+                                    let copy_button = l_ui.add(copy);
 
-                            if copy_button.clicked() {
-                                let mut clipboard = clippers::Clipboard::get();
-                                clipboard.write_text(&entry.user_name).unwrap();
-                            }
-                        },
-                    );
-                    ui.separator();
+                                    if copy_button.clicked() {
+                                        copied = Some(AuditAction::CopiedUserName);
+                                    }
+                                },
+                            );
+                            ui.separator();
 
-                    ui.label(Vault::app_text(
-                        "Password",
-                        12.0,
-                        None,
-                        self.settings.mode.clone(),
-                    ));
-                    ui.with_layout(
-                        Layout::left_to_right(egui::Align::Min).with_main_wrap(true),
-                        |l_ui| {
-                            let pw_text = if self.password_visible {
-                                &entry.password
-                            } else {
-                                "********"
-                            };
-                            let display_button_text = if self.password_visible {
-                                "Hide"
-                            } else {
-                                "Show"
-                            };
-
-                            let ui_width = l_ui.available_width();
-
-                            let text_label = Label::new(Vault::app_text(
-                                pw_text,
-                                18.0,
+                            ui.label(Vault::app_text(
+                                "Password",
+                                12.0,
                                 None,
                                 self.settings.mode.clone(),
-                            ))
-                            .wrap_mode(egui::TextWrapMode::Wrap);
+                            ));
+                            ui.with_layout(
+                                Layout::left_to_right(egui::Align::Min).with_main_wrap(true),
+                                |l_ui| {
+                                    let pw_text = if self.password_visible {
+                                        entry.password.expose()
+                                    } else {
+                                        "********"
+                                    };
+                                    let display_button_text = if self.password_visible {
+                                        "Hide"
+                                    } else {
+                                        "Show"
+                                    };
 
-                            let label_r = l_ui.add(text_label);
-                            let label_rect_width = label_r.rect.width();
+                                    let ui_width = l_ui.available_width();
 
-                            let added_space = if label_rect_width > ui_width * 0.825 {
-                                ui_width * 0.825 - (label_rect_width - ui_width * 0.825)
-                            } else {
-                                ui_width * 0.825 - label_r.rect.width()
-                            };
+                                    let text_label = Label::new(Vault::app_text(
+                                        pw_text,
+                                        18.0,
+                                        None,
+                                        self.settings.mode.clone(),
+                                    ))
+                                    .wrap_mode(egui::TextWrapMode::Wrap);
 
-                            l_ui.add_space(added_space);
+                                    let label_r = l_ui.add(text_label);
+                                    let label_rect_width = label_r.rect.width();
 
-                            let copy =
-                                Vault::app_button("Copy", 14.0, None, self.settings.mode.clone());
-                            let visibility = Vault::app_button(
-                                display_button_text,
-                                14.0,
-                                None,
-                                self.settings.mode.clone(),
+                                    let added_space = if label_rect_width > ui_width * 0.825 {
+                                        ui_width * 0.825 - (label_rect_width - ui_width * 0.825)
+                                    } else {
+                                        ui_width * 0.825 - label_r.rect.width()
+                                    };
+
+                                    l_ui.add_space(added_space);
+
+                                    let copy = Vault::app_button(
+                                        "Copy",
+                                        14.0,
+                                        None,
+                                        self.settings.mode.clone(),
+                                    );
+                                    let visibility = Vault::app_button(
+                                        display_button_text,
+                                        14.0,
+                                        None,
+                                        self.settings.mode.clone(),
+                                    );
+                                    let copy_button = l_ui.add(copy);
+                                    let visibility_button = l_ui.add(visibility);
+
+                                    if copy_button.clicked() {
+                                        copied = Some(AuditAction::CopiedPassword);
+                                    }
+                                    if visibility_button.clicked() {
+                                        self.password_visible = !self.password_visible;
+                                        revealed = self.password_visible;
+                                    }
+                                },
                             );
-                            let copy_button = l_ui.add(copy);
-                            let visibility_button = l_ui.add(visibility);
 
-                            if copy_button.clicked() {
-                                let mut clipboard = clippers::Clipboard::get();
-                                clipboard.write_text(&entry.password).unwrap();
+                            if !entry.url.is_empty() {
+                                ui.separator();
+                                ui.label(Vault::app_text(
+                                    "Website",
+                                    12.0,
+                                    None,
+                                    self.settings.mode.clone(),
+                                ));
+                                ui.label(Vault::app_text(
+                                    &entry.url,
+                                    16.0,
+                                    None,
+                                    self.settings.mode.clone(),
+                                ));
                             }
-                            if visibility_button.clicked() {
+                        }
+                        _ => {
+                            let (index, toggled) = Vault::show_payload(
+                                ui,
+                                entry.payload.expose(),
+                                self.password_visible,
+                                &self.settings.mode,
+                            );
+                            copied_field = index;
+                            if toggled {
                                 self.password_visible = !self.password_visible;
+                                revealed = self.password_visible;
                             }
-                        },
-                    );
+                        }
+                    }
                     ui.add_space(30.0);
 
                     let edit = Vault::app_button("Edit", 16.0, None, self.settings.mode.clone());
                     let delete =
                         Vault::app_button("Delete", 16.0, Some(RED), self.settings.mode.clone());
-                    ui.with_layout(Layout::left_to_right(egui::Align::Min), |b_ui| {
-                        let edit_button = b_ui.add(edit);
-                        let delete_button = b_ui.add(delete);
-                        if edit_button.clicked() {
-                            self.password_visible = false;
-                            let entry = &self.selected_entry.clone().unwrap();
-                            self.input_name = entry.name.clone();
-                            self.input_user_name = entry.user_name.clone();
-                            self.input_password = entry.password.clone();
-                            self.state = State::EditEntry;
-                        }
-                        if delete_button.clicked() {
-                            self.delete_dialog = true;
-                        }
-                    });
+                    let favorite = Vault::app_button(
+                        match entry.favorite {
+                            true => "Remove from Favorites",
+                            false => "Add to Favorites",
+                        },
+                        16.0,
+                        Some(BRIGHT_DEFAULT_GREY),
+                        self.settings.mode.clone(),
+                    );
+                    let (edit_clicked, favorite_clicked) = ui
+                        .with_layout(Layout::left_to_right(egui::Align::Min), |b_ui| {
+                            let edit_button = b_ui.add(edit);
+                            let delete_button = b_ui.add(delete);
+                            let favorite_button = b_ui.add(favorite);
+                            if delete_button.clicked() {
+                                self.delete_dialog = true;
+                            }
+                            (edit_button.clicked(), favorite_button.clicked())
+                        })
+                        .inner;
                     ui.add_space(15.0);
 
+                    if entry.kind != EntryKind::Login {
+                        ui.label(Vault::app_text(
+                            &format!("Kind: {}", entry.kind.label()),
+                            12.0,
+                            None,
+                            self.settings.mode.clone(),
+                        ));
+                    }
+                    if !entry.folder.is_empty() {
+                        ui.label(Vault::app_text(
+                            &format!("Folder: {}", entry.folder),
+                            12.0,
+                            None,
+                            self.settings.mode.clone(),
+                        ));
+                    }
+                    if !entry.tags.is_empty() {
+                        ui.label(Vault::app_text(
+                            &format!("Tags: {}", entry.tags.join(", ")),
+                            12.0,
+                            None,
+                            self.settings.mode.clone(),
+                        ));
+                    }
                     ui.label(Vault::app_text(
                         &format!("Created At: {}", timestamp_as_date(entry.created_at)),
                         12.0,
@@ -428,29 +677,38 @@ impl Vault {
                         None,
                         self.settings.mode.clone(),
                     ));
+
+                    let (id, favorite) = (entry.id.clone(), entry.favorite);
+                    let attachments = entry.attachments.clone();
+                    self.show_attachments(ui, &id, &attachments);
+                    if let Some(action) = copied {
+                        self.copy_entry_field(&id, action);
+                    }
+                    if let Some(index) = copied_field {
+                        self.copy_payload_field(&id, index);
+                    }
+                    if revealed {
+                        self.mark_used(&id);
+                    }
+                    if favorite_clicked {
+                        self.set_favorite(&id, !favorite);
+                    }
+                    if edit_clicked {
+                        self.start_editing();
+                    }
                 }
             }
             None => {}
         }
     }
 
-    fn edit_entry(&mut self, _ctx: &egui::Context, ui: &mut Ui) {
+    /// The username and password inputs of a login.
+    fn login_form(&mut self, ui: &mut Ui) {
         let visibility_text = if self.password_visible {
             "Hide"
         } else {
             "Show"
         };
-        ui.label(Vault::app_text(
-            "Name",
-            12.0,
-            None,
-            self.settings.mode.clone(),
-        ));
-        Vault::line_edit_frame(&self.settings.mode).show(ui, |f_ui| {
-            Vault::single_line_edit(f_ui, &mut self.input_name, None, &self.settings.mode)
-        });
-        ui.separator();
-
         ui.label(Vault::app_text(
             "Username",
             12.0,
@@ -484,6 +742,50 @@ impl Vault {
                 self.password_visible = !self.password_visible;
             }
         });
+        ui.separator();
+
+        ui.label(Vault::app_text(
+            "Website",
+            12.0,
+            None,
+            self.settings.mode.clone(),
+        ));
+        Vault::line_edit_frame(&self.settings.mode).show(ui, |f_ui| {
+            Vault::single_line_edit(f_ui, &mut self.input_url, None, &self.settings.mode)
+        });
+    }
+
+    /// The tags of any kind of entry.
+    fn tags_form(&mut self, ui: &mut Ui) {
+        ui.separator();
+        ui.label(Vault::app_text(
+            "Tags, separated by commas",
+            12.0,
+            None,
+            self.settings.mode.clone(),
+        ));
+        Vault::line_edit_frame(&self.settings.mode).show(ui, |f_ui| {
+            Vault::single_line_edit(f_ui, &mut self.input_tags, None, &self.settings.mode)
+        });
+    }
+
+    fn edit_entry(&mut self, _ctx: &egui::Context, ui: &mut Ui) {
+        ui.label(Vault::app_text(
+            "Name",
+            12.0,
+            None,
+            self.settings.mode.clone(),
+        ));
+        Vault::line_edit_frame(&self.settings.mode).show(ui, |f_ui| {
+            Vault::single_line_edit(f_ui, &mut self.input_name, None, &self.settings.mode)
+        });
+        ui.separator();
+
+        match self.input_kind {
+            EntryKind::Login => self.login_form(ui),
+            _ => self.payload_form(ui),
+        }
+        self.tags_form(ui);
         ui.add_space(30.0);
 
         let save = Vault::app_button("Save", 16.0, Some(GREEN), self.settings.mode.clone());
@@ -499,61 +801,55 @@ impl Vault {
             let cancel_button = b_ui.add(cancel);
 
             if save_button.clicked() {
-                let mut entry_index = 0;
-                for i in 0..self.entries.len() {
-                    if self.entries[i].id() == self.selected_entry.clone().unwrap().id {
-                        entry_index = i;
-                    }
-                }
-                match self.entries[entry_index].edit(
-                    &self.input_name,
-                    &self.input_user_name,
-                    &self.input_password,
-                ) {
-                    Ok(_) => match save_current_list(self.entries.clone()) {
-                        Ok(_) => {
-                            self.password_visible = false;
-                            self.toast_message = String::from("Entry Saved");
-                            self.has_error = Some(false);
-                            self.selected_entry =
-                                Some(self.entries[entry_index].get_details().unwrap());
-                            self.state = State::DisplayEntry;
-                            self.input_name = String::new();
-                            self.input_user_name = String::new();
-                            self.input_password = String::new();
-                            self.set_toast_time();
-                        }
-                        Err(err) => {
-                            self.toast_message =
-                                format!("{}\nPlease try again", err.toast_message());
-                            self.has_error = Some(true);
-                            self.set_toast_time();
+                let id = match &self.selected_entry {
+                    Some(entry) => entry.id.clone(),
+                    None => return,
+                };
+                let before = self.repository.get(&id).cloned();
+                let payload = self.input_as_payload();
+                let saved = self
+                    .repository
+                    .update_payload(&id, &self.input_name, &payload)
+                    .map(|_| ())
+                    .and_then(|_| self.save_input_url(&id))
+                    .and_then(|_| self.save_input_tags(&id));
+                let saved = saved.and_then(|_| {
+                    let entry = self
+                        .repository
+                        .get(&id)
+                        .ok_or(InternalError::from(DbError::EntryNotFound))?;
+                    Ok((entry, entry.get_details()?))
+                });
+                match saved {
+                    Ok((entry, details)) => {
+                        self.selected_entry = Some(details);
+                        if let Some(before) = before {
+                            self.history.record(Action::Edited {
+                                before: Box::new(before),
+                                after: Box::new(entry.clone()),
+                            });
+                            self.toasts.with_action("Entry Saved", ToastAction::Undo);
                         }
-                    },
+                        self.password_visible = false;
+                        self.state = State::DisplayEntry;
+                        self.clear_inputs();
+                    }
                     Err(err) => {
-                        self.toast_message = format!("{}\nPlease try again", err.toast_message());
-                        self.has_error = Some(true);
-                        self.set_toast_time();
+                        self.toasts
+                            .error(format!("{}\nPlease try again", err.toast_message()));
                     }
                 }
             }
             if cancel_button.clicked() {
                 self.password_visible = false;
                 self.state = State::DisplayEntry;
-                self.input_name = String::new();
-                self.input_user_name = String::new();
-                self.input_password = String::new();
+                self.clear_inputs();
             }
         });
     }
 
     fn add_entry(&mut self, _ctx: &egui::Context, ui: &mut Ui) {
-        let visibility_text = if self.password_visible {
-            "Hide"
-        } else {
-            "Show"
-        };
-
+        self.kind_selector(ui);
         ui.label(Vault::app_text(
             "Name",
             12.0,
@@ -565,39 +861,11 @@ impl Vault {
         });
         ui.separator();
 
-        ui.label(Vault::app_text(
-            "Username",
-            12.0,
-            None,
-            self.settings.mode.clone(),
-        ));
-        Vault::line_edit_frame(&self.settings.mode).show(ui, |f_ui| {
-            Vault::single_line_edit(f_ui, &mut self.input_user_name, None, &self.settings.mode)
-        });
-        ui.separator();
-
-        let visibility = Vault::app_button(visibility_text, 14.0, None, self.settings.mode.clone());
-        ui.label(Vault::app_text(
-            "Password",
-            12.0,
-            None,
-            self.settings.mode.clone(),
-        ));
-        ui.with_layout(Layout::left_to_right(egui::Align::Min), |l_ui| {
-            Vault::line_edit_frame(&self.settings.mode).show(l_ui, |f_ui| {
-                Vault::single_line_edit(
-                    f_ui,
-                    &mut self.input_password,
-                    Some(!self.password_visible),
-                    &self.settings.mode,
-                )
-            });
-            let visibility_button = l_ui.add(visibility);
-
-            if visibility_button.clicked() {
-                self.password_visible = !self.password_visible;
-            }
-        });
+        match self.input_kind {
+            EntryKind::Login => self.login_form(ui),
+            _ => self.payload_form(ui),
+        }
+        self.tags_form(ui);
         ui.add_space(30.0);
 
         let save = Vault::app_button("Save", 16.0, Some(GREEN), self.settings.mode.clone());
@@ -613,56 +881,47 @@ impl Vault {
             let cancel_button = b_ui.add(cancel);
 
             if save_button.clicked() {
-                if String::is_empty(&self.input_name)
-                    && String::is_empty(&self.input_user_name)
-                    && String::is_empty(&self.input_password)
-                {
+                let payload = self.input_as_payload();
+                if String::is_empty(&self.input_name) && payload.is_empty() {
                     return;
                 }
 
-                match add_entry(
-                    &self.input_name,
-                    &self.input_user_name,
-                    &self.input_password,
-                    self.entries.clone(),
-                ) {
-                    Ok(entries) => {
-                        self.toast_message = String::from("Entry Saved");
-                        self.has_error = Some(false);
-                        self.entries = entries;
-                        self.selected_entry =
-                            Some(self.entries[self.entries.len() - 1].get_details().unwrap());
+                let saved = self
+                    .repository
+                    .add_payload(&self.input_name, &payload)
+                    .map(|e| e.id())
+                    .and_then(|id| self.save_input_url(&id).map(|_| id))
+                    .and_then(|id| self.save_input_tags(&id).map(|_| id));
+                let saved = saved.and_then(|id| {
+                    let entry = self
+                        .repository
+                        .get(&id)
+                        .ok_or(InternalError::from(DbError::EntryNotFound))?;
+                    Ok((entry, entry.get_details()?))
+                });
+                match saved {
+                    Ok((entry, details)) => {
+                        self.selected_entry = Some(details);
+                        self.history.record(Action::Added(entry.clone()));
+                        self.toasts.success("Entry Saved");
                         self.password_visible = false;
                         self.state = State::DisplayEntry;
-                        self.input_name = String::new();
-                        self.input_user_name = String::new();
-                        self.input_password = String::new();
-                        self.set_toast_time();
+                        self.clear_inputs();
                     }
                     Err(err) => {
-                        self.toast_message = format!("{}\nPlease try again", err.toast_message());
-                        self.has_error = Some(true);
-                        self.set_toast_time();
+                        self.toasts
+                            .error(format!("{}\nPlease try again", err.toast_message()));
                     }
                 }
             }
             if cancel_button.clicked() {
                 self.password_visible = false;
                 self.state = State::None;
-                self.input_name = String::new();
-                self.input_user_name = String::new();
-                self.input_password = String::new();
+                self.clear_inputs();
             }
         });
     }
 
-    // fn show_toast(&mut self, ctx: &egui::Context) {
-    //     let ctx_right_bottom = ctx.available_rect().right_bottom();
-    //     let width = 150.0;
-    //     let x = ctx_right_bottom.x - width;
-    //     let y = ctx_right_bottom.y - 50.0;
-    // }
-
     fn authenticate(&mut self, ctx: &egui::Context) {
         egui::CentralPanel::default()
             .frame(Frame {
@@ -723,15 +982,28 @@ impl Vault {
                     if String::is_empty(&self.master_password) {
                         return;
                     }
-                    let master = get_master().unwrap();
-                    let master_decrypt = decrypt_text(&master, true, true).unwrap();
+                    // a master file which can not be read is not a wrong
+                    // password, so the recovery screen is shown instead
+                    let master_decrypt =
+                        match get_master().and_then(|m| decrypt_text(&m, true, true)) {
+                            Ok(master) => master,
+                            Err(err) => {
+                                log::error!("Failed to read the master file: {:?}", err);
+                                self.master_error = Some(err);
+                                return;
+                            }
+                        };
 
                     if master_decrypt == self.master_password {
                         self.passwords_match = true;
                         self.password_visible = false;
                         self.logged_in = true;
+                        self.last_activity = Instant::now();
+                        log::info!("Logged in");
+                        self.purge_expired_trash();
                     } else {
                         self.passwords_match = false;
+                        log::warn!("Login with a wrong master password");
                     }
                 }
             });
@@ -789,6 +1061,22 @@ impl Vault {
                     );
                 });
                 ui.add_space(5.0);
+                ui.with_layout(Layout::left_to_right(egui::Align::Center), |l_ui| {
+                    l_ui.label(Vault::app_text(
+                        "Storage",
+                        14.0,
+                        None,
+                        self.settings.mode.clone(),
+                    ));
+                    egui::ComboBox::from_id_source("Storage Backend")
+                        .selected_text(self.backend.label())
+                        .show_ui(l_ui, |c_ui| {
+                            for backend in [Backend::Json, Backend::Sqlite] {
+                                c_ui.selectable_value(&mut self.backend, backend, backend.label());
+                            }
+                        });
+                });
+                ui.add_space(5.0);
                 let visibility_button = ui.add(visibility);
                 ui.add_space(5.0);
                 let login_button = ui.add(login);
@@ -806,7 +1094,13 @@ impl Vault {
                             return;
                         }
                         match save_master(&self.master_password) {
-                            Ok(_) => self.logged_in = true,
+                            Ok(_) => match self.create_store() {
+                                Ok(_) => {
+                                    self.logged_in = true;
+                                    self.last_activity = Instant::now();
+                                }
+                                Err(_) => self.has_error = Some(true),
+                            },
                             Err(_) => {
                                 self.has_error = Some(true);
                             }
@@ -816,6 +1110,276 @@ impl Vault {
             });
     }
 
+    /// Shows the entry and highlights it inside the list.
+    fn open_entry(&mut self, id: &str) {
+        match self.repository.get(id).map(|e| e.get_details()) {
+            Some(Ok(details)) => {
+                self.password_visible = false;
+                self.delete_dialog = false;
+                self.attachment_dialog = None;
+                self.state = State::DisplayEntry;
+                self.selected_entry = Some(details);
+                self.list_cursor = Some(String::from(id));
+            }
+            Some(Err(err)) => self.toasts.error(err.toast_message()),
+            None => {}
+        }
+    }
+
+    fn start_adding(&mut self) {
+        self.selected_entry = None;
+        self.password_visible = false;
+        self.delete_dialog = false;
+        self.state = State::AddingEntry;
+        self.clear_inputs();
+    }
+
+    fn start_editing(&mut self) {
+        if let Some(entry) = &self.selected_entry {
+            self.password_visible = false;
+            self.delete_dialog = false;
+            self.input_name = entry.name.clone();
+            self.input_user_name = entry.user_name.expose().clone();
+            self.input_password = entry.password.expose().clone();
+            self.input_kind = entry.kind;
+            self.input_payload = entry.payload.expose().clone();
+            self.input_url = entry.url.clone();
+            self.input_tags = entry.tags.join(", ");
+            self.state = State::EditEntry;
+        }
+    }
+
+    /// Empties the form, a new entry starts as a login.
+    fn clear_inputs(&mut self) {
+        self.input_name = String::new();
+        self.input_user_name = String::new();
+        self.input_password = String::new();
+        self.input_kind = EntryKind::default();
+        self.input_payload = Payload::empty(self.input_kind);
+        self.input_url = String::new();
+        self.input_tags = String::new();
+    }
+
+    /// Stores the website of a login if it was changed.
+    fn save_input_url(&mut self, id: &str) -> Result<(), InternalError> {
+        match self.repository.get(id) {
+            Some(entry)
+                if self.input_kind == EntryKind::Login && entry.url() != self.input_url.trim() => {}
+            _ => return Ok(()),
+        }
+
+        self.repository.set_url(id, &self.input_url).map(|_| ())
+    }
+
+    /// Stores the tags of the entry if they were changed.
+    fn save_input_tags(&mut self, id: &str) -> Result<(), InternalError> {
+        let tags = self
+            .input_tags
+            .split(',')
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .collect::<Vec<&str>>();
+        match self.repository.get(id) {
+            Some(entry) if entry.tags() != tags.as_slice() => {}
+            _ => return Ok(()),
+        }
+
+        self.repository.set_tags(id, &tags).map(|_| ())
+    }
+
+    /// The ids and names of the entries, with a star for the favorites.
+    fn list_items(entries: Vec<&Entry>) -> Vec<(String, String)> {
+        entries
+            .into_iter()
+            .map(|entry| {
+                let name = match entry.is_favorite() {
+                    true => format!("★ {}", entry.name()),
+                    false => entry.name(),
+                };
+                (entry.id(), name)
+            })
+            .collect()
+    }
+
+    /// Lists the entries and returns the id of the clicked one. Only one of
+    /// the lists should scroll to the highlighted entry.
+    fn list_entries(
+        &mut self,
+        ui: &mut Ui,
+        items: Vec<(String, String)>,
+        scroll: bool,
+    ) -> Option<String> {
+        let mut clicked = None;
+        for (id, name) in items {
+            let is_selected = self.list_cursor.as_ref() == Some(&id)
+                || self.selected_entry.as_ref().map(|s| &s.id) == Some(&id);
+            let selected = Vault::selectable_text(ui, &name, is_selected, &self.settings.mode);
+            if scroll && is_selected && self.scroll_to_cursor {
+                selected.scroll_to_me(None);
+                self.scroll_to_cursor = false;
+            }
+            selected.dnd_set_drag_payload(EntryDrag(id.clone()));
+            if selected.clicked() {
+                clicked = Some(id);
+            }
+        }
+
+        clicked
+    }
+
+    /// Remembers that the username or password of the entry was just used.
+    fn mark_used(&mut self, id: &str) {
+        if let Err(err) = self.repository.mark_used(id) {
+            log::warn!("Failed to store the last use of {}: {:?}", id, err);
+        }
+    }
+
+    /// Pins the entry on top of the list, or removes it from there.
+    fn set_favorite(&mut self, id: &str, favorite: bool) {
+        match self.repository.set_favorite(id, favorite) {
+            Ok(_) => {
+                if let Some(selected) = self.selected_entry.as_mut() {
+                    if selected.id == id {
+                        selected.favorite = favorite;
+                    }
+                }
+            }
+            Err(err) => self.toasts.error(err.toast_message()),
+        }
+    }
+
+    fn open_trash(&mut self) {
+        self.selected_entry = None;
+        self.password_visible = false;
+        self.delete_dialog = false;
+        self.purge_dialog = None;
+        self.state = State::Trash;
+    }
+
+    fn open_audit(&mut self) {
+        self.selected_entry = None;
+        self.password_visible = false;
+        self.delete_dialog = false;
+        self.state = State::Audit;
+        self.load_audit();
+    }
+
+    fn set_sort_mode(&mut self, sort_mode: SortMode) {
+        self.settings.sort_mode = sort_mode;
+        if let Err(err) = utils::save_settings(&self.settings) {
+            self.toasts.error(err.toast_message());
+        }
+    }
+
+    fn toggle_mode(&mut self) {
+        self.settings.mode = self.settings.mode.clone().switch();
+        match utils::save_settings(&self.settings) {
+            Ok(_) => {}
+            Err(_) => match utils::save_settings(&self.settings) {
+                Ok(_) => {}
+                Err(err) => {
+                    self.toasts.error(err.toast_message());
+                }
+            },
+        }
+    }
+
+    /// Moves the open entry into the trash.
+    fn confirm_delete(&mut self) {
+        let id = match &self.selected_entry {
+            Some(entry) => entry.id.clone(),
+            None => return,
+        };
+        let before = self.repository.get(&id).cloned();
+        match self.repository.delete(&id) {
+            Ok(_) => {
+                if let Some(before) = before {
+                    self.history.record(Action::Deleted(before));
+                    self.toasts
+                        .with_action("Entry moved to Trash", ToastAction::Undo);
+                }
+                self.selected_entry = None;
+                self.state = State::None;
+                self.delete_dialog = false;
+            }
+            Err(err) => {
+                self.toasts
+                    .error(format!("{}\nPlease try again", err.toast_message()));
+            }
+        };
+    }
+
+    /// Goes back to the login, dropping everything decrypted.
+    fn lock(&mut self) {
+        self.logged_in = false;
+        self.master_password = String::new();
+        self.password_visible = false;
+        self.selected_entry = None;
+        self.state = State::None;
+        self.clear_inputs();
+        self.delete_dialog = false;
+        self.purge_dialog = None;
+        self.show_help = false;
+        self.palette = None;
+        self.folder_dialog = None;
+        self.attachment_dialog = None;
+        self.history = History::new();
+        self.toasts.dismiss_action(ToastAction::Undo);
+        #[cfg(unix)]
+        {
+            self.stop_ssh_agent();
+            self.stop_browser_bridge();
+            self.stop_local_api();
+        }
+        #[cfg(target_os = "linux")]
+        self.stop_secret_service();
+        log::info!("Locked");
+    }
+
+    /// Locks the vault once it was not used for the minutes of the settings.
+    fn check_auto_lock(&mut self, ctx: &egui::Context) {
+        if ctx.input(|i| !i.events.is_empty() || i.pointer.is_moving()) {
+            self.last_activity = Instant::now();
+        }
+        if self.settings.auto_lock_minutes == 0 {
+            return;
+        }
+
+        let timeout = Duration::from_secs(u64::from(self.settings.auto_lock_minutes) * 60);
+        let idle = self.last_activity.elapsed();
+        if idle >= timeout {
+            self.lock();
+            self.toasts.info(format!(
+                "Locked after {} minutes without use",
+                self.settings.auto_lock_minutes
+            ));
+            log::info!("Locked after being idle");
+            return;
+        }
+
+        ctx.request_repaint_after(timeout - idle);
+    }
+
+    /// Opens the entries of the store, recording all changes inside the audit
+    /// log.
+    fn open_repository(store: Box<dyn VaultStore>) -> Result<Repository, InternalError> {
+        Ok(Repository::open(store)?.with_audit(AuditLog::open_default()))
+    }
+
+    /// Creates the store of a new vault with the backend chosen by the user.
+    fn create_store(&mut self) -> Result<(), InternalError> {
+        let config = VaultConfig {
+            backend: self.backend,
+            ..Default::default()
+        };
+        save_config(&config)?;
+
+        self.repository = Vault::open_repository(open_backend(self.backend)?)?;
+        self.load_error = None;
+
+        Ok(())
+    }
+
     fn show_search(&mut self, ctx: &egui::Context) {
         egui::TopBottomPanel::top("Search Panel")
             .frame(Frame {
@@ -838,11 +1402,6 @@ impl Vault {
             });
     }
 
-    fn set_toast_time(&mut self) {
-        let end = chrono::Local::now().timestamp_millis() + 2000;
-        self.toast_end_time = end;
-    }
-
     fn app_text(text: &str, font_size: f32, color: Option<egui::Color32>, mode: Mode) -> RichText {
         match color {
             Some(color) => RichText::new(text).size(font_size).color(color),
@@ -962,7 +1521,7 @@ impl Vault {
                 .size(18.0);
             let label = egui::Label::new(text)
                 .selectable(false)
-                .sense(egui::Sense::click());
+                .sense(egui::Sense::click_and_drag());
             f_ui.add(label)
         });
 
@@ -975,12 +1534,47 @@ impl Vault {
 }
 impl eframe::App for Vault {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.show_toasts(ctx);
         if self.logged_in {
-            // if self.toast_end_time < 1 {
-            //     self.show_toast(ctx);
-            // }
+            self.check_auto_lock(ctx);
+        }
+
+        if self.logged_in && self.load_error.is_some() {
+            self.show_recovery(ctx);
+        } else if self.logged_in {
             // self.show_search(ctx);
+            self.handle_shortcuts(ctx);
             self.show_entry_list(ctx);
+            if self.show_help {
+                self.show_shortcut_help(ctx);
+            }
+            if self.palette.is_some() {
+                self.show_palette(ctx);
+            }
+            if self.folder_dialog.is_some() {
+                self.show_folder_dialog(ctx);
+            }
+            if self.attachment_dialog.is_some() {
+                self.show_attachment_dialog(ctx);
+            }
+            #[cfg(unix)]
+            {
+                self.sync_ssh_agent(ctx);
+                self.poll_ssh_agent();
+                self.show_ssh_agent_confirmation(ctx);
+                self.sync_browser_bridge(ctx);
+                self.poll_browser_bridge();
+                self.show_browser_approval(ctx);
+                self.sync_local_api(ctx);
+                self.poll_local_api();
+                self.show_local_api_approval(ctx);
+            }
+            #[cfg(target_os = "linux")]
+            {
+                self.sync_secret_service(ctx);
+                self.poll_secret_service();
+                self.show_secret_service_approval(ctx);
+            }
 
             egui::CentralPanel::default()
                 .frame(Frame {
@@ -1002,11 +1596,15 @@ impl eframe::App for Vault {
                     State::DisplayEntry => self.show_entry(ctx, ui),
                     State::EditEntry => self.edit_entry(ctx, ui),
                     State::AddingEntry => self.add_entry(ctx, ui),
+                    State::Trash => self.show_trash(ctx, ui),
+                    State::Audit => self.show_audit(ctx, ui),
                     State::None => {}
                     _ => {
-                        eprintln!("State {:#?} not implemented", self.state);
+                        log::warn!("State {:?} not implemented", self.state);
                     }
                 });
+        } else if self.master_error.is_some() {
+            self.show_master_recovery(ctx);
         } else if self.first_use {
             self.set_master(ctx);
         } else {
@@ -1020,6 +1618,8 @@ enum State {
     AddingEntry,
     DisplayEntry,
     EditEntry,
+    Trash,
+    Audit,
     #[default]
     None,
 }
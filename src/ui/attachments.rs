@@ -0,0 +1,224 @@
+use eframe::egui::{self, Layout, Ui};
+
+use crate::data_base::{
+    attachment::{self, format_size, read_source, Attachment, MAX_ATTACHMENT_SIZE},
+    audit::AuditAction,
+};
+
+use super::{Vault, BRIGHT_DEFAULT_GREY, RED};
+
+/// The open dialog for an attachment of the shown entry.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum AttachmentDialog {
+    Attach { path: String },
+    SaveAs { id: String, path: String },
+    Remove { id: String, name: String },
+}
+
+impl Vault {
+    /// Lists the attachments of the entry. Files dropped onto the window are
+    /// attached to it.
+    pub(super) fn show_attachments(&mut self, ui: &mut Ui, id: &str, attachments: &[Attachment]) {
+        ui.add_space(15.0);
+        ui.label(Vault::app_text(
+            "Attachments",
+            12.0,
+            None,
+            self.settings.mode.clone(),
+        ));
+
+        for attachment in attachments {
+            ui.with_layout(Layout::left_to_right(egui::Align::Center), |l_ui| {
+                l_ui.label(Vault::app_text(
+                    &attachment.name,
+                    16.0,
+                    None,
+                    self.settings.mode.clone(),
+                ));
+                l_ui.label(Vault::app_text(
+                    &format_size(attachment.size),
+                    12.0,
+                    Some(BRIGHT_DEFAULT_GREY),
+                    self.settings.mode.clone(),
+                ));
+
+                let save = Vault::app_button("Save As", 14.0, None, self.settings.mode.clone());
+                let remove =
+                    Vault::app_button("Remove", 14.0, Some(RED), self.settings.mode.clone());
+                if l_ui.add(save).clicked() {
+                    self.attachment_dialog = Some(AttachmentDialog::SaveAs {
+                        id: attachment.id.clone(),
+                        path: attachment.name.clone(),
+                    });
+                }
+                if l_ui.add(remove).clicked() {
+                    self.attachment_dialog = Some(AttachmentDialog::Remove {
+                        id: attachment.id.clone(),
+                        name: attachment.name.clone(),
+                    });
+                }
+            });
+        }
+
+        let attach = Vault::app_button("Attach File", 14.0, None, self.settings.mode.clone());
+        if ui.add(attach).clicked() {
+            self.attachment_dialog = Some(AttachmentDialog::Attach {
+                path: String::new(),
+            });
+        }
+        ui.label(Vault::app_text(
+            &format!(
+                "Files can also be dropped here, up to {} each",
+                format_size(MAX_ATTACHMENT_SIZE)
+            ),
+            12.0,
+            Some(BRIGHT_DEFAULT_GREY),
+            self.settings.mode.clone(),
+        ));
+
+        let dropped = ui.ctx().input(|i| i.raw.dropped_files.clone());
+        for file in dropped {
+            match (file.path, file.bytes) {
+                (Some(path), _) => {
+                    self.attach_file(id, &path.to_string_lossy());
+                }
+                (None, Some(bytes)) => {
+                    self.attach_data(id, &file.name, &bytes);
+                }
+                (None, None) => {}
+            }
+        }
+    }
+
+    pub(super) fn show_attachment_dialog(&mut self, ctx: &egui::Context) {
+        let (mut dialog, id) = match (self.attachment_dialog.take(), &self.selected_entry) {
+            (Some(d), Some(entry)) => (d, entry.id.clone()),
+            _ => return,
+        };
+
+        let title = match &dialog {
+            AttachmentDialog::Attach { .. } => "Attach File",
+            AttachmentDialog::SaveAs { .. } => "Save Attachment",
+            AttachmentDialog::Remove { .. } => "Remove Attachment",
+        };
+        let mut open = true;
+        let mut done = false;
+        egui::Window::new(title)
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| match &mut dialog {
+                AttachmentDialog::Attach { path } => {
+                    ui.label(Vault::app_text(
+                        "The path of the file",
+                        12.0,
+                        Some(BRIGHT_DEFAULT_GREY),
+                        self.settings.mode.clone(),
+                    ));
+                    let submitted = Vault::dialog_input(ui, path);
+                    let attach =
+                        Vault::app_button("Attach", 14.0, None, self.settings.mode.clone());
+                    if ui.add(attach).clicked() || submitted {
+                        done = self.attach_file(&id, path);
+                    }
+                }
+                AttachmentDialog::SaveAs {
+                    id: attachment_id,
+                    path,
+                } => {
+                    ui.label(Vault::app_text(
+                        "Where the decrypted file is saved",
+                        12.0,
+                        Some(BRIGHT_DEFAULT_GREY),
+                        self.settings.mode.clone(),
+                    ));
+                    let submitted = Vault::dialog_input(ui, path);
+                    let save = Vault::app_button("Save", 14.0, None, self.settings.mode.clone());
+                    if ui.add(save).clicked() || submitted {
+                        done = self.save_attachment(&id, attachment_id, path);
+                    }
+                }
+                AttachmentDialog::Remove {
+                    id: attachment_id,
+                    name,
+                } => {
+                    ui.label(Vault::app_text(
+                        &format!("Do you want to delete \"{}\" for good?", name),
+                        16.0,
+                        None,
+                        self.settings.mode.clone(),
+                    ));
+                    ui.add_space(15.0);
+                    let remove =
+                        Vault::app_button("Delete", 14.0, Some(RED), self.settings.mode.clone());
+                    if ui.add(remove).clicked() {
+                        done = self.remove_attachment(&id, attachment_id);
+                    }
+                }
+            });
+
+        if open && !done {
+            self.attachment_dialog = Some(dialog);
+        }
+    }
+
+    /// Returns if the file was attached.
+    fn attach_file(&mut self, id: &str, path: &str) -> bool {
+        match read_source(path.trim()) {
+            Ok((name, data)) => self.attach_data(id, &name, &data),
+            Err(err) => {
+                self.toasts.error(err.toast_message());
+                false
+            }
+        }
+    }
+
+    fn attach_data(&mut self, id: &str, name: &str, data: &[u8]) -> bool {
+        match self.repository.add_attachment(id, name, data) {
+            Ok(_) => {
+                self.toasts.success(format!("\"{}\" attached", name));
+                self.refresh_selected();
+                true
+            }
+            Err(err) => {
+                self.toasts.error(err.toast_message());
+                false
+            }
+        }
+    }
+
+    /// Returns if the file was saved.
+    fn save_attachment(&mut self, id: &str, attachment_id: &str, path: &str) -> bool {
+        let saved = self
+            .repository
+            .read_attachment(id, attachment_id)
+            .and_then(|data| attachment::export(path.trim(), &data));
+
+        match saved {
+            Ok(_) => {
+                self.repository.audit(AuditAction::Exported, Some(id));
+                self.toasts.success("Attachment saved");
+                true
+            }
+            Err(err) => {
+                self.toasts.error(err.toast_message());
+                false
+            }
+        }
+    }
+
+    /// Returns if the attachment was removed.
+    fn remove_attachment(&mut self, id: &str, attachment_id: &str) -> bool {
+        match self.repository.remove_attachment(id, attachment_id) {
+            Ok(_) => {
+                self.refresh_selected();
+                true
+            }
+            Err(err) => {
+                self.toasts.error(err.toast_message());
+                false
+            }
+        }
+    }
+}
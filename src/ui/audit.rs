@@ -0,0 +1,121 @@
+use eframe::egui::{self, Ui};
+
+use crate::{
+    data_base::audit::{ChainStatus, VerifiedRecord},
+    helpers::timestamp_as_date,
+};
+
+use super::{Vault, BRIGHT_DEFAULT_GREY, GREEN, RED};
+
+impl Vault {
+    /// Reads the audit log again and verifies its chain.
+    pub(super) fn load_audit(&mut self) {
+        let result = match self.repository.audit_log() {
+            Some(audit) => audit.verify(),
+            None => Ok(Vec::<VerifiedRecord>::new()),
+        };
+
+        match result {
+            Ok(records) => self.audit_records = records,
+            Err(err) => {
+                self.audit_records.clear();
+                self.toasts.error(err.toast_message());
+            }
+        }
+    }
+
+    pub(super) fn show_audit(&mut self, _ctx: &egui::Context, ui: &mut Ui) {
+        ui.label(Vault::app_text(
+            "Audit Log",
+            20.0,
+            None,
+            self.settings.mode.clone(),
+        ));
+
+        let broken = self
+            .audit_records
+            .iter()
+            .filter(|r| r.status != ChainStatus::Valid)
+            .count();
+        let (summary, color) = if broken == 0 {
+            (
+                format!("The chain is intact ({} records)", self.audit_records.len()),
+                GREEN,
+            )
+        } else {
+            (
+                format!(
+                    "{} of {} records break the chain",
+                    broken,
+                    self.audit_records.len()
+                ),
+                RED,
+            )
+        };
+        ui.label(Vault::app_text(
+            &summary,
+            14.0,
+            Some(color),
+            self.settings.mode.clone(),
+        ));
+
+        let verify = Vault::app_button("Verify again", 14.0, None, self.settings.mode.clone());
+        if ui.add(verify).clicked() {
+            self.load_audit();
+        }
+        ui.separator();
+
+        egui::ScrollArea::vertical()
+            .auto_shrink(false)
+            .show(ui, |sa_ui| {
+                for verified in self.audit_records.iter().rev() {
+                    let text = match &verified.record {
+                        Some(record) => {
+                            let entry = match &record.entry_id {
+                                Some(id) => match self.repository.get(id) {
+                                    Some(entry) => entry.name(),
+                                    None => id.clone(),
+                                },
+                                None => String::new(),
+                            };
+                            format!(
+                                "{} - {} - {} {}",
+                                timestamp_as_date(record.timestamp),
+                                record.actor,
+                                record.action.label(),
+                                entry
+                            )
+                        }
+                        None => String::from("Unreadable record"),
+                    };
+                    let (problem, color) = match verified.status {
+                        ChainStatus::Valid => (None, None),
+                        ChainStatus::Unreadable => {
+                            (Some("The record can not be decrypted"), Some(RED))
+                        }
+                        ChainStatus::Altered => (Some("The record was altered"), Some(RED)),
+                        ChainStatus::Broken => (
+                            Some("Records before this one were removed or reordered"),
+                            Some(RED),
+                        ),
+                    };
+
+                    sa_ui.label(Vault::app_text(
+                        &text,
+                        14.0,
+                        color,
+                        self.settings.mode.clone(),
+                    ));
+                    if let Some(problem) = problem {
+                        sa_ui.label(Vault::app_text(
+                            problem,
+                            12.0,
+                            Some(BRIGHT_DEFAULT_GREY),
+                            self.settings.mode.clone(),
+                        ));
+                    }
+                    sa_ui.separator();
+                }
+            });
+    }
+}
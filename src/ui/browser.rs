@@ -0,0 +1,223 @@
+use std::{
+    collections::VecDeque,
+    sync::mpsc::{self, Receiver},
+};
+
+use eframe::egui;
+
+use crate::{
+    data_base::{audit::AuditAction, kind::EntryKind},
+    native_messaging::{BrowserBridge, BrowserRequest, Credential, BRIDGE_SOCKET},
+};
+
+use super::{utils, Vault, BRIGHT_DEFAULT_GREY, GREEN, RED};
+
+mod test;
+
+/// The bridge while it runs, together with the requests it passed on.
+pub(super) struct BridgeState {
+    requests: Receiver<BrowserRequest>,
+    /// The requests waiting for the user, the first one is asked for.
+    approvals: VecDeque<Approval>,
+    /// Closes the socket once it is dropped, which waits for its clients. It
+    /// is dropped last, so no client still waits for an answer by then.
+    _bridge: BrowserBridge,
+}
+impl BridgeState {
+    /// Refuses every request still waiting before the bridge stops.
+    fn stop(self) {
+        for approval in self.approvals {
+            let _ = approval.request.reply.send(None);
+        }
+        for request in self.requests.try_iter() {
+            let _ = request.reply.send(None);
+        }
+        drop(self.requests);
+        drop(self._bridge);
+    }
+}
+
+/// Logins of a site the browser extension asked for.
+struct Approval {
+    /// The ids and names of the matching logins.
+    entries: Vec<(String, String)>,
+    request: BrowserRequest,
+}
+
+impl Vault {
+    /// Starts the bridge if it is turned on but not running yet. A failed
+    /// start turns it off until it is turned on again.
+    pub(super) fn sync_browser_bridge(&mut self, ctx: &egui::Context) {
+        if !self.settings.browser_bridge || self.browser_bridge.is_some() {
+            return;
+        }
+
+        let (sender, requests) = mpsc::channel();
+        let wake_ctx = ctx.clone();
+        let wake = move || wake_ctx.request_repaint();
+        match BrowserBridge::start(BRIDGE_SOCKET, sender, wake) {
+            Ok(bridge) => {
+                self.browser_bridge = Some(BridgeState {
+                    requests,
+                    approvals: VecDeque::new(),
+                    _bridge: bridge,
+                });
+            }
+            Err(err) => {
+                log::error!("Failed to start the browser bridge: {:?}", err);
+                self.settings.browser_bridge = false;
+                self.toasts.error(err.toast_message());
+            }
+        }
+    }
+
+    /// Stops the bridge, every request still waiting is refused.
+    pub(super) fn stop_browser_bridge(&mut self) {
+        if let Some(state) = self.browser_bridge.take() {
+            state.stop();
+        }
+    }
+
+    pub(super) fn toggle_browser_bridge(&mut self) {
+        self.settings.browser_bridge = !self.settings.browser_bridge;
+        if let Err(err) = utils::save_settings(&self.settings) {
+            self.toasts.error(err.toast_message());
+        }
+
+        match self.settings.browser_bridge {
+            true => self.toasts.info("Browser autofill turned on"),
+            false => {
+                self.stop_browser_bridge();
+                self.toasts.info("Browser autofill turned off");
+            }
+        }
+    }
+
+    /// Looks up the logins of the requested sites, which all wait for the
+    /// user. Sites without any wait as well, so the browser can not learn
+    /// which sites have logins without the user allowing it.
+    pub(super) fn poll_browser_bridge(&mut self) {
+        let requests = match &self.browser_bridge {
+            Some(state) => state.requests.try_iter().collect::<Vec<_>>(),
+            None => return,
+        };
+
+        for request in requests {
+            let entries = self
+                .repository
+                .find_by_origin(&request.origin)
+                .into_iter()
+                .filter(|e| e.kind() == EntryKind::Login)
+                .map(|e| (e.id(), e.name()))
+                .collect::<Vec<(String, String)>>();
+
+            match self.browser_bridge.as_mut() {
+                Some(state) => state.approvals.push_back(Approval { entries, request }),
+                None => {
+                    let _ = request.reply.send(None);
+                }
+            }
+        }
+    }
+
+    /// Asks the user to allow the first waiting request.
+    pub(super) fn show_browser_approval(&mut self, ctx: &egui::Context) {
+        let approval = match self.browser_bridge.as_mut() {
+            Some(state) => match state.approvals.pop_front() {
+                Some(a) => a,
+                None => return,
+            },
+            None => return,
+        };
+
+        let mut open = true;
+        let mut allowed = None;
+        egui::Window::new("Browser Autofill")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(Vault::app_text(
+                    &format!(
+                        "Send the logins of {} to the browser?",
+                        approval.request.origin
+                    ),
+                    16.0,
+                    None,
+                    self.settings.mode.clone(),
+                ));
+                ui.label(Vault::app_text(
+                    &format!("Asked for by {}", approval.request.extension),
+                    12.0,
+                    Some(BRIGHT_DEFAULT_GREY),
+                    self.settings.mode.clone(),
+                ));
+                ui.add_space(10.0);
+                if approval.entries.is_empty() {
+                    ui.label(Vault::app_text(
+                        "No login is stored for this site",
+                        14.0,
+                        Some(BRIGHT_DEFAULT_GREY),
+                        self.settings.mode.clone(),
+                    ));
+                }
+                for (_, name) in &approval.entries {
+                    ui.label(Vault::app_text(
+                        name,
+                        14.0,
+                        None,
+                        self.settings.mode.clone(),
+                    ));
+                }
+                ui.add_space(15.0);
+                ui.horizontal(|h_ui| {
+                    let allow =
+                        Vault::app_button("Allow", 14.0, Some(GREEN), self.settings.mode.clone());
+                    let deny =
+                        Vault::app_button("Deny", 14.0, Some(RED), self.settings.mode.clone());
+                    if h_ui.add(allow).clicked() {
+                        allowed = Some(true);
+                    }
+                    if h_ui.add(deny).clicked() {
+                        allowed = Some(false);
+                    }
+                });
+            });
+
+        match (allowed, open) {
+            (Some(true), _) => self.send_credentials(approval),
+            (Some(false), _) | (None, false) => {
+                let _ = approval.request.reply.send(None);
+            }
+            (None, true) => {
+                if let Some(state) = self.browser_bridge.as_mut() {
+                    state.approvals.push_front(approval);
+                }
+            }
+        }
+    }
+
+    fn send_credentials(&mut self, approval: Approval) {
+        let mut credentials = Vec::<Credential>::new();
+        for (id, _) in &approval.entries {
+            if let Some(details) = self.entry_details(id) {
+                credentials.push(Credential {
+                    name: details.name.clone(),
+                    username: details.user_name.expose().clone(),
+                    password: details.password.expose().clone(),
+                });
+            }
+        }
+
+        if approval.request.reply.send(Some(credentials)).is_err() {
+            self.toasts
+                .info("The browser no longer waits for the logins");
+            return;
+        }
+        for (id, _) in &approval.entries {
+            self.repository.audit(AuditAction::SentToBrowser, Some(id));
+            self.mark_used(id);
+        }
+    }
+}
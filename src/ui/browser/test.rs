@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::VecDeque,
+        sync::mpsc,
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use crate::{
+        native_messaging::{forward, BridgeRequest, BrowserBridge, Refusal, Request, Response},
+        ui::browser::{Approval, BridgeState},
+    };
+
+    #[test]
+    fn stopping_refuses_waiting_requests() {
+        let path =
+            std::env::temp_dir().join(format!("vault-browser-{}.sock", uuid::Uuid::new_v4()));
+        let (sender, requests) = mpsc::channel();
+        let bridge = BrowserBridge::start(&path, sender, || {}).unwrap();
+
+        let client = {
+            let path = path.clone();
+            thread::spawn(move || {
+                let request = BridgeRequest {
+                    extension: String::from("test"),
+                    request: Request::Credentials {
+                        origin: String::from("https://github.com"),
+                    },
+                };
+                forward(&path, &request)
+            })
+        };
+        let request = requests.recv_timeout(Duration::from_secs(5)).unwrap();
+        let state = BridgeState {
+            requests,
+            approvals: VecDeque::from([Approval {
+                entries: Vec::new(),
+                request,
+            }]),
+            _bridge: bridge,
+        };
+
+        let stopping = Instant::now();
+        state.stop();
+        assert!(
+            stopping.elapsed() < Duration::from_secs(5),
+            "\n!!! Stopping waited for the user to answer"
+        );
+        assert_eq!(
+            client.join().unwrap(),
+            Response::Error {
+                error: Refusal::Denied
+            },
+            "\n!!! The waiting request was not refused"
+        );
+    }
+}
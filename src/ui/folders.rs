@@ -0,0 +1,396 @@
+use std::collections::HashMap;
+
+use eframe::egui::{self, Layout, Ui};
+
+use crate::data_base::{
+    folder::{self, save_folders, FolderContents, FolderNode},
+    history::Action,
+};
+
+use super::{toast::ToastAction, State, Vault, BRIGHT_DEFAULT_GREY, DEFAULT_COLOR, RED};
+
+/// The payload of an entry which is dragged onto a folder.
+pub(super) struct EntryDrag(pub String);
+
+/// The open dialog to change a folder.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum FolderDialog {
+    Create { parent: String, name: String },
+    Rename { path: String, name: String },
+    Delete { path: String },
+}
+
+impl Vault {
+    /// Shows the folders with their entries, followed by the entries on the
+    /// top level. Returns the id of the clicked entry.
+    pub(super) fn show_folder_tree(
+        &mut self,
+        ui: &mut Ui,
+        names: &HashMap<String, String>,
+    ) -> Option<String> {
+        let entries = self.repository.sorted(self.settings.sort_mode);
+        let tree = FolderNode::build(&self.folders, &entries);
+
+        let mut clicked = None;
+        let mut dropped = None;
+
+        let top = ui.label(Vault::app_text(
+            "All Entries",
+            14.0,
+            Some(BRIGHT_DEFAULT_GREY),
+            self.settings.mode.clone(),
+        ));
+        if let Some(drag) = top.dnd_release_payload::<EntryDrag>() {
+            dropped = Some((drag.0.clone(), String::new()));
+        }
+        top.context_menu(|menu| {
+            if menu.button("New Folder").clicked() {
+                self.folder_dialog = Some(FolderDialog::Create {
+                    parent: String::new(),
+                    name: String::new(),
+                });
+                menu.close_menu();
+            }
+        });
+
+        self.show_folder(ui, &tree, names, &mut clicked, &mut dropped);
+
+        if let Some((id, path)) = dropped {
+            self.move_entry(&id, &path);
+        }
+
+        clicked
+    }
+
+    fn show_folder(
+        &mut self,
+        ui: &mut Ui,
+        node: &FolderNode,
+        names: &HashMap<String, String>,
+        clicked: &mut Option<String>,
+        dropped: &mut Option<(String, String)>,
+    ) {
+        for child in node.folders.iter() {
+            // the folder of the highlighted entry is opened, so it can be seen
+            let open = match (&self.list_cursor, self.scroll_to_cursor) {
+                (Some(id), true) if child.contains(id) => Some(true),
+                _ => None,
+            };
+            let title = format!("{} ({})", child.name(), child.count());
+            let response = egui::CollapsingHeader::new(Vault::app_text(
+                &title,
+                16.0,
+                None,
+                self.settings.mode.clone(),
+            ))
+            .id_source(("Folder", &child.path))
+            .open(open)
+            .show(ui, |c_ui| {
+                self.show_folder(c_ui, child, names, clicked, dropped);
+            });
+
+            let header = response.header_response;
+            if header.dnd_hover_payload::<EntryDrag>().is_some() {
+                ui.painter()
+                    .rect_stroke(header.rect, 2.0, egui::Stroke::new(1.0, DEFAULT_COLOR));
+            }
+            if let Some(drag) = header.dnd_release_payload::<EntryDrag>() {
+                *dropped = Some((drag.0.clone(), child.path.clone()));
+            }
+            header.context_menu(|menu| {
+                if menu.button("New Subfolder").clicked() {
+                    self.folder_dialog = Some(FolderDialog::Create {
+                        parent: child.path.clone(),
+                        name: String::new(),
+                    });
+                    menu.close_menu();
+                }
+                if menu.button("Rename").clicked() {
+                    self.folder_dialog = Some(FolderDialog::Rename {
+                        path: child.path.clone(),
+                        name: String::from(child.name()),
+                    });
+                    menu.close_menu();
+                }
+                if menu.button("Delete").clicked() {
+                    self.folder_dialog = Some(FolderDialog::Delete {
+                        path: child.path.clone(),
+                    });
+                    menu.close_menu();
+                }
+            });
+        }
+
+        let items = node
+            .entries
+            .iter()
+            .filter_map(|id| names.get(id).map(|name| (id.clone(), name.clone())))
+            .collect::<Vec<(String, String)>>();
+        if let Some(id) = self.list_entries(ui, items, true) {
+            *clicked = Some(id);
+        }
+    }
+
+    /// Moves the entry into the folder, which can be undone.
+    fn move_entry(&mut self, id: &str, path: &str) {
+        let before = match self.repository.get(id) {
+            Some(entry) if entry.folder() != path => entry.clone(),
+            _ => return,
+        };
+
+        match self.repository.move_to_folder(id, path) {
+            Ok(after) => {
+                let after = after.clone();
+                self.history.record(Action::Edited {
+                    before: Box::new(before),
+                    after: Box::new(after),
+                });
+                let target = match path.is_empty() {
+                    true => String::from("the top level"),
+                    false => format!("\"{}\"", path),
+                };
+                self.toasts
+                    .with_action(format!("Moved to {}", target), ToastAction::Undo);
+                self.refresh_selected();
+            }
+            Err(err) => self.toasts.error(err.toast_message()),
+        }
+    }
+
+    pub(super) fn show_folder_dialog(&mut self, ctx: &egui::Context) {
+        let mut dialog = match self.folder_dialog.take() {
+            Some(d) => d,
+            None => return,
+        };
+
+        let title = match &dialog {
+            FolderDialog::Create { .. } => "New Folder",
+            FolderDialog::Rename { .. } => "Rename Folder",
+            FolderDialog::Delete { .. } => "Delete Folder",
+        };
+        let mut open = true;
+        let mut done = false;
+        egui::Window::new(title)
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| match &mut dialog {
+                FolderDialog::Create { parent, name } => {
+                    if !parent.is_empty() {
+                        ui.label(Vault::app_text(
+                            &format!("Inside \"{}\"", parent),
+                            12.0,
+                            Some(BRIGHT_DEFAULT_GREY),
+                            self.settings.mode.clone(),
+                        ));
+                    }
+                    let submitted = Vault::dialog_input(ui, name);
+                    let create =
+                        Vault::app_button("Create", 14.0, None, self.settings.mode.clone());
+                    if ui.add(create).clicked() || submitted {
+                        done = self.create_folder(&format!("{}/{}", parent, name));
+                    }
+                }
+                FolderDialog::Rename { path, name } => {
+                    let submitted = Vault::dialog_input(ui, name);
+                    let rename =
+                        Vault::app_button("Rename", 14.0, None, self.settings.mode.clone());
+                    if ui.add(rename).clicked() || submitted {
+                        let to = format!("{}/{}", folder::parent(path), name);
+                        done = self.rename_folder(path, &to);
+                    }
+                }
+                FolderDialog::Delete { path } => {
+                    done = self.show_delete_folder(ui, path);
+                }
+            });
+
+        if open && !done {
+            self.folder_dialog = Some(dialog);
+        }
+    }
+
+    /// The input of a dialog, which has the focus while the dialog is open.
+    /// Returns if the text was submitted with enter.
+    pub(super) fn dialog_input(ui: &mut Ui, name: &mut String) -> bool {
+        let input = ui.text_edit_singleline(name);
+        if ui.memory(|m| m.focused().is_none()) {
+            input.request_focus();
+        }
+
+        input.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter))
+    }
+
+    /// Asks what happens to the entries of the folder. Returns if the
+    /// dialog is done.
+    fn show_delete_folder(&mut self, ui: &mut Ui, path: &str) -> bool {
+        let count = self
+            .repository
+            .list()
+            .into_iter()
+            .filter(|e| folder::is_inside(&e.folder(), path))
+            .count();
+        let parent = match folder::parent(path) {
+            p if p.is_empty() => String::from("the top level"),
+            p => format!("\"{}\"", p),
+        };
+
+        let question = match count {
+            0 => format!("Do you want to delete the folder \"{}\"?", path),
+            _ => format!(
+                "The folder \"{}\" holds {} entries. What should happen to them?",
+                path, count
+            ),
+        };
+        ui.label(Vault::app_text(
+            &question,
+            16.0,
+            None,
+            self.settings.mode.clone(),
+        ));
+        ui.add_space(15.0);
+
+        let move_text = format!("Move them to {}", parent);
+        let (move_up, trash) = match count {
+            0 => (
+                Vault::app_button("Delete", 14.0, Some(RED), self.settings.mode.clone()),
+                None,
+            ),
+            _ => (
+                Vault::app_button(&move_text, 14.0, None, self.settings.mode.clone()),
+                Some(Vault::app_button(
+                    "Move them to the Trash",
+                    14.0,
+                    Some(RED),
+                    self.settings.mode.clone(),
+                )),
+            ),
+        };
+
+        let chosen = ui
+            .with_layout(Layout::left_to_right(egui::Align::Min), |b_ui| {
+                if b_ui.add(move_up).clicked() {
+                    return Some(FolderContents::MoveToParent);
+                }
+                match trash.map(|t| b_ui.add(t).clicked()) {
+                    Some(true) => Some(FolderContents::Trash),
+                    _ => None,
+                }
+            })
+            .inner;
+
+        match chosen {
+            Some(contents) => self.delete_folder(path, contents),
+            None => false,
+        }
+    }
+
+    /// Returns if the folder was created.
+    fn create_folder(&mut self, path: &str) -> bool {
+        let path = folder::normalize(path);
+        if path.is_empty() {
+            self.toasts.error("Please enter a name for the folder");
+            return false;
+        }
+
+        if !self.folders.contains(&path) {
+            self.folders.push(path);
+        }
+        self.save_folders();
+
+        true
+    }
+
+    /// Returns if the folder was renamed.
+    fn rename_folder(&mut self, from: &str, to: &str) -> bool {
+        let to = folder::normalize(to);
+        if to.is_empty() {
+            self.toasts.error("Please enter a name for the folder");
+            return false;
+        }
+
+        match self.repository.rename_folder(from, &to) {
+            Ok(_) => {
+                self.folders = self
+                    .folders
+                    .iter()
+                    .map(|f| folder::renamed(f, from, &to).unwrap_or(f.clone()))
+                    .collect();
+                self.folders.sort();
+                self.folders.dedup();
+                self.save_folders();
+                self.refresh_selected();
+                true
+            }
+            Err(err) => {
+                self.toasts.error(err.toast_message());
+                false
+            }
+        }
+    }
+
+    /// Returns if the folder was deleted.
+    fn delete_folder(&mut self, path: &str, contents: FolderContents) -> bool {
+        match self.repository.delete_folder(path, contents) {
+            Ok(count) => {
+                let parent = folder::parent(path);
+                self.folders = self
+                    .folders
+                    .iter()
+                    .filter(|f| f.as_str() != path)
+                    .filter_map(|f| match contents {
+                        FolderContents::MoveToParent => {
+                            Some(folder::renamed(f, path, &parent).unwrap_or(f.clone()))
+                        }
+                        FolderContents::Trash if folder::is_inside(f, path) => None,
+                        FolderContents::Trash => Some(f.clone()),
+                    })
+                    .collect();
+                // folders moved into the parent can meet ones anywhere in it
+                self.folders.sort();
+                self.folders.dedup();
+                self.save_folders();
+                self.refresh_selected();
+                if contents == FolderContents::Trash && count > 0 {
+                    self.toasts
+                        .info(format!("{} entries were moved to the Trash", count));
+                }
+                true
+            }
+            Err(err) => {
+                self.toasts.error(err.toast_message());
+                false
+            }
+        }
+    }
+
+    pub(super) fn save_folders(&mut self) {
+        if let Err(err) = save_folders(&self.folders) {
+            self.toasts.error(err.toast_message());
+        }
+    }
+
+    /// Loads the open entry again after it was changed from the outside, or
+    /// closes it if it is not listed anymore.
+    pub(super) fn refresh_selected(&mut self) {
+        let id = match &self.selected_entry {
+            Some(entry) => entry.id.clone(),
+            None => return,
+        };
+
+        match self
+            .repository
+            .get(&id)
+            .map(|e| (e.is_deleted(), e.get_details()))
+        {
+            Some((false, Ok(details))) => self.selected_entry = Some(details),
+            _ => {
+                self.selected_entry = None;
+                if self.state == State::DisplayEntry || self.state == State::EditEntry {
+                    self.state = State::None;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use crate::{data_base::history::Action, error::InternalError};
+
+use super::{toast::ToastAction, State, Vault};
+
+impl Vault {
+    pub(super) fn undo(&mut self) {
+        let result = self.history.undo(&mut self.repository);
+        self.after_history_change(result, "Undone");
+    }
+
+    pub(super) fn redo(&mut self) {
+        let result = self.history.redo(&mut self.repository);
+        self.after_history_change(result, "Redone");
+    }
+
+    /// Shows the entry the undone or redone action was about, if it is still
+    /// part of the list.
+    fn after_history_change(&mut self, result: Result<Option<Action>, InternalError>, done: &str) {
+        self.toasts.dismiss_action(ToastAction::Undo);
+
+        match result {
+            Ok(Some(action)) => {
+                self.password_visible = false;
+                self.delete_dialog = false;
+                self.purge_dialog = None;
+
+                let shown = match self.repository.get(&action.id()) {
+                    Some(entry) if !entry.is_deleted() => self.entry_details(&action.id()),
+                    _ => None,
+                };
+                match shown {
+                    Some(details) => {
+                        self.selected_entry = Some(details);
+                        self.state = State::DisplayEntry;
+                    }
+                    None => {
+                        self.selected_entry = None;
+                        if self.state != State::Trash {
+                            self.state = State::None;
+                        }
+                    }
+                }
+
+                self.toasts
+                    .info(format!("{}: {}", done, action.description()));
+            }
+            Ok(None) => {}
+            Err(err) => {
+                self.toasts
+                    .error(format!("{}\nPlease try again", err.toast_message()));
+            }
+        }
+    }
+}
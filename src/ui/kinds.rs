@@ -0,0 +1,196 @@
+use eframe::egui::{self, Label, Layout, Ui};
+
+use crate::data_base::{
+    audit::AuditAction,
+    kind::{EntryKind, Field, Payload},
+};
+
+use super::{Mode, Vault, BRIGHT_BG, DARK_BG};
+
+impl Vault {
+    /// The selection of the kind of a new entry. Changing it starts with an
+    /// empty payload.
+    pub(super) fn kind_selector(&mut self, ui: &mut Ui) {
+        let mut kind = self.input_kind;
+        ui.with_layout(Layout::left_to_right(egui::Align::Center), |l_ui| {
+            l_ui.label(Vault::app_text(
+                "Kind",
+                14.0,
+                None,
+                self.settings.mode.clone(),
+            ));
+            egui::ComboBox::from_id_source("Entry Kind")
+                .selected_text(kind.label())
+                .show_ui(l_ui, |c_ui| {
+                    for option in EntryKind::ALL {
+                        c_ui.selectable_value(&mut kind, option, option.label());
+                    }
+                });
+        });
+        ui.add_space(5.0);
+
+        if kind != self.input_kind {
+            self.input_kind = kind;
+            self.input_payload = Payload::empty(kind);
+        }
+    }
+
+    /// The inputs for the fields of every kind besides logins, which keep
+    /// their own form.
+    pub(super) fn payload_form(&mut self, ui: &mut Ui) {
+        let fields = self.input_payload.kind().fields();
+        let visibility_text = if self.password_visible {
+            "Hide"
+        } else {
+            "Show"
+        };
+        let mut toggled = false;
+
+        for (field, value) in fields.iter().zip(self.input_payload.values_mut()) {
+            ui.label(Vault::app_text(
+                field.label,
+                12.0,
+                None,
+                self.settings.mode.clone(),
+            ));
+            ui.with_layout(Layout::left_to_right(egui::Align::Min), |l_ui| {
+                Vault::line_edit_frame(&self.settings.mode).show(l_ui, |f_ui| {
+                    Vault::field_edit(
+                        f_ui,
+                        value,
+                        field,
+                        self.password_visible,
+                        &self.settings.mode,
+                    )
+                });
+                if field.secret {
+                    let visibility =
+                        Vault::app_button(visibility_text, 14.0, None, self.settings.mode.clone());
+                    if l_ui.add(visibility).clicked() {
+                        toggled = true;
+                    }
+                }
+            });
+            ui.separator();
+        }
+
+        if toggled {
+            self.password_visible = !self.password_visible;
+        }
+    }
+
+    fn field_edit(
+        ui: &mut Ui,
+        text: &mut String,
+        field: &Field,
+        password_visible: bool,
+        mode: &Mode,
+    ) -> egui::Response {
+        let (edit, font) = match field.multiline {
+            true => (
+                egui::TextEdit::multiline(text).desired_rows(4),
+                egui::TextStyle::Monospace,
+            ),
+            false => (egui::TextEdit::singleline(text), egui::TextStyle::Heading),
+        };
+        let edit = edit
+            .frame(false)
+            .password(field.secret && !password_visible)
+            .hint_text(field.hint)
+            .text_color(match mode {
+                Mode::Dark => BRIGHT_BG,
+                Mode::Bright => DARK_BG,
+            })
+            .font(font);
+
+        ui.add(edit)
+    }
+
+    /// Shows the fields of the payload which have a value, secrets only
+    /// while they are revealed. Returns the index of the copied field and if
+    /// the secrets were shown or hidden.
+    pub(super) fn show_payload(
+        ui: &mut Ui,
+        payload: &Payload,
+        password_visible: bool,
+        mode: &Mode,
+    ) -> (Option<usize>, bool) {
+        let fields = payload.kind().fields();
+        let visibility_text = if password_visible { "Hide" } else { "Show" };
+        let mut copied = None;
+        let mut toggled = false;
+
+        for (index, (field, value)) in fields.iter().zip(payload.values()).enumerate() {
+            if value.is_empty() {
+                continue;
+            }
+
+            ui.label(Vault::app_text(field.label, 12.0, None, mode.clone()));
+            ui.with_layout(
+                Layout::left_to_right(egui::Align::Min).with_main_wrap(true),
+                |l_ui| {
+                    let text = match field.secret && !password_visible {
+                        true => "********",
+                        false => value.as_str(),
+                    };
+                    let size = match field.multiline {
+                        true => 14.0,
+                        false => 18.0,
+                    };
+                    let text_label = Label::new(Vault::app_text(text, size, None, mode.clone()))
+                        .wrap_mode(egui::TextWrapMode::Wrap);
+                    l_ui.add(text_label);
+
+                    let copy = Vault::app_button("Copy", 14.0, None, mode.clone());
+                    if l_ui.add(copy).clicked() {
+                        copied = Some(index);
+                    }
+                    if field.secret {
+                        let visibility =
+                            Vault::app_button(visibility_text, 14.0, None, mode.clone());
+                        if l_ui.add(visibility).clicked() {
+                            toggled = true;
+                        }
+                    }
+                },
+            );
+            ui.separator();
+        }
+
+        (copied, toggled)
+    }
+
+    /// Copies one field of an entry, which is audited like its username or
+    /// password depending on if the field is secret.
+    pub(super) fn copy_payload_field(&mut self, id: &str, index: usize) {
+        let details = match self.entry_details(id) {
+            Some(details) => details,
+            None => return,
+        };
+        let payload = details.payload.expose();
+        let (field, value) = match (
+            payload.kind().fields().get(index),
+            payload.values().get(index),
+        ) {
+            (Some(field), Some(value)) => (field, value.as_str()),
+            _ => return,
+        };
+
+        let action = match field.secret {
+            true => AuditAction::CopiedPassword,
+            false => AuditAction::CopiedUserName,
+        };
+        self.write_clipboard(id, value, action, &format!("{} copied", field.label));
+    }
+
+    /// The input as the payload to store, logins use their own inputs.
+    pub(super) fn input_as_payload(&self) -> Payload {
+        match self.input_kind {
+            EntryKind::Login => Payload::Login {
+                user_name: self.input_user_name.clone(),
+                password: self.input_password.clone(),
+            },
+            _ => self.input_payload.clone(),
+        }
+    }
+}
@@ -0,0 +1,333 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::mpsc::{self, Receiver},
+};
+
+use eframe::egui;
+
+use crate::{
+    data_base::audit::AuditAction,
+    local_api::{ApiCall, ApiRefusal, ApiRequest, ApiResponse, LocalApi, API_SOCKET, TOKEN_PATH},
+    local_socket::Peer,
+};
+
+use super::{utils, Vault, BRIGHT_DEFAULT_GREY, GREEN, RED};
+
+mod test;
+
+/// The API while it runs, together with the calls it passed on.
+pub(super) struct ApiState {
+    calls: Receiver<ApiCall>,
+    /// The calls waiting for the user, the first one is asked for.
+    approvals: VecDeque<Approval>,
+    /// What the user allowed each client process until the vault is locked.
+    /// Nothing is remembered for a client whose process is unknown.
+    grants: HashMap<Peer, HashSet<Scope>>,
+    /// Stops the API once it is dropped, which waits for its clients. It is
+    /// dropped last, so no client still waits for an answer by then.
+    _api: LocalApi,
+}
+impl ApiState {
+    /// Refuses every call still waiting before the API stops.
+    fn stop(self) {
+        for approval in self.approvals {
+            let _ = approval.call.reply.send(ApiRefusal::Locked.into());
+        }
+        for call in self.calls.try_iter() {
+            let _ = call.reply.send(ApiRefusal::Locked.into());
+        }
+        drop(self.calls);
+        drop(self._api);
+    }
+}
+
+/// What a client can be allowed to do.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Scope {
+    /// Listing the names of all entries.
+    List,
+    /// Reading the fields of the entry with the id.
+    Read(String),
+    /// Reading the fields of an entry with a name no single entry has.
+    /// Whether there is such an entry is only told after the user allowed it.
+    Missing(String),
+}
+
+/// A call which needs the approval of the user.
+struct Approval {
+    scope: Scope,
+    call: ApiCall,
+}
+
+impl Vault {
+    /// Starts the API if it is turned on but not running yet. A failed start
+    /// turns it off until it is turned on again.
+    pub(super) fn sync_local_api(&mut self, ctx: &egui::Context) {
+        if !self.settings.local_api || self.local_api.is_some() {
+            return;
+        }
+
+        let (sender, calls) = mpsc::channel();
+        let wake_ctx = ctx.clone();
+        let wake = move || wake_ctx.request_repaint();
+        match LocalApi::start(API_SOCKET, TOKEN_PATH, sender, wake) {
+            Ok(api) => {
+                self.local_api = Some(ApiState {
+                    calls,
+                    approvals: VecDeque::new(),
+                    grants: HashMap::new(),
+                    _api: api,
+                });
+            }
+            Err(err) => {
+                log::error!("Failed to start the local API: {:?}", err);
+                self.settings.local_api = false;
+                self.toasts.error(err.toast_message());
+            }
+        }
+    }
+
+    /// Stops the API, every call still waiting is refused and every client
+    /// has to be allowed again.
+    pub(super) fn stop_local_api(&mut self) {
+        if let Some(state) = self.local_api.take() {
+            state.stop();
+        }
+    }
+
+    pub(super) fn toggle_local_api(&mut self) {
+        self.settings.local_api = !self.settings.local_api;
+        if let Err(err) = utils::save_settings(&self.settings) {
+            self.toasts.error(err.toast_message());
+        }
+
+        match self.settings.local_api {
+            true => self
+                .toasts
+                .info(format!("Local API listening on {}", API_SOCKET)),
+            false => {
+                self.stop_local_api();
+                self.toasts.info("Local API stopped");
+            }
+        }
+    }
+
+    /// Answers the calls the clients are allowed to make, the others wait for
+    /// the user.
+    pub(super) fn poll_local_api(&mut self) {
+        let calls = match &self.local_api {
+            Some(state) => state.calls.try_iter().collect::<Vec<_>>(),
+            None => return,
+        };
+
+        for call in calls {
+            let scope = match &call.request {
+                ApiRequest::List => Scope::List,
+                ApiRequest::Get { entry, .. } => match self.entry_named(entry) {
+                    Some(id) => Scope::Read(id),
+                    None => Scope::Missing(entry.clone()),
+                },
+            };
+            let granted = self.local_api.as_ref().is_some_and(|state| {
+                call.peer
+                    .as_ref()
+                    .and_then(|peer| state.grants.get(peer))
+                    .is_some_and(|g| g.contains(&scope))
+            });
+            match (granted, self.local_api.as_mut()) {
+                (true, _) => self.answer_api_call(call, &scope),
+                (false, Some(state)) => state.approvals.push_back(Approval { scope, call }),
+                (false, None) => {
+                    let _ = call.reply.send(ApiRefusal::Locked.into());
+                }
+            }
+        }
+    }
+
+    /// The id of the only entry with the name. Which of several entries is
+    /// meant can not be told.
+    fn entry_named(&self, name: &str) -> Option<String> {
+        let matching = self
+            .repository
+            .list()
+            .into_iter()
+            .filter(|e| e.name() == name)
+            .collect::<Vec<_>>();
+        match matching.as_slice() {
+            [entry] => Some(entry.id()),
+            _ => None,
+        }
+    }
+
+    /// Reads the field of the entry with the id.
+    fn read_api_field(&self, id: &str, field: &str) -> Result<String, ApiRefusal> {
+        let details = match self.repository.get(id).map(|e| e.get_details()) {
+            Some(Ok(details)) => details,
+            Some(Err(err)) => {
+                log::error!("Failed to decrypt {} for the local API: {:?}", id, err);
+                return Err(ApiRefusal::NotFound);
+            }
+            None => return Err(ApiRefusal::NotFound),
+        };
+
+        match details.payload.expose().field(field) {
+            Some(value) => Ok(String::from(value)),
+            None => Err(ApiRefusal::UnknownField),
+        }
+    }
+
+    fn answer_api_call(&mut self, call: ApiCall, scope: &Scope) {
+        let mut read = false;
+        let response = match (&call.request, scope) {
+            (ApiRequest::Get { field, .. }, Scope::Read(id)) => {
+                match self.read_api_field(id, field) {
+                    Ok(value) => {
+                        read = true;
+                        ApiResponse::Field { value }
+                    }
+                    Err(refusal) => refusal.into(),
+                }
+            }
+            (ApiRequest::Get { .. }, _) => ApiRefusal::NotFound.into(),
+            (ApiRequest::List, _) => {
+                let mut names = self
+                    .repository
+                    .list()
+                    .iter()
+                    .map(|e| e.name())
+                    .collect::<Vec<String>>();
+                names.sort();
+                ApiResponse::Names { names }
+            }
+        };
+
+        let client = call.label();
+        if call.reply.send(response).is_err() {
+            log::info!("Local API: {} no longer waits", client);
+            return;
+        }
+        match (scope, read) {
+            (Scope::List, _) => {
+                log::info!("Local API: sent the names to {}", client);
+                self.repository
+                    .audit_for_client(AuditAction::ListedByApi, None, &client);
+            }
+            (Scope::Read(id), true) => {
+                log::info!("Local API: sent a field of {} to {}", id, client);
+                self.repository
+                    .audit_for_client(AuditAction::ReadByApi, Some(id), &client);
+                self.mark_used(id);
+            }
+            (Scope::Read(id), false) => {
+                log::info!("Local API: {} asked for a missing field of {}", client, id);
+            }
+            (Scope::Missing(name), _) => {
+                log::info!("Local API: {} asked for the missing {:?}", client, name);
+            }
+        }
+    }
+
+    /// Asks the user to allow the first waiting call. Allowing it also
+    /// allows the same calls of the client process until the vault is
+    /// locked.
+    pub(super) fn show_local_api_approval(&mut self, ctx: &egui::Context) {
+        let approval = match self.local_api.as_mut() {
+            Some(state) => match state.approvals.pop_front() {
+                Some(a) => a,
+                None => return,
+            },
+            None => return,
+        };
+
+        let wish = match &approval.call.request {
+            ApiRequest::List => String::from("list the names of all entries"),
+            ApiRequest::Get { entry, .. } => format!("read the fields of \"{}\"", entry),
+        };
+        let remembered = match &approval.call.peer {
+            Some(_) => "This program keeps this access until the vault is locked",
+            None => "The program is unknown, so it is asked again next time",
+        };
+        let mut open = true;
+        let mut allowed = None;
+        egui::Window::new("Local API")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(Vault::app_text(
+                    &format!("Allow {} to {}?", approval.call.label(), wish),
+                    16.0,
+                    None,
+                    self.settings.mode.clone(),
+                ));
+                ui.label(Vault::app_text(
+                    remembered,
+                    12.0,
+                    Some(BRIGHT_DEFAULT_GREY),
+                    self.settings.mode.clone(),
+                ));
+                ui.add_space(15.0);
+                ui.horizontal(|h_ui| {
+                    let allow =
+                        Vault::app_button("Allow", 14.0, Some(GREEN), self.settings.mode.clone());
+                    let deny =
+                        Vault::app_button("Deny", 14.0, Some(RED), self.settings.mode.clone());
+                    if h_ui.add(allow).clicked() {
+                        allowed = Some(true);
+                    }
+                    if h_ui.add(deny).clicked() {
+                        allowed = Some(false);
+                    }
+                });
+            });
+
+        match (allowed, open) {
+            (Some(true), _) => self.grant_api_scope(approval),
+            (Some(false), _) | (None, false) => {
+                let id = match &approval.scope {
+                    Scope::Read(id) => Some(id.as_str()),
+                    Scope::List | Scope::Missing(_) => None,
+                };
+                let client = approval.call.label();
+                log::info!("Local API: denied {:?} to {}", approval.scope, client);
+                self.repository
+                    .audit_for_client(AuditAction::RefusedToApi, id, &client);
+                let _ = approval.call.reply.send(ApiRefusal::Denied.into());
+            }
+            (None, true) => {
+                if let Some(state) = self.local_api.as_mut() {
+                    state.approvals.push_front(approval);
+                }
+            }
+        }
+    }
+
+    /// Answers the call together with all waiting calls the grant covers.
+    fn grant_api_scope(&mut self, approval: Approval) {
+        let covered = match self.local_api.as_mut() {
+            Some(state) => match &approval.call.peer {
+                Some(peer) => {
+                    state
+                        .grants
+                        .entry(peer.clone())
+                        .or_default()
+                        .insert(approval.scope.clone());
+                    let (covered, waiting) =
+                        state.approvals.drain(..).partition::<Vec<_>, _>(|a| {
+                            a.scope == approval.scope && a.call.peer.as_ref() == Some(peer)
+                        });
+                    state.approvals = waiting.into();
+                    covered
+                }
+                None => Vec::new(),
+            },
+            None => return,
+        };
+
+        self.answer_api_call(approval.call, &approval.scope);
+        for other in covered {
+            self.answer_api_call(other.call, &other.scope);
+        }
+    }
+}
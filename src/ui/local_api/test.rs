@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{HashMap, VecDeque},
+        io::{BufRead, BufReader, Write},
+        os::unix::net::UnixStream,
+        sync::mpsc,
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use crate::{
+        local_api::{ApiRefusal, ApiResponse, LocalApi},
+        ui::local_api::{ApiState, Approval, Scope},
+    };
+
+    fn temp_path(extension: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("vault-api-{}.{}", uuid::Uuid::new_v4(), extension))
+    }
+
+    #[test]
+    fn stopping_refuses_waiting_calls() {
+        let (socket, token_path) = (temp_path("sock"), temp_path("token"));
+        let (sender, calls) = mpsc::channel();
+        let api = LocalApi::start(&socket, &token_path, sender, || {}).unwrap();
+
+        let client = {
+            let line = format!(
+                "{{\"token\":\"{}\",\"client\":\"deploy\",\"type\":\"list\"}}\n",
+                api.token()
+            );
+            thread::spawn(move || {
+                let mut stream = UnixStream::connect(&socket).unwrap();
+                stream.write_all(line.as_bytes()).unwrap();
+                let mut answer = String::new();
+                BufReader::new(stream).read_line(&mut answer).unwrap();
+                serde_json::from_str::<ApiResponse>(&answer).unwrap()
+            })
+        };
+        let call = calls.recv_timeout(Duration::from_secs(5)).unwrap();
+        let state = ApiState {
+            calls,
+            approvals: VecDeque::from([Approval {
+                scope: Scope::List,
+                call,
+            }]),
+            grants: HashMap::new(),
+            _api: api,
+        };
+
+        let stopping = Instant::now();
+        state.stop();
+        assert!(
+            stopping.elapsed() < Duration::from_secs(5),
+            "\n!!! Stopping waited for the user to answer"
+        );
+        assert_eq!(
+            client.join().unwrap(),
+            ApiRefusal::Locked.into(),
+            "\n!!! The waiting call was not refused"
+        );
+    }
+}
@@ -0,0 +1,297 @@
+use eframe::egui::{self, Key, Modifiers};
+
+use crate::{
+    cryptography::generate_password,
+    data_base::{audit::AuditAction, kind::EntryKind},
+    helpers::fuzzy_score,
+};
+
+use super::{State, Vault, BRIGHT_DEFAULT_GREY, DEFAULT_COLOR};
+
+/// How many matches are listed at once.
+const MAX_MATCHES: usize = 8;
+
+/// Everything the palette can run.
+#[derive(Debug, Clone, PartialEq)]
+enum PaletteAction {
+    CopyPassword(String),
+    CopyUserName(String),
+    OpenEntry(String),
+    GeneratePassword,
+    AddEntry,
+    Lock,
+    ToggleTheme,
+    OpenTrash,
+    OpenAudit,
+    #[cfg(unix)]
+    ToggleSshAgent,
+    #[cfg(unix)]
+    ToggleBrowserBridge,
+    #[cfg(unix)]
+    ToggleLocalApi,
+    #[cfg(target_os = "linux")]
+    ToggleSecretService,
+}
+
+struct PaletteItem {
+    label: String,
+    action: PaletteAction,
+}
+
+/// The state of the open command palette.
+#[derive(Debug, Default)]
+pub(super) struct Palette {
+    query: String,
+    /// The index of the highlighted match.
+    selected: usize,
+    /// The input only gets the focus when the palette opens.
+    focused: bool,
+}
+
+impl Vault {
+    pub(super) fn toggle_palette(&mut self) {
+        self.palette = match self.palette {
+            Some(_) => None,
+            None => Some(Palette::default()),
+        };
+    }
+
+    /// The items of every entry, followed by the other actions. Copying the
+    /// password comes first, so it is picked when only a name was typed.
+    /// Other kinds name the field which takes the place of the password or
+    /// username, and leave it out if they have none.
+    fn palette_items(&self) -> Vec<PaletteItem> {
+        let mut items = Vec::<PaletteItem>::new();
+        for entry in self.repository.sorted(self.settings.sort_mode) {
+            let (id, name, kind) = (entry.id(), entry.name(), entry.kind());
+            if let Some(index) = kind.secret_index() {
+                items.push(PaletteItem {
+                    label: format!(
+                        "Copy {} of {}",
+                        kind.fields()[index].label.to_lowercase(),
+                        name
+                    ),
+                    action: PaletteAction::CopyPassword(id.clone()),
+                });
+            }
+            if let Some(index) = kind.identifier_index() {
+                items.push(PaletteItem {
+                    label: format!(
+                        "Copy {} of {}",
+                        kind.fields()[index].label.to_lowercase(),
+                        name
+                    ),
+                    action: PaletteAction::CopyUserName(id.clone()),
+                });
+            }
+            items.push(PaletteItem {
+                label: format!("Open {}", name),
+                action: PaletteAction::OpenEntry(id),
+            });
+        }
+
+        items.extend([
+            PaletteItem {
+                label: String::from("Generate password"),
+                action: PaletteAction::GeneratePassword,
+            },
+            PaletteItem {
+                label: String::from("Add entry"),
+                action: PaletteAction::AddEntry,
+            },
+            PaletteItem {
+                label: String::from("Lock"),
+                action: PaletteAction::Lock,
+            },
+            PaletteItem {
+                label: String::from("Toggle theme"),
+                action: PaletteAction::ToggleTheme,
+            },
+            PaletteItem {
+                label: String::from("Open Trash"),
+                action: PaletteAction::OpenTrash,
+            },
+            PaletteItem {
+                label: String::from("Open Audit Log"),
+                action: PaletteAction::OpenAudit,
+            },
+        ]);
+        #[cfg(unix)]
+        items.push(PaletteItem {
+            label: match self.settings.ssh_agent {
+                true => String::from("Stop SSH agent"),
+                false => String::from("Start SSH agent"),
+            },
+            action: PaletteAction::ToggleSshAgent,
+        });
+        #[cfg(unix)]
+        items.push(PaletteItem {
+            label: match self.settings.browser_bridge {
+                true => String::from("Turn off browser autofill"),
+                false => String::from("Turn on browser autofill"),
+            },
+            action: PaletteAction::ToggleBrowserBridge,
+        });
+        #[cfg(unix)]
+        items.push(PaletteItem {
+            label: match self.settings.local_api {
+                true => String::from("Stop local API"),
+                false => String::from("Start local API"),
+            },
+            action: PaletteAction::ToggleLocalApi,
+        });
+        #[cfg(target_os = "linux")]
+        items.push(PaletteItem {
+            label: match self.settings.secret_service {
+                true => String::from("Stop Secret Service"),
+                false => String::from("Start Secret Service"),
+            },
+            action: PaletteAction::ToggleSecretService,
+        });
+
+        items
+    }
+
+    /// The best matches of the query, the best one first.
+    fn palette_matches(&self, query: &str) -> Vec<PaletteItem> {
+        let mut scored = self
+            .palette_items()
+            .into_iter()
+            .filter_map(|item| fuzzy_score(query, &item.label).map(|score| (score, item)))
+            .collect::<Vec<(i32, PaletteItem)>>();
+        // the sort is stable, so equal scores keep the order of the items
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+
+        scored
+            .into_iter()
+            .take(MAX_MATCHES)
+            .map(|(_, item)| item)
+            .collect()
+    }
+
+    pub(super) fn show_palette(&mut self, ctx: &egui::Context) {
+        let mut palette = match self.palette.take() {
+            Some(p) => p,
+            None => return,
+        };
+        let matches = self.palette_matches(&palette.query);
+
+        // the keys are taken before the input sees them, which would
+        // otherwise lose its focus on enter
+        let (up, down, enter) = ctx.input_mut(|i| {
+            (
+                i.consume_key(Modifiers::NONE, Key::ArrowUp),
+                i.consume_key(Modifiers::NONE, Key::ArrowDown),
+                i.consume_key(Modifiers::NONE, Key::Enter),
+            )
+        });
+        if up {
+            palette.selected = palette.selected.saturating_sub(1);
+        }
+        if down && palette.selected + 1 < matches.len() {
+            palette.selected += 1;
+        }
+
+        let mut chosen = match enter {
+            true => matches.get(palette.selected).map(|m| m.action.clone()),
+            false => None,
+        };
+
+        egui::Window::new("Command Palette")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .fixed_size([420.0, 0.0])
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 60.0])
+            .show(ctx, |ui| {
+                let input = ui.add(
+                    egui::TextEdit::singleline(&mut palette.query)
+                        .hint_text("Type an entry or a command")
+                        .desired_width(f32::INFINITY),
+                );
+                if !palette.focused {
+                    input.request_focus();
+                    palette.focused = true;
+                }
+                if input.changed() {
+                    palette.selected = 0;
+                }
+                ui.separator();
+
+                if matches.is_empty() {
+                    ui.label(Vault::app_text(
+                        "Nothing found",
+                        14.0,
+                        Some(BRIGHT_DEFAULT_GREY),
+                        self.settings.mode.clone(),
+                    ));
+                }
+                for (index, item) in matches.iter().enumerate() {
+                    let color = match index == palette.selected {
+                        true => Some(DEFAULT_COLOR),
+                        false => None,
+                    };
+                    let text =
+                        Vault::app_text(&item.label, 14.0, color, self.settings.mode.clone());
+                    let label = ui.add(egui::Label::new(text).sense(egui::Sense::click()));
+                    if label.clicked() {
+                        chosen = Some(item.action.clone());
+                    }
+                }
+            });
+
+        match chosen {
+            Some(action) => self.run_palette_action(action),
+            None => self.palette = Some(palette),
+        }
+    }
+
+    fn run_palette_action(&mut self, action: PaletteAction) {
+        match action {
+            PaletteAction::CopyPassword(id) => {
+                self.copy_entry_field(&id, AuditAction::CopiedPassword)
+            }
+            PaletteAction::CopyUserName(id) => {
+                self.copy_entry_field(&id, AuditAction::CopiedUserName)
+            }
+            PaletteAction::OpenEntry(id) => self.open_entry(&id),
+            PaletteAction::GeneratePassword => self.copy_generated_password(),
+            PaletteAction::AddEntry => self.start_adding(),
+            PaletteAction::Lock => self.lock(),
+            PaletteAction::ToggleTheme => self.toggle_mode(),
+            PaletteAction::OpenTrash => self.open_trash(),
+            PaletteAction::OpenAudit => self.open_audit(),
+            #[cfg(unix)]
+            PaletteAction::ToggleSshAgent => self.toggle_ssh_agent(),
+            #[cfg(unix)]
+            PaletteAction::ToggleBrowserBridge => self.toggle_browser_bridge(),
+            #[cfg(unix)]
+            PaletteAction::ToggleLocalApi => self.toggle_local_api(),
+            #[cfg(target_os = "linux")]
+            PaletteAction::ToggleSecretService => self.toggle_secret_service(),
+        }
+    }
+
+    /// Copies a new password to the clipboard. While an entry is added or
+    /// edited, it is also used as its password.
+    fn copy_generated_password(&mut self) {
+        let password = match generate_password(&self.settings.password_rules) {
+            Ok(p) => p,
+            Err(err) => {
+                self.toasts.error(err.toast_message());
+                return;
+            }
+        };
+
+        if matches!(self.state, State::AddingEntry | State::EditEntry)
+            && self.input_kind == EntryKind::Login
+        {
+            self.input_password = password.clone();
+        }
+        let mut clipboard = clippers::Clipboard::get();
+        match clipboard.write_text(password) {
+            Ok(_) => self.toasts.success("Generated password copied"),
+            Err(_) => self.toasts.error("Failed to copy to the clipboard"),
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use std::error::Error;
+
+use eframe::egui::{self, Frame, Layout, Margin};
+
+use crate::{
+    data_base::{
+        recovery::{reset_master, restore_backup, start_fresh},
+        store::VaultStore,
+    },
+    error::InternalError,
+};
+
+use super::{Mode, Vault, BLACK, BRIGHT_BG, BRIGHT_DEFAULT_GREY, RED};
+
+impl Vault {
+    /// Shown instead of the entries when they could not be loaded. Offers to
+    /// open a backup or to start over, both keep the broken file inside the
+    /// data folder.
+    pub(super) fn show_recovery(&mut self, ctx: &egui::Context) {
+        let (code, details) = match &self.load_error {
+            Some(err) => (err.code(), Vault::error_details(err)),
+            None => return,
+        };
+
+        egui::CentralPanel::default()
+            .frame(Frame {
+                inner_margin: Margin::same(20.0),
+                fill: match &self.settings.mode {
+                    Mode::Bright => BRIGHT_BG,
+                    Mode::Dark => BLACK,
+                },
+                ..Default::default()
+            })
+            .show(ctx, |ui| {
+                self.show_error_header(
+                    ui,
+                    "The entries of the vault could not be loaded",
+                    &code,
+                    details,
+                );
+
+                ui.label(Vault::app_text(
+                    "Path of the Backup (.json or .sqlite)",
+                    16.0,
+                    None,
+                    self.settings.mode.clone(),
+                ));
+                ui.with_layout(Layout::left_to_right(egui::Align::Min), |l_ui| {
+                    let input_frame = Vault::line_edit_frame(&self.settings.mode);
+                    input_frame.show(l_ui, |f_ui| {
+                        Vault::single_line_edit(
+                            f_ui,
+                            &mut self.backup_path,
+                            None,
+                            &self.settings.mode,
+                        );
+                    });
+                    let open =
+                        Vault::app_button("Open Backup", 14.0, None, self.settings.mode.clone());
+                    if l_ui.add(open).clicked() && !self.backup_path.is_empty() {
+                        let result = restore_backup(&self.backup_path);
+                        self.finish_recovery(result, "Backup restored");
+                    }
+                });
+                ui.add_space(10.0);
+
+                ui.label(Vault::app_text(
+                    "Starting over keeps the broken file inside the data folder.",
+                    12.0,
+                    Some(BRIGHT_DEFAULT_GREY),
+                    self.settings.mode.clone(),
+                ));
+                let fresh = Vault::app_button(
+                    "Start a fresh Vault",
+                    14.0,
+                    Some(RED),
+                    self.settings.mode.clone(),
+                );
+                if ui.add(fresh).clicked() {
+                    let result = start_fresh();
+                    self.finish_recovery(result, "Started a fresh Vault");
+                }
+            });
+    }
+
+    /// Shown instead of the login when the master file could not be read.
+    /// Offers to set a new master password, which keeps the broken file
+    /// inside the data folder and the entries as they are.
+    pub(super) fn show_master_recovery(&mut self, ctx: &egui::Context) {
+        let (code, details) = match &self.master_error {
+            Some(err) => (err.code(), Vault::error_details(err)),
+            None => return,
+        };
+
+        egui::CentralPanel::default()
+            .frame(Frame {
+                inner_margin: Margin::same(20.0),
+                fill: match &self.settings.mode {
+                    Mode::Bright => BRIGHT_BG,
+                    Mode::Dark => BLACK,
+                },
+                ..Default::default()
+            })
+            .show(ctx, |ui| {
+                self.show_error_header(ui, "The master password could not be read", &code, details);
+
+                ui.label(Vault::app_text(
+                    "A new master password keeps the broken file inside the data folder.",
+                    12.0,
+                    Some(BRIGHT_DEFAULT_GREY),
+                    self.settings.mode.clone(),
+                ));
+                let reset = Vault::app_button(
+                    "Set a new Master Password",
+                    14.0,
+                    Some(RED),
+                    self.settings.mode.clone(),
+                );
+                if ui.add(reset).clicked() {
+                    match reset_master() {
+                        Ok(_) => {
+                            self.master_error = None;
+                            self.first_use = true;
+                            log::info!("Reset the master password, the broken file was kept");
+                        }
+                        Err(err) => self.toasts.error(err.toast_message()),
+                    }
+                }
+            });
+    }
+
+    fn show_error_header(&self, ui: &mut egui::Ui, title: &str, code: &str, details: Vec<String>) {
+        ui.label(Vault::app_text(
+            title,
+            20.0,
+            Some(RED),
+            self.settings.mode.clone(),
+        ));
+        ui.label(Vault::app_text(
+            &format!("Error Code: {}", code),
+            16.0,
+            None,
+            self.settings.mode.clone(),
+        ));
+        for line in details {
+            ui.label(Vault::app_text(
+                &line,
+                12.0,
+                Some(BRIGHT_DEFAULT_GREY),
+                self.settings.mode.clone(),
+            ));
+        }
+        ui.separator();
+    }
+
+    fn finish_recovery(&mut self, result: Result<Box<dyn VaultStore>, InternalError>, done: &str) {
+        match result.and_then(Vault::open_repository) {
+            Ok(repository) => {
+                self.repository = repository;
+                self.load_error = None;
+                self.backup_path.clear();
+                log::info!("{}, the broken file was kept", done);
+                self.toasts
+                    .success(format!("{}, the broken file was kept", done));
+            }
+            Err(err) => {
+                self.toasts.error(err.toast_message());
+            }
+        }
+    }
+
+    /// The message of the error followed by all of its causes.
+    fn error_details(err: &InternalError) -> Vec<String> {
+        let mut details = vec![err.message()];
+        let mut source = err.source().and_then(|s| s.source());
+        while let Some(cause) = source {
+            details.push(format!("Caused by: {}", cause));
+            source = cause.source();
+        }
+
+        details
+    }
+}
@@ -0,0 +1,338 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::mpsc::{self, Receiver},
+};
+
+use eframe::egui;
+
+use crate::{
+    data_base::audit::AuditAction,
+    secret_service::{
+        apply, Access, Catalog, SecretService, ServiceCall, ServiceRefusal, ServiceResponse,
+    },
+};
+
+use super::{utils, Vault, BRIGHT_DEFAULT_GREY, GREEN, RED};
+
+mod test;
+
+/// The Secret Service while it runs, together with the calls it passed on.
+pub(super) struct ServiceState {
+    /// Leaves the bus once it is dropped.
+    service: SecretService,
+    calls: Receiver<ServiceCall>,
+    approvals: Approvals,
+    /// The revision of the entries and the folders which were published
+    /// last, the catalog is only built again once they change.
+    published: Option<(u64, Vec<String>)>,
+}
+
+/// The calls waiting for the user, together with what the user allowed each
+/// app until the vault is locked.
+#[derive(Default)]
+struct Approvals {
+    /// The first one is asked for.
+    waiting: VecDeque<Approval>,
+    /// By the unique name of the connection of each app.
+    grants: HashMap<String, HashSet<Access>>,
+}
+impl Approvals {
+    /// Hands the call back if the app is allowed to make it, otherwise it
+    /// waits for the user.
+    fn admit(&mut self, access: Access, call: ServiceCall) -> Option<Approval> {
+        let granted = call
+            .client
+            .sender
+            .as_ref()
+            .and_then(|sender| self.grants.get(sender))
+            .is_some_and(|g| g.contains(&access));
+        let approval = Approval { access, call };
+        if granted {
+            return Some(approval);
+        }
+        self.waiting.push_back(approval);
+
+        None
+    }
+
+    /// The first call which still waits for the user. The others were given
+    /// up on by the service, so they are dropped.
+    fn next(&mut self) -> Option<Approval> {
+        while let Some(approval) = self.waiting.pop_front() {
+            if !approval.call.reply.is_closed() {
+                return Some(approval);
+            }
+            log::info!(
+                "Secret Service: {} no longer waits",
+                approval.call.client.label
+            );
+        }
+
+        None
+    }
+
+    /// Asks for the call again before any other.
+    fn postpone(&mut self, approval: Approval) {
+        self.waiting.push_front(approval);
+    }
+
+    /// Allows the app the access until the vault is locked, and hands back
+    /// the waiting calls this covers. An unknown app is never remembered.
+    fn grant(&mut self, approval: &Approval) -> Vec<Approval> {
+        let sender = match &approval.call.client.sender {
+            Some(s) => s,
+            None => return Vec::new(),
+        };
+        self.grants
+            .entry(sender.clone())
+            .or_default()
+            .insert(approval.access.clone());
+        let (covered, waiting) = self.waiting.drain(..).partition::<Vec<_>, _>(|a| {
+            a.access == approval.access && a.call.client.sender.as_ref() == Some(sender)
+        });
+        self.waiting = waiting.into();
+
+        covered
+    }
+}
+
+/// A call which needs the approval of the user.
+struct Approval {
+    access: Access,
+    call: ServiceCall,
+}
+
+impl Vault {
+    /// Starts the Secret Service if it is turned on but not running yet, and
+    /// shows the entries as they are now. A failed start turns it off until
+    /// it is turned on again.
+    pub(super) fn sync_secret_service(&mut self, ctx: &egui::Context) {
+        if self.settings.secret_service && self.secret_service.is_none() {
+            let (sender, calls) = mpsc::channel();
+            let wake_ctx = ctx.clone();
+            let wake = move || wake_ctx.request_repaint();
+            match SecretService::start(None, sender, wake) {
+                Ok(service) => {
+                    self.secret_service = Some(ServiceState {
+                        service,
+                        calls,
+                        approvals: Approvals::default(),
+                        published: None,
+                    });
+                }
+                Err(err) => {
+                    log::error!("Failed to start the Secret Service: {:?}", err);
+                    self.settings.secret_service = false;
+                    self.toasts.error(err.toast_message());
+                }
+            }
+        }
+
+        self.publish_entries();
+    }
+
+    /// Only the entries which changed are published again, and the catalog
+    /// is only built once the entries or the folders changed.
+    fn publish_entries(&mut self) {
+        let state = match self.secret_service.as_mut() {
+            Some(s) => s,
+            None => return,
+        };
+        let current = (self.repository.revision(), self.folders.clone());
+        if state.published.as_ref() == Some(&current) {
+            return;
+        }
+        let catalog = Catalog::build(&self.repository, &self.folders);
+        let published = state.service.publish(catalog);
+        state.published = Some(current);
+        if let Err(err) = published {
+            log::error!("Failed to publish the entries: {:?}", err);
+            self.settings.secret_service = false;
+            self.stop_secret_service();
+            self.toasts.error(err.toast_message());
+        }
+    }
+
+    /// Stops the Secret Service, every call still waiting is refused and
+    /// every app has to be allowed again.
+    pub(super) fn stop_secret_service(&mut self) {
+        self.secret_service = None;
+    }
+
+    pub(super) fn toggle_secret_service(&mut self) {
+        self.settings.secret_service = !self.settings.secret_service;
+        if let Err(err) = utils::save_settings(&self.settings) {
+            self.toasts.error(err.toast_message());
+        }
+
+        match self.settings.secret_service {
+            true => self.toasts.info("Secret Service started"),
+            false => {
+                self.stop_secret_service();
+                self.toasts.info("Secret Service stopped");
+            }
+        }
+    }
+
+    /// Answers the calls which the apps are allowed to make, the others wait
+    /// for the user.
+    pub(super) fn poll_secret_service(&mut self) {
+        let calls = match &self.secret_service {
+            Some(state) => state.calls.try_iter().collect::<Vec<_>>(),
+            None => return,
+        };
+
+        for call in calls {
+            let access = call.request.access(&self.repository);
+            let granted = match self.secret_service.as_mut() {
+                Some(state) => state.approvals.admit(access, call),
+                None => {
+                    let _ = call.reply.send_blocking(ServiceRefusal::Locked.into());
+                    continue;
+                }
+            };
+            if let Some(approval) = granted {
+                self.answer_service_call(approval.call, &approval.access);
+            }
+        }
+    }
+
+    /// The entries are published again before the app is answered, so it
+    /// finds the items it added. A call nobody waits for anymore is dropped
+    /// without doing anything.
+    fn answer_service_call(&mut self, call: ServiceCall, access: &Access) {
+        if call.reply.is_closed() {
+            log::info!("Secret Service: {} no longer waits", call.client.label);
+            return;
+        }
+        let response = apply(&mut self.repository, &mut self.folders, &call.request);
+        if matches!(response, ServiceResponse::Collection(_)) {
+            self.save_folders();
+        }
+        self.publish_entries();
+        self.refresh_selected();
+
+        let read = matches!(response, ServiceResponse::Secret(_));
+        if call.reply.send_blocking(response).is_err() {
+            log::info!("Secret Service: {} no longer waits", call.client.label);
+            return;
+        }
+        if let (true, Access::Read(id)) = (read, access) {
+            log::info!(
+                "Secret Service: sent the secret of {} to {}",
+                id,
+                call.client.label
+            );
+            self.repository.audit_for_client(
+                AuditAction::ReadBySecretService,
+                Some(id),
+                &call.client.label,
+            );
+            self.mark_used(id);
+        }
+    }
+
+    /// Asks the user to allow the first waiting call. Allowing it also
+    /// allows the same calls of the app until the vault is locked, unless the
+    /// app is unknown.
+    pub(super) fn show_secret_service_approval(&mut self, ctx: &egui::Context) {
+        let approval = match self.secret_service.as_mut() {
+            Some(state) => match state.approvals.next() {
+                Some(a) => a,
+                None => return,
+            },
+            None => return,
+        };
+
+        let wish = match &approval.access {
+            Access::Read(_) => "read the secret of",
+            Access::Change(_) => "change or delete",
+            Access::Add => "add entries to the vault",
+        };
+        let name = match approval.access.entry() {
+            Some(id) => match self.repository.get(id) {
+                Some(entry) => format!(" \"{}\"", entry.name()),
+                None => format!(" {}", id),
+            },
+            None => String::new(),
+        };
+        let mut open = true;
+        let mut allowed = None;
+        egui::Window::new("Secret Service")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(Vault::app_text(
+                    &format!(
+                        "Allow \"{}\" to {}{}?",
+                        approval.call.client.label, wish, name
+                    ),
+                    16.0,
+                    None,
+                    self.settings.mode.clone(),
+                ));
+                ui.label(Vault::app_text(
+                    match approval.call.client.sender {
+                        Some(_) => "It keeps this access until it quits or the vault is locked",
+                        None => "The app is unknown, so it is asked for every time",
+                    },
+                    12.0,
+                    Some(BRIGHT_DEFAULT_GREY),
+                    self.settings.mode.clone(),
+                ));
+                ui.add_space(15.0);
+                ui.horizontal(|h_ui| {
+                    let allow =
+                        Vault::app_button("Allow", 14.0, Some(GREEN), self.settings.mode.clone());
+                    let deny =
+                        Vault::app_button("Deny", 14.0, Some(RED), self.settings.mode.clone());
+                    if h_ui.add(allow).clicked() {
+                        allowed = Some(true);
+                    }
+                    if h_ui.add(deny).clicked() {
+                        allowed = Some(false);
+                    }
+                });
+            });
+
+        match (allowed, open) {
+            (Some(true), _) => self.grant_service_access(approval),
+            (Some(false), _) | (None, false) => {
+                log::info!(
+                    "Secret Service: denied {:?} to {}",
+                    approval.access,
+                    approval.call.client.label
+                );
+                self.repository.audit_for_client(
+                    AuditAction::RefusedToSecretService,
+                    approval.access.entry(),
+                    &approval.call.client.label,
+                );
+                let _ = approval
+                    .call
+                    .reply
+                    .send_blocking(ServiceRefusal::Denied.into());
+            }
+            (None, true) => {
+                if let Some(state) = self.secret_service.as_mut() {
+                    state.approvals.postpone(approval);
+                }
+            }
+        }
+    }
+
+    /// Answers the call together with all waiting calls the grant covers.
+    fn grant_service_access(&mut self, approval: Approval) {
+        let covered = match self.secret_service.as_mut() {
+            Some(state) => state.approvals.grant(&approval),
+            None => return,
+        };
+
+        self.answer_service_call(approval.call, &approval.access);
+        for other in covered {
+            self.answer_service_call(other.call, &other.access);
+        }
+    }
+}
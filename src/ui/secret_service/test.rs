@@ -0,0 +1,113 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        secret_service::{Access, ServiceCall, ServiceClient, ServiceRequest, ServiceResponse},
+        ui::secret_service::Approvals,
+    };
+
+    /// A call of the app asking for the secret of the entry. The receiver
+    /// stands for the app waiting for the answer.
+    fn call(
+        sender: Option<&str>,
+        entry: &str,
+    ) -> (ServiceCall, async_channel::Receiver<ServiceResponse>) {
+        let (reply, answer) = async_channel::bounded(1);
+        let call = ServiceCall {
+            client: ServiceClient {
+                sender: sender.map(String::from),
+                label: String::from("secret-tool"),
+            },
+            request: ServiceRequest::GetSecret {
+                entry: String::from(entry),
+            },
+            reply,
+        };
+
+        (call, answer)
+    }
+
+    fn read(entry: &str) -> Access {
+        Access::Read(String::from(entry))
+    }
+
+    #[test]
+    fn grants_cover_the_same_calls_of_the_app() {
+        let mut approvals = Approvals::default();
+        let (first, _first) = call(Some(":1.7"), "mail");
+        let (second, _second) = call(Some(":1.7"), "mail");
+        let (other_entry, _other_entry) = call(Some(":1.7"), "bank");
+        let (other_app, _other_app) = call(Some(":1.8"), "mail");
+        assert!(
+            approvals.admit(read("mail"), first).is_none(),
+            "\n!!! A call was answered before the user allowed it"
+        );
+        approvals.admit(read("mail"), second);
+        approvals.admit(read("bank"), other_entry);
+        approvals.admit(read("mail"), other_app);
+
+        let asked = approvals.next().unwrap();
+        let covered = approvals.grant(&asked);
+        assert_eq!(
+            covered.len(),
+            1,
+            "\n!!! The grant covered other calls than the same one of the app"
+        );
+        let left = std::iter::from_fn(|| approvals.next())
+            .map(|a| (a.call.client.sender.unwrap(), a.access))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            left,
+            vec![
+                (String::from(":1.7"), read("bank")),
+                (String::from(":1.8"), read("mail"))
+            ],
+            "\n!!! Calls the grant does not cover stopped waiting"
+        );
+
+        let (again, _again) = call(Some(":1.7"), "mail");
+        assert!(
+            approvals.admit(read("mail"), again).is_some(),
+            "\n!!! The app was asked for again after it was allowed"
+        );
+        let (spoofed, _spoofed) = call(Some(":1.9"), "mail");
+        assert!(
+            approvals.admit(read("mail"), spoofed).is_none(),
+            "\n!!! Another app with the same name took the grant"
+        );
+    }
+
+    #[test]
+    fn unknown_apps_are_always_asked() {
+        let mut approvals = Approvals::default();
+        let (first, _first) = call(None, "mail");
+        approvals.admit(read("mail"), first);
+
+        let asked = approvals.next().unwrap();
+        assert!(
+            approvals.grant(&asked).is_empty(),
+            "\n!!! The grant covered other calls of an unknown app"
+        );
+        let (again, _again) = call(None, "mail");
+        assert!(
+            approvals.admit(read("mail"), again).is_none(),
+            "\n!!! An unknown app was remembered"
+        );
+    }
+
+    #[test]
+    fn calls_nobody_waits_for_are_dropped() {
+        let mut approvals = Approvals::default();
+        let (given_up, answer) = call(Some(":1.7"), "mail");
+        let (waiting, _waiting) = call(Some(":1.7"), "bank");
+        approvals.admit(read("mail"), given_up);
+        approvals.admit(read("bank"), waiting);
+        drop(answer);
+
+        assert_eq!(
+            approvals.next().map(|a| a.access),
+            Some(read("bank")),
+            "\n!!! The user was asked for a call nobody waits for"
+        );
+        assert!(approvals.next().is_none());
+    }
+}
@@ -0,0 +1,414 @@
+use std::collections::BTreeMap;
+
+use eframe::egui::{self, Event, InputState, Key, Modifiers};
+use serde::{Deserialize, Serialize};
+
+use crate::data_base::{audit::AuditAction, EntryDisplay};
+
+use super::{State, Vault, BRIGHT_DEFAULT_GREY};
+
+/// Everything that can be done with the keyboard.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Command {
+    Next,
+    Previous,
+    Open,
+    Add,
+    Edit,
+    CopyPassword,
+    CopyUserName,
+    Delete,
+    Lock,
+    Cancel,
+    Undo,
+    Redo,
+    Palette,
+    Help,
+}
+impl Command {
+    pub const ALL: [Command; 14] = [
+        Command::Next,
+        Command::Previous,
+        Command::Open,
+        Command::Add,
+        Command::Edit,
+        Command::CopyPassword,
+        Command::CopyUserName,
+        Command::Delete,
+        Command::Lock,
+        Command::Cancel,
+        Command::Undo,
+        Command::Redo,
+        Command::Palette,
+        Command::Help,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Command::Next => "Next Entry",
+            Command::Previous => "Previous Entry",
+            Command::Open => "Open Entry",
+            Command::Add => "Add Entry",
+            Command::Edit => "Edit Entry",
+            Command::CopyPassword => "Copy Password",
+            Command::CopyUserName => "Copy Username",
+            Command::Delete => "Delete Entry",
+            Command::Lock => "Lock",
+            Command::Cancel => "Cancel",
+            Command::Undo => "Undo",
+            Command::Redo => "Redo",
+            Command::Palette => "Command Palette",
+            Command::Help => "Show Shortcuts",
+        }
+    }
+
+    /// Text inputs need most keys themselves, so only these commands work
+    /// while one has the focus.
+    fn works_while_typing(&self) -> bool {
+        matches!(self, Command::Cancel | Command::Lock | Command::Palette)
+    }
+}
+
+/// A key together with its modifiers. The key is given by its name, e.g. `N`,
+/// `ArrowDown` or `Escape`.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Binding {
+    pub key: String,
+    /// Ctrl, or Cmd on Mac.
+    #[serde(default)]
+    pub command: bool,
+    #[serde(default)]
+    pub shift: bool,
+}
+impl Binding {
+    fn new(key: Key, command: bool, shift: bool) -> Self {
+        Self {
+            key: String::from(key.name()),
+            command,
+            shift,
+        }
+    }
+
+    pub fn label(&self) -> String {
+        let mut label = String::new();
+        if self.command {
+            label.push_str("Ctrl+");
+        }
+        if self.shift {
+            label.push_str("Shift+");
+        }
+        label.push_str(&self.key);
+
+        label
+    }
+
+    /// Removes the key press from the input if it matches exactly, so it is
+    /// not handled a second time.
+    fn consume(&self, input: &mut InputState) -> bool {
+        let key = match Key::from_name(&self.key) {
+            Some(k) => k,
+            None => return false,
+        };
+        let mut modifiers = Modifiers::NONE;
+        if self.command {
+            modifiers = modifiers.plus(Modifiers::COMMAND);
+        }
+        if self.shift {
+            modifiers = modifiers.plus(Modifiers::SHIFT);
+        }
+
+        // Ctrl+C is turned into a copy event instead of a key press
+        let is_copy = key == Key::C && self.command;
+        let position = input.events.iter().position(|event| match event {
+            Event::Copy => is_copy && input.modifiers.matches_exact(modifiers),
+            Event::Key {
+                key: pressed_key,
+                pressed: true,
+                modifiers: pressed_modifiers,
+                ..
+            } => *pressed_key == key && pressed_modifiers.matches_exact(modifiers),
+            _ => false,
+        });
+
+        match position {
+            Some(index) => {
+                input.events.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// The bindings of all commands, a command can have multiple of them.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(transparent)]
+pub struct Keymap(BTreeMap<Command, Vec<Binding>>);
+impl Keymap {
+    /// The bindings of a command. Commands missing in the settings use their
+    /// default bindings.
+    pub fn bindings(&self, command: Command) -> Vec<Binding> {
+        match self.0.get(&command) {
+            Some(bindings) => bindings.clone(),
+            None => Keymap::default_bindings(command),
+        }
+    }
+
+    fn default_bindings(command: Command) -> Vec<Binding> {
+        match command {
+            Command::Next => vec![
+                Binding::new(Key::ArrowDown, false, false),
+                Binding::new(Key::J, false, false),
+            ],
+            Command::Previous => vec![
+                Binding::new(Key::ArrowUp, false, false),
+                Binding::new(Key::K, false, false),
+            ],
+            Command::Open => vec![Binding::new(Key::Enter, false, false)],
+            Command::Add => vec![Binding::new(Key::N, true, false)],
+            Command::Edit => vec![Binding::new(Key::E, true, false)],
+            Command::CopyPassword => vec![Binding::new(Key::C, true, false)],
+            Command::CopyUserName => vec![Binding::new(Key::C, true, true)],
+            Command::Delete => vec![Binding::new(Key::Delete, false, false)],
+            Command::Lock => vec![Binding::new(Key::L, true, false)],
+            Command::Cancel => vec![Binding::new(Key::Escape, false, false)],
+            Command::Undo => vec![Binding::new(Key::Z, true, false)],
+            Command::Redo => vec![Binding::new(Key::Z, true, true)],
+            Command::Palette => vec![Binding::new(Key::K, true, false)],
+            Command::Help => vec![
+                Binding::new(Key::F1, false, false),
+                Binding::new(Key::Questionmark, false, true),
+            ],
+        }
+    }
+}
+impl Default for Keymap {
+    fn default() -> Self {
+        Self(
+            Command::ALL
+                .iter()
+                .map(|c| (*c, Keymap::default_bindings(*c)))
+                .collect(),
+        )
+    }
+}
+
+impl Vault {
+    /// Runs the command whose binding was pressed, if there is one.
+    pub(super) fn handle_shortcuts(&mut self, ctx: &egui::Context) {
+        let typing = ctx.wants_keyboard_input();
+        let keymap = &self.settings.shortcuts;
+        let triggered = ctx.input_mut(|i| {
+            Command::ALL.into_iter().find(|command| {
+                (!typing || command.works_while_typing())
+                    && keymap.bindings(*command).iter().any(|b| b.consume(i))
+            })
+        });
+
+        if let Some(command) = triggered {
+            self.run_command(command);
+        }
+    }
+
+    pub(super) fn run_command(&mut self, command: Command) {
+        match command {
+            Command::Next => self.move_cursor(1),
+            Command::Previous => self.move_cursor(-1),
+            Command::Open if self.delete_dialog => self.confirm_delete(),
+            Command::Open => {
+                if let Some(id) = self.list_cursor.clone() {
+                    self.open_entry(&id);
+                }
+            }
+            Command::Add => self.start_adding(),
+            Command::Edit => {
+                if self.state == State::DisplayEntry {
+                    self.start_editing();
+                }
+            }
+            Command::CopyPassword => self.copy_field(AuditAction::CopiedPassword),
+            Command::CopyUserName => self.copy_field(AuditAction::CopiedUserName),
+            Command::Delete => {
+                if self.state == State::DisplayEntry {
+                    self.delete_dialog = true;
+                }
+            }
+            Command::Lock => self.lock(),
+            Command::Cancel => self.cancel(),
+            Command::Undo => self.undo(),
+            Command::Redo => self.redo(),
+            Command::Palette => self.toggle_palette(),
+            Command::Help => self.show_help = !self.show_help,
+        }
+    }
+
+    /// Moves the highlighted entry of the list up or down, without opening it.
+    fn move_cursor(&mut self, step: isize) {
+        let ids = self
+            .repository
+            .sorted(self.settings.sort_mode)
+            .into_iter()
+            .map(|e| e.id())
+            .collect::<Vec<String>>();
+        if ids.is_empty() {
+            return;
+        }
+
+        // without a highlighted entry, the open one is the starting point
+        let current = self
+            .list_cursor
+            .as_ref()
+            .or(self.selected_entry.as_ref().map(|e| &e.id))
+            .and_then(|id| ids.iter().position(|i| i == id));
+        let next = match current {
+            Some(index) => (index as isize + step).clamp(0, ids.len() as isize - 1) as usize,
+            None if step < 0 => ids.len() - 1,
+            None => 0,
+        };
+
+        self.list_cursor = Some(ids[next].clone());
+        self.scroll_to_cursor = true;
+    }
+
+    /// Copies the password or username of the open entry, or of the
+    /// highlighted one if none is open.
+    fn copy_field(&mut self, action: AuditAction) {
+        let id = match (&self.selected_entry, &self.list_cursor) {
+            (Some(entry), _) if self.state == State::DisplayEntry => entry.id.clone(),
+            (_, Some(id)) => id.clone(),
+            _ => return,
+        };
+        self.copy_entry_field(&id, action);
+    }
+
+    /// Copies the password or username of an entry to the clipboard. Other
+    /// kinds copy the field which takes their place, e.g. the number of a
+    /// card.
+    pub(super) fn copy_entry_field(&mut self, id: &str, action: AuditAction) {
+        let details = match self.entry_details(id) {
+            Some(details) => details,
+            None => return,
+        };
+
+        let payload = details.payload.expose();
+        let field = match action {
+            AuditAction::CopiedUserName => payload.identifier(),
+            _ => payload.secret(),
+        };
+        match field {
+            Some((label, text)) => {
+                self.write_clipboard(id, text, action, &format!("{} copied", label))
+            }
+            None => self.toasts.info(format!(
+                "{} entries have no {}",
+                payload.kind().label(),
+                match action {
+                    AuditAction::CopiedUserName => "username",
+                    _ => "password",
+                }
+            )),
+        }
+    }
+
+    /// The decrypted entry, errors are shown as a toast.
+    pub(super) fn entry_details(&mut self, id: &str) -> Option<EntryDisplay> {
+        match self.repository.get(id).map(|e| e.get_details()) {
+            Some(Ok(details)) => Some(details),
+            Some(Err(err)) => {
+                self.toasts.error(err.toast_message());
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Copies the text and marks the entry as used.
+    pub(super) fn write_clipboard(
+        &mut self,
+        id: &str,
+        text: &str,
+        action: AuditAction,
+        copied: &str,
+    ) {
+        let mut clipboard = clippers::Clipboard::get();
+        match clipboard.write_text(text) {
+            Ok(_) => {
+                self.repository.audit(action, Some(id));
+                self.mark_used(id);
+                self.toasts.success(copied);
+            }
+            Err(_) => self.toasts.error("Failed to copy to the clipboard"),
+        }
+    }
+
+    /// Closes whatever is open on top, or leaves the form being filled.
+    fn cancel(&mut self) {
+        if self.palette.is_some() {
+            self.palette = None;
+        } else if self.folder_dialog.is_some() {
+            self.folder_dialog = None;
+        } else if self.attachment_dialog.is_some() {
+            self.attachment_dialog = None;
+        } else if self.show_help {
+            self.show_help = false;
+        } else if self.delete_dialog {
+            self.delete_dialog = false;
+        } else if self.purge_dialog.is_some() {
+            self.purge_dialog = None;
+        } else {
+            match self.state {
+                State::EditEntry => self.state = State::DisplayEntry,
+                State::AddingEntry => self.state = State::None,
+                _ => {}
+            }
+        }
+    }
+
+    /// Shows all commands together with their bindings.
+    pub(super) fn show_shortcut_help(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_help;
+        egui::Window::new("Keyboard Shortcuts")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                egui::Grid::new("Shortcut Grid")
+                    .num_columns(2)
+                    .spacing([30.0, 6.0])
+                    .show(ui, |g_ui| {
+                        for command in Command::ALL {
+                            let bindings = self
+                                .settings
+                                .shortcuts
+                                .bindings(command)
+                                .iter()
+                                .map(|b| b.label())
+                                .collect::<Vec<String>>()
+                                .join(", ");
+                            g_ui.label(Vault::app_text(
+                                command.label(),
+                                14.0,
+                                None,
+                                self.settings.mode.clone(),
+                            ));
+                            g_ui.label(Vault::app_text(
+                                &bindings,
+                                14.0,
+                                None,
+                                self.settings.mode.clone(),
+                            ));
+                            g_ui.end_row();
+                        }
+                    });
+                ui.add_space(10.0);
+                ui.label(Vault::app_text(
+                    "The bindings can be changed inside settings.json",
+                    12.0,
+                    Some(BRIGHT_DEFAULT_GREY),
+                    self.settings.mode.clone(),
+                ));
+            });
+        self.show_help = open && self.show_help;
+    }
+}
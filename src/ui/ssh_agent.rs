@@ -0,0 +1,311 @@
+use std::{
+    collections::VecDeque,
+    sync::mpsc::{self, Receiver, Sender},
+};
+
+use eframe::egui;
+
+use crate::{
+    data_base::{
+        audit::AuditAction,
+        kind::{EntryKind, Payload},
+        Entry,
+    },
+    ssh_agent::{AgentKey, AgentRequest, Identity, SshAgent},
+};
+
+use super::{utils, Vault, BRIGHT_DEFAULT_GREY, GREEN, RED};
+
+mod test;
+
+/// The agent while it runs, together with the requests it passed on.
+pub(super) struct AgentState {
+    requests: Receiver<AgentRequest>,
+    /// The signatures waiting for the user, the first one is asked for.
+    confirmations: VecDeque<Confirmation>,
+    /// The keys of the entries, parsed once since decrypting a key with a
+    /// passphrase takes a while.
+    keys: Vec<CachedKey>,
+    /// Stops the agent once it is dropped, which waits for its clients. It
+    /// is dropped last, so no client still waits for an answer by then.
+    _agent: SshAgent,
+}
+impl AgentState {
+    /// Refuses every signature still waiting before the agent stops.
+    fn stop(self) {
+        for confirmation in self.confirmations {
+            let _ = confirmation.reply.send(None);
+        }
+        for request in self.requests.try_iter() {
+            if let AgentRequest::Sign { reply, .. } = request {
+                let _ = reply.send(None);
+            }
+        }
+        drop(self.requests);
+        drop(self._agent);
+    }
+}
+
+/// The key of an entry as it was when it was parsed.
+struct CachedKey {
+    /// Still **encrypted**, to tell if the entry changed since.
+    entry: Entry,
+    /// `None` if the key can not be used.
+    key: Option<AgentKey>,
+}
+
+/// A signature which needs the approval of the user.
+struct Confirmation {
+    id: String,
+    name: String,
+    key: AgentKey,
+    data: Vec<u8>,
+    flags: u32,
+    reply: Sender<Option<Vec<u8>>>,
+}
+
+impl Vault {
+    /// Starts the agent if it is turned on but not running yet. A failed start
+    /// turns it off until it is turned on again.
+    pub(super) fn sync_ssh_agent(&mut self, ctx: &egui::Context) {
+        if !self.settings.ssh_agent || self.ssh_agent.is_some() {
+            return;
+        }
+
+        let (sender, requests) = mpsc::channel();
+        let wake_ctx = ctx.clone();
+        let wake = move || wake_ctx.request_repaint();
+        match SshAgent::start(&self.settings.ssh_agent_socket, sender, wake) {
+            Ok(agent) => {
+                self.ssh_agent = Some(AgentState {
+                    requests,
+                    confirmations: VecDeque::new(),
+                    keys: Vec::new(),
+                    _agent: agent,
+                });
+            }
+            Err(err) => {
+                log::error!("Failed to start the SSH agent: {:?}", err);
+                self.settings.ssh_agent = false;
+                self.toasts.error(err.toast_message());
+            }
+        }
+    }
+
+    /// Stops the agent, every signature still waiting is refused.
+    pub(super) fn stop_ssh_agent(&mut self) {
+        if let Some(state) = self.ssh_agent.take() {
+            state.stop();
+        }
+    }
+
+    pub(super) fn toggle_ssh_agent(&mut self) {
+        self.settings.ssh_agent = !self.settings.ssh_agent;
+        if let Err(err) = utils::save_settings(&self.settings) {
+            self.toasts.error(err.toast_message());
+        }
+
+        match self.settings.ssh_agent {
+            true => self.toasts.info(format!(
+                "SSH agent listening on {}",
+                self.settings.ssh_agent_socket
+            )),
+            false => {
+                self.stop_ssh_agent();
+                self.toasts.info("SSH agent stopped");
+            }
+        }
+    }
+
+    /// Answers the requests of the agent. Signatures wait for the user.
+    pub(super) fn poll_ssh_agent(&mut self) {
+        let requests = match &self.ssh_agent {
+            Some(state) => state.requests.try_iter().collect::<Vec<_>>(),
+            None => return,
+        };
+        if requests.is_empty() {
+            return;
+        }
+        self.refresh_agent_keys();
+
+        for request in requests {
+            match request {
+                AgentRequest::Identities { reply } => {
+                    let identities = self
+                        .agent_keys()
+                        .into_iter()
+                        .map(|(_, name, key)| Identity {
+                            blob: key.blob,
+                            comment: name,
+                        })
+                        .collect();
+                    let _ = reply.send(identities);
+                }
+                AgentRequest::Sign {
+                    blob,
+                    data,
+                    flags,
+                    reply,
+                } => {
+                    let found = self
+                        .agent_keys()
+                        .into_iter()
+                        .find(|(_, _, key)| key.blob == blob);
+                    match (found, self.ssh_agent.as_mut()) {
+                        (Some((id, name, key)), Some(state)) => {
+                            state.confirmations.push_back(Confirmation {
+                                id,
+                                name,
+                                key,
+                                data,
+                                flags,
+                                reply,
+                            });
+                        }
+                        _ => {
+                            let _ = reply.send(None);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Parses the keys of the entries which were added or changed since the
+    /// last time, the others are taken from the cache.
+    fn refresh_agent_keys(&mut self) {
+        let state = match self.ssh_agent.as_mut() {
+            Some(s) => s,
+            None => return,
+        };
+
+        let mut keys = Vec::<CachedKey>::new();
+        for entry in self.repository.list() {
+            if entry.kind() != EntryKind::SshKey {
+                continue;
+            }
+            match state.keys.iter().position(|c| c.entry.same_values(entry)) {
+                Some(index) => keys.push(state.keys.swap_remove(index)),
+                None => keys.push(CachedKey {
+                    entry: entry.clone(),
+                    key: parse_agent_key(entry),
+                }),
+            }
+        }
+        state.keys = keys;
+    }
+
+    /// The keys of all entries together with their ids and names.
+    /// Keys which can not be used are left out.
+    fn agent_keys(&self) -> Vec<(String, String, AgentKey)> {
+        let cached = match &self.ssh_agent {
+            Some(state) => &state.keys,
+            None => return Vec::new(),
+        };
+
+        cached
+            .iter()
+            .filter_map(|c| {
+                let key = c.key.clone()?;
+                Some((c.entry.id(), c.entry.name(), key))
+            })
+            .collect()
+    }
+
+    /// Asks the user to allow the first waiting signature.
+    pub(super) fn show_ssh_agent_confirmation(&mut self, ctx: &egui::Context) {
+        let confirmation = match self.ssh_agent.as_mut() {
+            Some(state) => match state.confirmations.pop_front() {
+                Some(c) => c,
+                None => return,
+            },
+            None => return,
+        };
+
+        let mut open = true;
+        let mut allowed = None;
+        egui::Window::new("SSH Signature")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(Vault::app_text(
+                    &format!("Allow a signature with \"{}\"?", confirmation.name),
+                    16.0,
+                    None,
+                    self.settings.mode.clone(),
+                ));
+                ui.label(Vault::app_text(
+                    "A program connected to the SSH agent wants to use this key",
+                    12.0,
+                    Some(BRIGHT_DEFAULT_GREY),
+                    self.settings.mode.clone(),
+                ));
+                ui.add_space(15.0);
+                ui.horizontal(|h_ui| {
+                    let allow =
+                        Vault::app_button("Allow", 14.0, Some(GREEN), self.settings.mode.clone());
+                    let deny =
+                        Vault::app_button("Deny", 14.0, Some(RED), self.settings.mode.clone());
+                    if h_ui.add(allow).clicked() {
+                        allowed = Some(true);
+                    }
+                    if h_ui.add(deny).clicked() {
+                        allowed = Some(false);
+                    }
+                });
+            });
+
+        match (allowed, open) {
+            (Some(true), _) => {
+                let signature = confirmation
+                    .key
+                    .sign(&confirmation.data, confirmation.flags);
+                if signature.is_none() {
+                    let _ = confirmation.reply.send(None);
+                    self.toasts
+                        .error("The key can not sign what the program asked for");
+                } else if confirmation.reply.send(signature).is_ok() {
+                    self.repository
+                        .audit(AuditAction::Signed, Some(&confirmation.id));
+                    self.mark_used(&confirmation.id);
+                } else {
+                    self.toasts.info("The signature was no longer needed");
+                }
+            }
+            (Some(false), _) | (None, false) => {
+                let _ = confirmation.reply.send(None);
+            }
+            (None, true) => {
+                if let Some(state) = self.ssh_agent.as_mut() {
+                    state.confirmations.push_front(confirmation);
+                }
+            }
+        }
+    }
+}
+
+/// Decrypts and parses the key of the entry. `None` if it can not be used.
+fn parse_agent_key(entry: &Entry) -> Option<AgentKey> {
+    let details = match entry.get_details() {
+        Ok(details) => details,
+        Err(err) => {
+            log::debug!("The key of {} is not served: {:?}", entry.id(), err);
+            return None;
+        }
+    };
+    if let Payload::SshKey {
+        private_key,
+        passphrase,
+        ..
+    } = details.payload.expose()
+    {
+        match AgentKey::parse(private_key, passphrase) {
+            Ok(key) => return Some(key),
+            Err(err) => log::debug!("The key of {} is not served: {:?}", details.id, err),
+        }
+    }
+
+    None
+}
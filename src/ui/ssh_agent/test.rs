@@ -0,0 +1,85 @@
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::VecDeque,
+        io::{Read, Write},
+        os::unix::net::UnixStream,
+        sync::mpsc,
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use ed25519_dalek::SigningKey;
+    use ssh_key::{private::Ed25519Keypair, LineEnding, PrivateKey};
+
+    use crate::{
+        ssh_agent::{put_string, AgentKey, AgentRequest, SshAgent},
+        ui::ssh_agent::{AgentState, Confirmation},
+    };
+
+    fn agent_key() -> AgentKey {
+        let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+        let keypair = Ed25519Keypair::from_bytes(&signing_key.to_keypair_bytes()).unwrap();
+        let key = PrivateKey::from(keypair)
+            .to_openssh(LineEnding::LF)
+            .unwrap();
+
+        AgentKey::parse(&key, "").unwrap()
+    }
+
+    #[test]
+    fn stopping_refuses_waiting_signatures() {
+        let path = std::env::temp_dir().join(format!("vault-agent-{}.sock", uuid::Uuid::new_v4()));
+        let (sender, requests) = mpsc::channel();
+        let agent = SshAgent::start(&path, sender, || {}).unwrap();
+        let key = agent_key();
+
+        let client = {
+            let (path, blob) = (path.clone(), key.blob.clone());
+            thread::spawn(move || {
+                let mut message = vec![13];
+                put_string(&mut message, &blob);
+                put_string(&mut message, b"data");
+                message.extend_from_slice(&0u32.to_be_bytes());
+
+                let mut stream = UnixStream::connect(&path).unwrap();
+                stream
+                    .write_all(&(message.len() as u32).to_be_bytes())
+                    .unwrap();
+                stream.write_all(&message).unwrap();
+                let mut response = [0u8; 5];
+                stream.read_exact(&mut response).unwrap();
+                response[4]
+            })
+        };
+        let reply = match requests.recv_timeout(Duration::from_secs(5)) {
+            Ok(AgentRequest::Sign { reply, .. }) => reply,
+            other => panic!("\n!!! The signature was not asked for: {:?}", other),
+        };
+        let state = AgentState {
+            requests,
+            confirmations: VecDeque::from([Confirmation {
+                id: String::from("id"),
+                name: String::from("server"),
+                key,
+                data: b"data".to_vec(),
+                flags: 0,
+                reply,
+            }]),
+            keys: Vec::new(),
+            _agent: agent,
+        };
+
+        let stopping = Instant::now();
+        state.stop();
+        assert!(
+            stopping.elapsed() < Duration::from_secs(5),
+            "\n!!! Stopping waited for the user to answer"
+        );
+        assert_eq!(
+            client.join().unwrap(),
+            5,
+            "\n!!! The waiting signature was not refused"
+        );
+    }
+}
@@ -0,0 +1,209 @@
+use eframe::egui::{self, Layout};
+
+use super::{Mode, Vault, BRIGHT_DEFAULT_GREY, DEFAULT_COLOR, GREEN, RED};
+
+/// How long info and success toasts are shown.
+const TOAST_MILLIS: i64 = 2000;
+/// How long toasts offering an action are shown, so there is time to use it.
+const ACTION_TOAST_MILLIS: i64 = 5000;
+/// How many toasts are stacked at most. Errors are never dropped to stay
+/// below this.
+const MAX_TOASTS: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Info,
+    Success,
+    Error,
+}
+impl Severity {
+    fn color(&self, mode: &Mode) -> egui::Color32 {
+        match self {
+            Severity::Info => match mode {
+                Mode::Bright => BRIGHT_DEFAULT_GREY,
+                Mode::Dark => DEFAULT_COLOR,
+            },
+            Severity::Success => GREEN,
+            Severity::Error => RED,
+        }
+    }
+}
+
+/// What happens when the button of a toast is clicked.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ToastAction {
+    Undo,
+}
+impl ToastAction {
+    fn label(&self) -> &'static str {
+        match self {
+            ToastAction::Undo => "Undo",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Toast {
+    id: u64,
+    message: String,
+    severity: Severity,
+    /// Errors have no end time, they stay until they are dismissed.
+    end_time: Option<i64>,
+    action: Option<ToastAction>,
+}
+
+/// The queue of the toasts currently shown, the newest one is at the end.
+#[derive(Debug, Default)]
+pub struct Toasts {
+    queue: Vec<Toast>,
+    next_id: u64,
+}
+impl Toasts {
+    pub fn info(&mut self, message: impl Into<String>) {
+        self.push(message.into(), Severity::Info, None);
+    }
+
+    pub fn success(&mut self, message: impl Into<String>) {
+        self.push(message.into(), Severity::Success, None);
+    }
+
+    /// Shows an error, which is written into the log as well.
+    pub fn error(&mut self, message: impl Into<String>) {
+        let message = message.into();
+        log::error!("{}", message);
+        self.push(message, Severity::Error, None);
+    }
+
+    /// Shows a toast with a button which triggers the action.
+    pub fn with_action(&mut self, message: impl Into<String>, action: ToastAction) {
+        self.push(message.into(), Severity::Success, Some(action));
+    }
+
+    /// Removes all toasts offering the action, e.g. when it is not possible
+    /// anymore.
+    pub fn dismiss_action(&mut self, action: ToastAction) {
+        self.queue.retain(|t| t.action != Some(action));
+    }
+
+    fn push(&mut self, message: String, severity: Severity, action: Option<ToastAction>) {
+        let now = chrono::Local::now().timestamp_millis();
+        let end_time = match (severity, action) {
+            (Severity::Error, _) => None,
+            (_, Some(_)) => Some(now + ACTION_TOAST_MILLIS),
+            (_, None) => Some(now + TOAST_MILLIS),
+        };
+
+        self.queue.push(Toast {
+            id: self.next_id,
+            message,
+            severity,
+            end_time,
+            action,
+        });
+        self.next_id += 1;
+
+        while self.queue.len() > MAX_TOASTS {
+            match self
+                .queue
+                .iter()
+                .position(|t| t.severity != Severity::Error)
+            {
+                Some(index) => {
+                    self.queue.remove(index);
+                }
+                None => break,
+            }
+        }
+    }
+
+    fn dismiss(&mut self, id: u64) {
+        self.queue.retain(|t| t.id != id);
+    }
+
+    /// Drops the expired toasts and returns how long it takes until the next
+    /// one expires.
+    fn remove_expired(&mut self) -> Option<i64> {
+        let now = chrono::Local::now().timestamp_millis();
+        self.queue
+            .retain(|t| t.end_time.is_none_or(|end| end > now));
+
+        self.queue
+            .iter()
+            .filter_map(|t| t.end_time)
+            .min()
+            .map(|end| end - now)
+    }
+}
+
+impl Vault {
+    /// Shows the toasts stacked in the bottom right corner, the newest one at
+    /// the bottom.
+    pub(super) fn show_toasts(&mut self, ctx: &egui::Context) {
+        if let Some(remaining) = self.toasts.remove_expired() {
+            ctx.request_repaint_after(std::time::Duration::from_millis(remaining.max(0) as u64));
+        }
+        if self.toasts.queue.is_empty() {
+            return;
+        }
+
+        let mut dismissed = Vec::<u64>::new();
+        let mut triggered = None;
+
+        egui::Area::new(egui::Id::new("Toasts"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, [-10.0, -10.0])
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                ui.with_layout(Layout::bottom_up(egui::Align::Max), |s_ui| {
+                    for toast in self.toasts.queue.iter().rev() {
+                        let color = toast.severity.color(&self.settings.mode);
+                        egui::Frame::popup(s_ui.style())
+                            .stroke(egui::Stroke::new(1.0, color))
+                            .show(s_ui, |f_ui| {
+                                f_ui.set_max_width(320.0);
+                                f_ui.horizontal(|h_ui| {
+                                    h_ui.add(
+                                        egui::Label::new(Vault::app_text(
+                                            &toast.message,
+                                            14.0,
+                                            Some(color),
+                                            self.settings.mode.clone(),
+                                        ))
+                                        .wrap_mode(egui::TextWrapMode::Wrap),
+                                    );
+                                    if let Some(action) = toast.action {
+                                        let button = Vault::app_button(
+                                            action.label(),
+                                            14.0,
+                                            None,
+                                            self.settings.mode.clone(),
+                                        );
+                                        if h_ui.add(button).clicked() {
+                                            triggered = Some(action);
+                                            dismissed.push(toast.id);
+                                        }
+                                    }
+                                    if toast.severity == Severity::Error {
+                                        let close = Vault::app_button(
+                                            "x",
+                                            14.0,
+                                            Some(BRIGHT_DEFAULT_GREY),
+                                            self.settings.mode.clone(),
+                                        );
+                                        if h_ui.add(close).clicked() {
+                                            dismissed.push(toast.id);
+                                        }
+                                    }
+                                });
+                            });
+                    }
+                });
+            });
+
+        for id in dismissed {
+            self.toasts.dismiss(id);
+        }
+        if let Some(ToastAction::Undo) = triggered {
+            self.undo();
+        }
+    }
+}
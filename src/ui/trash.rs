@@ -0,0 +1,188 @@
+use eframe::egui::{self, Layout, Ui};
+
+use crate::helpers::timestamp_as_date;
+
+use super::{utils, State, Vault, BRIGHT_DEFAULT_GREY, GREEN, RED};
+
+impl Vault {
+    /// Deletes the entries which are inside the trash for longer than the
+    /// settings allow. Only done once the user logged in, so nobody else can
+    /// empty the trash by starting the app.
+    pub(super) fn purge_expired_trash(&mut self) {
+        match self.repository.purge_expired(self.settings.trash_days) {
+            Ok(0) => {}
+            Ok(purged) => {
+                log::info!("Purged {} expired entries from the trash", purged);
+                self.toasts
+                    .info(format!("{} entries were deleted from the Trash", purged));
+            }
+            Err(err) => self.toasts.error(err.toast_message()),
+        }
+    }
+
+    pub(super) fn show_trash(&mut self, _ctx: &egui::Context, ui: &mut Ui) {
+        if let Some(id) = self.purge_dialog.clone() {
+            self.show_purge_dialog(ui, &id);
+            return;
+        }
+
+        ui.label(Vault::app_text(
+            "Trash",
+            20.0,
+            None,
+            self.settings.mode.clone(),
+        ));
+        ui.with_layout(Layout::left_to_right(egui::Align::Center), |l_ui| {
+            l_ui.label(Vault::app_text(
+                "Delete entries for good after",
+                12.0,
+                None,
+                self.settings.mode.clone(),
+            ));
+            let days = l_ui.add(
+                egui::DragValue::new(&mut self.settings.trash_days)
+                    .range(utils::TRASH_DAYS)
+                    .suffix(" days"),
+            );
+            if days.lost_focus() || days.drag_stopped() {
+                if let Err(err) = utils::save_settings(&self.settings) {
+                    self.toasts.error(err.toast_message());
+                }
+            }
+        });
+        ui.separator();
+
+        let trash = self
+            .repository
+            .trash()
+            .into_iter()
+            .map(|e| (e.id(), e.name(), e.deleted_at().unwrap_or_default()))
+            .collect::<Vec<(String, String, i64)>>();
+
+        if trash.is_empty() {
+            ui.label(Vault::app_text(
+                "The Trash is empty",
+                14.0,
+                Some(BRIGHT_DEFAULT_GREY),
+                self.settings.mode.clone(),
+            ));
+            return;
+        }
+
+        egui::ScrollArea::vertical()
+            .auto_shrink(false)
+            .show(ui, |sa_ui| {
+                for (id, name, deleted_at) in trash {
+                    sa_ui.label(Vault::app_text(
+                        &name,
+                        18.0,
+                        None,
+                        self.settings.mode.clone(),
+                    ));
+                    let purge_at = deleted_at + i64::from(self.settings.trash_days) * 24 * 60 * 60;
+                    sa_ui.label(Vault::app_text(
+                        &format!(
+                            "Deleted At: {} - Deleted for good at: {}",
+                            timestamp_as_date(deleted_at),
+                            timestamp_as_date(purge_at)
+                        ),
+                        12.0,
+                        None,
+                        self.settings.mode.clone(),
+                    ));
+
+                    let restore =
+                        Vault::app_button("Restore", 14.0, Some(GREEN), self.settings.mode.clone());
+                    let purge = Vault::app_button(
+                        "Delete for good",
+                        14.0,
+                        Some(RED),
+                        self.settings.mode.clone(),
+                    );
+                    sa_ui.with_layout(Layout::left_to_right(egui::Align::Min), |b_ui| {
+                        let restore_button = b_ui.add(restore);
+                        let purge_button = b_ui.add(purge);
+
+                        if restore_button.clicked() {
+                            match self.repository.restore(&id) {
+                                Ok(_) => {
+                                    self.toasts.success("Entry Restored");
+                                    if let Some(details) = self.entry_details(&id) {
+                                        self.selected_entry = Some(details);
+                                        self.state = State::DisplayEntry;
+                                    }
+                                }
+                                Err(err) => {
+                                    self.toasts.error(format!(
+                                        "{}\nPlease try again",
+                                        err.toast_message()
+                                    ));
+                                }
+                            }
+                        }
+                        if purge_button.clicked() {
+                            self.purge_dialog = Some(id.clone());
+                        }
+                    });
+                    sa_ui.separator();
+                }
+            });
+    }
+
+    fn show_purge_dialog(&mut self, ui: &mut Ui, id: &str) {
+        let name = match self.repository.get(id) {
+            Some(entry) => entry.name(),
+            None => {
+                self.purge_dialog = None;
+                return;
+            }
+        };
+
+        let text = Vault::app_text(
+            &format!(
+                "Do you want to delete the Entry \"{}\" for good?\nThis can not be undone.",
+                name
+            ),
+            20.0,
+            None,
+            self.settings.mode.clone(),
+        );
+        let mut pop_up = egui::Frame::popup(&egui::Style::default()).begin(ui);
+        {
+            pop_up.content_ui.label(text);
+            pop_up.content_ui.add_space(30.0);
+            pop_up
+                .content_ui
+                .with_layout(Layout::left_to_right(egui::Align::Min), |ui| {
+                    let confirm =
+                        Vault::app_button("Confirm", 18.0, Some(RED), self.settings.mode.clone());
+                    let cancel = Vault::app_button(
+                        "Cancel",
+                        18.0,
+                        Some(BRIGHT_DEFAULT_GREY),
+                        self.settings.mode.clone(),
+                    );
+
+                    let confirm_button = ui.add(confirm);
+                    let cancel_button = ui.add(cancel);
+
+                    if cancel_button.clicked() {
+                        self.purge_dialog = None;
+                    }
+                    if confirm_button.clicked() {
+                        match self.repository.purge(id) {
+                            Ok(_) => {
+                                self.toasts.success("Entry Deleted");
+                                self.purge_dialog = None;
+                            }
+                            Err(err) => {
+                                self.toasts
+                                    .error(format!("{}\nPlease try again", err.toast_message()));
+                            }
+                        }
+                    }
+                });
+        }
+        pop_up.end(ui);
+    }
+}
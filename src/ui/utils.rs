@@ -1,25 +1,79 @@
 use std::{
     fs::{self, File},
     io::Write,
+    ops::RangeInclusive,
 };
 
 use serde::{Deserialize, Serialize};
 
-use crate::helpers::InternalError;
+use crate::{
+    cryptography::PasswordRules,
+    data_base::repository::SortMode,
+    error::{InternalError, SettingsError},
+    logging::LogLevel,
+};
+
+use super::shortcuts::Keymap;
 
-#[derive(Debug, Deserialize, Serialize, Default)]
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
 pub struct Settings {
     pub mode: super::Mode,
+    /// After how many days entries inside the trash are deleted for good.
+    pub trash_days: u32,
+    pub log_level: LogLevel,
+    /// The keyboard shortcuts, commands missing here use their defaults.
+    pub shortcuts: Keymap,
+    /// Used for generated passwords.
+    pub password_rules: PasswordRules,
+    pub sort_mode: SortMode,
+    /// After how many minutes without any input the vault locks itself, `0`
+    /// never locks it.
+    pub auto_lock_minutes: u32,
+    /// If the SSH agent runs while the vault is unlocked.
+    pub ssh_agent: bool,
+    /// The socket the SSH agent listens on.
+    pub ssh_agent_socket: String,
+    /// If the browser extension can ask for logins while the vault is
+    /// unlocked.
+    pub browser_bridge: bool,
+    /// If scripts can ask for secrets over the local API while the vault is
+    /// unlocked.
+    pub local_api: bool,
+    /// If apps can store and read their passwords through the Secret Service
+    /// while the vault is unlocked.
+    pub secret_service: bool,
+}
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            mode: super::Mode::default(),
+            trash_days: 30,
+            log_level: LogLevel::default(),
+            shortcuts: Keymap::default(),
+            password_rules: PasswordRules::default(),
+            sort_mode: SortMode::default(),
+            auto_lock_minutes: 10,
+            ssh_agent: false,
+            ssh_agent_socket: String::from(SSH_AGENT_SOCKET),
+            browser_bridge: false,
+            local_api: false,
+            secret_service: false,
+        }
+    }
 }
 
+/// How many days entries can be kept inside the trash.
+pub const TRASH_DAYS: RangeInclusive<u32> = 1..=365;
 const SETTINGS_PATH: &str = "./data/settings.json";
+const SSH_AGENT_SOCKET: &str = "./data/ssh-agent.sock";
 
 pub fn save_settings(settings: &Settings) -> Result<(), InternalError> {
     let file = File::options().read(true).write(true).open(SETTINGS_PATH);
 
     let buffer = match serde_json::to_string_pretty(settings) {
         Ok(s) => s,
-        Err(_) => return Err(InternalError::new("[UI_U_SS-1]", "Failed to write buffer")),
+        Err(err) => return Err(SettingsError::Serialize(err).into()),
     };
 
     match file {
@@ -29,7 +83,7 @@ pub fn save_settings(settings: &Settings) -> Result<(), InternalError> {
             f.set_len(0).unwrap();
             match f.write_all(buffer.as_bytes()) {
                 Ok(_) => Ok(()),
-                Err(_) => Err(InternalError::new("[UI_U_SS-2]", "Failed to save buffer")),
+                Err(err) => Err(SettingsError::Write(err).into()),
             }
         }
         Err(_) => {
@@ -42,12 +96,9 @@ pub fn save_settings(settings: &Settings) -> Result<(), InternalError> {
             match new_file {
                 Ok(mut f) => match f.write_all(buffer.as_bytes()) {
                     Ok(_) => Ok(()),
-                    Err(_) => Err(InternalError::new(
-                        "[UI_U_SS-3]",
-                        "Failed to save buffer in new file",
-                    )),
+                    Err(err) => Err(SettingsError::WriteNew(err).into()),
                 },
-                Err(_) => Err(InternalError::new("[UI_U_SS-4]", "Failed to create file")),
+                Err(err) => Err(SettingsError::Create(err).into()),
             }
         }
     }
@@ -59,7 +110,13 @@ pub fn load_settings() -> Settings {
     match file {
         Ok(_) => match fs::read_to_string(SETTINGS_PATH) {
             Ok(s) => match serde_json::from_str::<Settings>(&s) {
-                Ok(settings) => settings,
+                // an edited file could empty the trash at the next login
+                Ok(mut settings) => {
+                    settings.trash_days = settings
+                        .trash_days
+                        .clamp(*TRASH_DAYS.start(), *TRASH_DAYS.end());
+                    settings
+                }
                 Err(_) => Settings::default(),
             },
             Err(_) => Settings::default(),
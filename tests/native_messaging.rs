@@ -0,0 +1,117 @@
+//! Drives the app as a native messaging host like a browser extension would,
+//! with the test taking the place of the unlocked app.
+#![cfg(unix)]
+
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+    sync::mpsc,
+    thread,
+};
+
+use vault::native_messaging::{
+    read_message, write_message, BrowserBridge, Credential, Refusal, Response, HOST_FLAG,
+    MAX_RESPONSE_SIZE,
+};
+
+fn request(host: &mut std::process::Child, message: &str) -> Response {
+    write_message(host.stdin.as_mut().unwrap(), message.as_bytes()).unwrap();
+    let response = read_message(host.stdout.as_mut().unwrap(), MAX_RESPONSE_SIZE)
+        .unwrap()
+        .unwrap();
+
+    serde_json::from_slice(&response).unwrap()
+}
+
+#[test]
+fn extension_gets_logins_only_while_allowed() {
+    let socket = std::env::temp_dir().join(format!("vault-browser-{}.sock", uuid::Uuid::new_v4()));
+    let mut host = Command::new(env!("CARGO_BIN_EXE_vault"))
+        .args([HOST_FLAG, "test-extension"])
+        .env("VAULT_BROWSER_SOCKET", &socket)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    assert_eq!(
+        request(&mut host, r#"{"type":"status"}"#),
+        Response::Status { unlocked: false },
+        "\n!!! The vault is unlocked without the app",
+    );
+    assert_eq!(
+        request(
+            &mut host,
+            r#"{"type":"credentials","origin":"https://github.com"}"#
+        ),
+        Response::Error {
+            error: Refusal::Locked
+        },
+    );
+
+    // allows the first request and denies every other
+    let (sender, receiver) = mpsc::channel();
+    let bridge = BrowserBridge::start(&socket, sender, || {}).unwrap();
+    let app = thread::spawn(move || {
+        let mut allowed = false;
+        for request in receiver {
+            assert_eq!(request.extension, "test-extension");
+            assert_eq!(request.origin, "https://github.com");
+            let credentials = match allowed {
+                true => None,
+                false => Some(vec![Credential {
+                    name: String::from("GitHub"),
+                    username: String::from("user"),
+                    password: String::from("pw"),
+                }]),
+            };
+            allowed = true;
+            request.reply.send(credentials).unwrap();
+        }
+    });
+
+    assert_eq!(
+        request(&mut host, r#"{"type":"status"}"#),
+        Response::Status { unlocked: true },
+    );
+    match request(
+        &mut host,
+        r#"{"type":"credentials","origin":"https://github.com"}"#,
+    ) {
+        Response::Credentials {
+            origin,
+            credentials,
+        } => {
+            assert_eq!(origin, "https://github.com");
+            assert_eq!(credentials.len(), 1, "\n!!! Not exactly one login was sent");
+            assert_eq!(credentials[0].username, "user");
+            assert_eq!(credentials[0].password, "pw");
+        }
+        response => panic!("\n!!! The allowed request got {:?}", response),
+    }
+    assert_eq!(
+        request(
+            &mut host,
+            r#"{"type":"credentials","origin":"https://github.com"}"#
+        ),
+        Response::Error {
+            error: Refusal::Denied
+        },
+        "\n!!! A denied request got logins",
+    );
+    assert_eq!(
+        request(&mut host, "not json"),
+        Response::Error {
+            error: Refusal::InvalidRequest
+        },
+    );
+
+    // the browser closes the input once it is done with the host
+    host.stdin.take().unwrap().flush().unwrap();
+    assert!(
+        host.wait().unwrap().success(),
+        "\n!!! The host did not stop cleanly",
+    );
+    drop(bridge);
+    app.join().unwrap();
+}
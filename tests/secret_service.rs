@@ -0,0 +1,392 @@
+//! Stores and reads a password through the Secret Service like a libsecret
+//! app would, on a private bus, with the test taking the place of the
+//! unlocked app.
+#![cfg(target_os = "linux")]
+
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader},
+    process::{Child, Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use aes::cipher::{block_padding::Pkcs7, BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use aes_gcm::aead::{rand_core::RngCore, OsRng};
+use hkdf::Hkdf;
+use rsa::BigUint;
+use sha2::Sha256;
+use vault::{
+    data_base::{repository::Repository, store::MemoryStore},
+    secret_service::{
+        apply, Catalog, SecretService, ServiceRefusal, ServiceRequest, SERVICE_NAME, SERVICE_PATH,
+    },
+};
+use zbus::{
+    blocking::{Connection, ConnectionBuilder},
+    zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Value},
+    Message,
+};
+
+const SERVICE: &str = "org.freedesktop.Secret.Service";
+const COLLECTION: &str = "org.freedesktop.Secret.Collection";
+const ITEM: &str = "org.freedesktop.Secret.Item";
+const SESSION: &str = "org.freedesktop.Secret.Session";
+const DH_AES: &str = "dh-ietf1024-sha256-aes128-cbc-pkcs7";
+/// The Second Oakley Group of RFC 2409, whose generator is 2.
+const PRIME: &str = "\
+    FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74020BBEA63B139B22514A0879\
+    8E3404DDEF9519B3CD3A431B302B0A6DF25F14374FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B\
+    0BFF5CB6F406B7EDEE386BFB5A899FA5AE9F24117C4B1FE649286651ECE65381FFFFFFFFFFFFFFFF";
+
+type Encryptor = cbc::Encryptor<aes::Aes128>;
+type Decryptor = cbc::Decryptor<aes::Aes128>;
+
+/// The session, parameters, value and content type of a secret.
+type SecretValue = (OwnedObjectPath, Vec<u8>, Vec<u8>, String);
+
+/// A bus of its own, so the test neither needs nor disturbs the session bus
+/// of the user. It needs `dbus-daemon` to be installed.
+fn private_bus() -> (Bus, String) {
+    let socket = std::env::temp_dir().join(format!("vault-bus-{}", uuid::Uuid::new_v4()));
+    let daemon = Command::new("dbus-daemon")
+        .args(["--session", "--nofork", "--print-address"])
+        .arg(format!("--address=unix:path={}", socket.display()))
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("\n!!! dbus-daemon is needed to test the Secret Service");
+    let mut bus = Bus(daemon);
+    let mut address = String::new();
+    if let Some(stdout) = bus.0.stdout.take() {
+        BufReader::new(stdout).read_line(&mut address).unwrap();
+    }
+    assert!(!address.trim().is_empty(), "\n!!! The bus did not start");
+
+    (bus, String::from(address.trim()))
+}
+
+/// Stops the bus even if the test fails, which would otherwise keep the
+/// output of the test open.
+struct Bus(Child);
+impl Drop for Bus {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+fn call(
+    client: &Connection,
+    path: &str,
+    interface: &str,
+    method: &str,
+    body: &(impl serde::Serialize + zbus::zvariant::DynamicType),
+) -> zbus::Result<Arc<Message>> {
+    client.call_method(Some(SERVICE_NAME), path, Some(interface), method, body)
+}
+
+fn error_name(result: zbus::Result<Arc<Message>>) -> String {
+    match result {
+        Err(zbus::Error::MethodError(name, _, _)) => name.to_string(),
+        other => panic!("\n!!! The call did not fail: {:?}", other),
+    }
+}
+
+/// Opens an encrypted session the way libsecret does. Returns its path
+/// together with the key the service agreed on.
+fn open_encrypted_session(client: &Connection) -> (OwnedObjectPath, [u8; 16]) {
+    let prime = BigUint::parse_bytes(PRIME.as_bytes(), 16).unwrap();
+    let mut private = [0u8; 128];
+    OsRng.fill_bytes(&mut private);
+    let private = BigUint::from_bytes_be(&private);
+    let public = BigUint::from(2u32).modpow(&private, &prime);
+    let (output, session): (OwnedValue, OwnedObjectPath) = call(
+        client,
+        SERVICE_PATH,
+        SERVICE,
+        "OpenSession",
+        &(DH_AES, Value::from(public.to_bytes_be())),
+    )
+    .unwrap()
+    .body()
+    .unwrap();
+
+    let service_key = Vec::<u8>::try_from(Value::from(output)).unwrap();
+    let shared = BigUint::from_bytes_be(&service_key)
+        .modpow(&private, &prime)
+        .to_bytes_be();
+    let mut input = vec![0u8; 128 - shared.len()];
+    input.extend_from_slice(&shared);
+    let mut key = [0u8; 16];
+    Hkdf::<Sha256>::new(None, &input)
+        .expand(&[], &mut key)
+        .unwrap();
+
+    (session, key)
+}
+
+#[test]
+fn apps_store_and_read_passwords() {
+    let (_bus, address) = private_bus();
+
+    // applies every request, besides deleting which the user denies
+    let (ready, started) = mpsc::channel();
+    let stop = Arc::new(AtomicBool::new(false));
+    let app = {
+        let (address, stop) = (address.clone(), stop.clone());
+        thread::spawn(move || {
+            let mut repository = Repository::open(Box::new(MemoryStore::new())).unwrap();
+            let mut folders = Vec::<String>::new();
+            let (sender, calls) = mpsc::channel();
+            let mut service = SecretService::start(Some(&address), sender, || {}).unwrap();
+            service
+                .publish(Catalog::build(&repository, &folders))
+                .unwrap();
+            ready.send(()).unwrap();
+
+            while !stop.load(Ordering::Relaxed) {
+                let call = match calls.recv_timeout(Duration::from_millis(50)) {
+                    Ok(c) => c,
+                    Err(_) => continue,
+                };
+                assert!(
+                    call.client.sender.is_some(),
+                    "\n!!! The app is not told apart from others"
+                );
+                let response = match call.request {
+                    ServiceRequest::DeleteItem { .. } => ServiceRefusal::Denied.into(),
+                    _ => apply(&mut repository, &mut folders, &call.request),
+                };
+                service
+                    .publish(Catalog::build(&repository, &folders))
+                    .unwrap();
+                call.reply.send_blocking(response).unwrap();
+            }
+        })
+    };
+    started.recv().unwrap();
+
+    let client = ConnectionBuilder::address(address.as_str())
+        .unwrap()
+        .build()
+        .unwrap();
+    assert_eq!(
+        error_name(call(
+            &client,
+            SERVICE_PATH,
+            SERVICE,
+            "OpenSession",
+            &("dh-ietf2048-sha256-aes256-cbc-pkcs7", Value::from(""))
+        )),
+        "org.freedesktop.DBus.Error.NotSupported",
+        "\n!!! libsecret only falls back to plain sessions on NotSupported",
+    );
+    let (_, session): (OwnedValue, OwnedObjectPath) = call(
+        &client,
+        SERVICE_PATH,
+        SERVICE,
+        "OpenSession",
+        &("plain", Value::from("")),
+    )
+    .unwrap()
+    .body()
+    .unwrap();
+
+    let attributes = HashMap::from([("service", "mail"), ("username", "anna")]);
+    let properties = HashMap::from([
+        ("org.freedesktop.Secret.Item.Label", Value::from("Mail")),
+        (
+            "org.freedesktop.Secret.Item.Attributes",
+            Value::from(attributes.clone()),
+        ),
+    ]);
+    let secret: SecretValue = (
+        session.clone(),
+        Vec::new(),
+        b"hunter2".to_vec(),
+        String::from("text/plain"),
+    );
+    let (item, _): (OwnedObjectPath, OwnedObjectPath) = call(
+        &client,
+        &format!("{}/aliases/default", SERVICE_PATH),
+        COLLECTION,
+        "CreateItem",
+        &(properties, secret, true),
+    )
+    .unwrap()
+    .body()
+    .unwrap();
+
+    let (unlocked, locked): (Vec<OwnedObjectPath>, Vec<OwnedObjectPath>) = call(
+        &client,
+        SERVICE_PATH,
+        SERVICE,
+        "SearchItems",
+        &(HashMap::from([("service", "mail")]),),
+    )
+    .unwrap()
+    .body()
+    .unwrap();
+    assert_eq!(
+        unlocked,
+        vec![item.clone()],
+        "\n!!! The stored item was not found"
+    );
+    assert!(
+        locked.is_empty(),
+        "\n!!! Items are locked while the vault is not"
+    );
+
+    let secrets: HashMap<OwnedObjectPath, SecretValue> = call(
+        &client,
+        SERVICE_PATH,
+        SERVICE,
+        "GetSecrets",
+        &(vec![item.clone()], session.clone()),
+    )
+    .unwrap()
+    .body()
+    .unwrap();
+    assert_eq!(
+        secrets.get(&item).map(|s| s.2.clone()),
+        Some(b"hunter2".to_vec()),
+        "\n!!! The stored password was not sent",
+    );
+
+    assert_eq!(
+        error_name(call(
+            &client,
+            SERVICE_PATH,
+            SERVICE,
+            "OpenSession",
+            &(DH_AES, Value::from(vec![1u8]))
+        )),
+        "org.freedesktop.DBus.Error.InvalidArgs",
+        "\n!!! A session was agreed on with an invalid key",
+    );
+    let (encrypted, key) = open_encrypted_session(&client);
+    let secrets: HashMap<OwnedObjectPath, SecretValue> = call(
+        &client,
+        SERVICE_PATH,
+        SERVICE,
+        "GetSecrets",
+        &(vec![item.clone()], encrypted.clone()),
+    )
+    .unwrap()
+    .body()
+    .unwrap();
+    let (_, iv, value, _) = secrets.get(&item).unwrap();
+    assert_eq!(
+        Decryptor::new(&key.into(), iv.as_slice().into())
+            .decrypt_padded_vec_mut::<Pkcs7>(value)
+            .ok(),
+        Some(b"hunter2".to_vec()),
+        "\n!!! The password was not encrypted with the key of the session",
+    );
+
+    let iv = [9u8; 16];
+    let value =
+        Encryptor::new(&key.into(), &iv.into()).encrypt_padded_vec_mut::<Pkcs7>(b"correct horse");
+    let secret: SecretValue = (
+        encrypted.clone(),
+        iv.to_vec(),
+        value,
+        String::from("text/plain"),
+    );
+    call(&client, item.as_str(), ITEM, "SetSecret", &(secret,)).unwrap();
+    let (_, _, value, _): SecretValue = call(
+        &client,
+        item.as_str(),
+        ITEM,
+        "GetSecret",
+        &(session.clone(),),
+    )
+    .unwrap()
+    .body()
+    .unwrap();
+    assert_eq!(
+        value,
+        b"correct horse".to_vec(),
+        "\n!!! An encrypted password was not decrypted before it was stored",
+    );
+    assert_eq!(
+        error_name(call(
+            &client,
+            item.as_str(),
+            ITEM,
+            "GetSecret",
+            &(ObjectPath::try_from("/org/freedesktop/secrets/session/closed").unwrap(),)
+        )),
+        "org.freedesktop.Secret.Error.NoSession",
+        "\n!!! A secret was sent outside of a session",
+    );
+    let other = ConnectionBuilder::address(address.as_str())
+        .unwrap()
+        .build()
+        .unwrap();
+    assert_eq!(
+        error_name(call(
+            &other,
+            item.as_str(),
+            ITEM,
+            "GetSecret",
+            &(session.clone(),)
+        )),
+        "org.freedesktop.Secret.Error.NoSession",
+        "\n!!! A secret was sent within the session of another app",
+    );
+
+    let (_, closed): (OwnedValue, OwnedObjectPath) = call(
+        &other,
+        SERVICE_PATH,
+        SERVICE,
+        "OpenSession",
+        &("plain", Value::from("")),
+    )
+    .unwrap()
+    .body()
+    .unwrap();
+    drop(other);
+    let mut gone = false;
+    for _ in 0..40 {
+        let result = call(&client, closed.as_str(), SESSION, "Close", &());
+        if error_name(result) == "org.freedesktop.DBus.Error.UnknownObject" {
+            gone = true;
+            break;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    assert!(
+        gone,
+        "\n!!! The session of an app which left the bus is still open"
+    );
+
+    assert_eq!(
+        error_name(call(&client, item.as_str(), ITEM, "Delete", &())),
+        "org.freedesktop.DBus.Error.AccessDenied",
+        "\n!!! The item was deleted without the user allowing it",
+    );
+    let label: OwnedValue = call(
+        &client,
+        item.as_str(),
+        "org.freedesktop.DBus.Properties",
+        "Get",
+        &(ITEM, "Label"),
+    )
+    .unwrap()
+    .body()
+    .unwrap();
+    assert_eq!(
+        String::try_from(label).unwrap(),
+        "Mail",
+        "\n!!! The item is gone or lost its label",
+    );
+
+    stop.store(true, Ordering::Relaxed);
+    app.join().unwrap();
+}